@@ -2,8 +2,12 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use std::hint::black_box;
 
 use dom_query::Document;
-use dom_sanitizer::plugin_policy::{preset, RestrictivePluginPolicy};
+use dom_query::NodeRef;
+use dom_sanitizer::plugin_policy::{
+    preset, NodeChecker, PermissivePluginPolicy, PluginPolicy, RestrictivePluginPolicy,
+};
 use dom_sanitizer::RestrictivePolicy;
+use html5ever::{local_name, LocalName};
 
 fn sanitize_restrictive_policy(contents: &str, policy: &RestrictivePolicy) {
     let doc = Document::from(contents);
@@ -43,5 +47,168 @@ fn bench_restrictive_plugin_policy(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_restrictive, bench_restrictive_plugin_policy);
+fn bench_restrictive_plugin_policy_mixed_cost(c: &mut Criterion) {
+    let contents = include_str!("../test-pages/rustwiki_2024.html");
+    // the expensive checker is added first, but the cheap one is evaluated first
+    let policy = RestrictivePluginPolicy::builder()
+        .exclude(preset::WithinMatcher::new("table.infobox").unwrap())
+        .exclude(preset::LocalNamesMatcher::new(&[
+            "b", "del", "em", "i", "ins", "mark", "s", "small", "strong", "u",
+        ]))
+        .remove(preset::LocalNameMatcher::new("style"))
+        .build();
+    c.bench_function("restrictive_plugin_policy_mixed_cost", |b| {
+        b.iter(|| sanitize_restrictive_plugin_policy(black_box(contents), black_box(&policy)))
+    });
+}
+
+fn sanitize_permissive_plugin_policy(contents: &str, policy: &PermissivePluginPolicy) {
+    let doc = Document::from(contents);
+
+    policy.sanitize_document(&doc);
+}
+
+fn bench_permissive_plugin_policy_attrs_only(c: &mut Criterion) {
+    let contents = include_str!("../test-pages/rustwiki_2024.html");
+    // no node checkers: elements are walked only to sanitize their attributes
+    let policy = PermissivePluginPolicy::builder()
+        .exclude_attr(preset::EventHandlerMatcher)
+        .exclude_attr(preset::AttrMatcher::new(None, &["style", "class"]))
+        .build();
+    c.bench_function("permissive_plugin_policy_attrs_only", |b| {
+        b.iter(|| sanitize_permissive_plugin_policy(black_box(contents), black_box(&policy)))
+    });
+}
+
+const MANY_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+    "var",
+];
+
+/// A `LocalNameMatcher` without a declared scope, consulted for every element.
+struct UnscopedLocalNameMatcher(preset::LocalNameMatcher);
+
+impl NodeChecker for UnscopedLocalNameMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        self.0.is_match(node)
+    }
+}
+
+fn bench_restrictive_plugin_policy_many_checkers(c: &mut Criterion) {
+    let contents = include_str!("../test-pages/rustwiki_2024.html");
+    let scoped: RestrictivePluginPolicy = MANY_ELEMENTS
+        .iter()
+        .fold(PluginPolicy::builder(), |builder, name| {
+            builder.exclude(preset::LocalNameMatcher::new(name))
+        })
+        .build();
+    let unscoped: RestrictivePluginPolicy = MANY_ELEMENTS
+        .iter()
+        .fold(PluginPolicy::builder(), |builder, name| {
+            builder.exclude(UnscopedLocalNameMatcher(preset::LocalNameMatcher::new(
+                name,
+            )))
+        })
+        .build();
+    c.bench_function("restrictive_plugin_policy_many_checkers_scoped", |b| {
+        b.iter(|| sanitize_restrictive_plugin_policy(black_box(contents), black_box(&scoped)))
+    });
+    c.bench_function("restrictive_plugin_policy_many_checkers_unscoped", |b| {
+        b.iter(|| sanitize_restrictive_plugin_policy(black_box(contents), black_box(&unscoped)))
+    });
+}
+
+const HIGHLIGHT_ELEMENTS: &[LocalName] = &[
+    local_name!("b"),
+    local_name!("del"),
+    local_name!("em"),
+    local_name!("i"),
+    local_name!("ins"),
+    local_name!("mark"),
+    local_name!("s"),
+    local_name!("small"),
+    local_name!("strong"),
+    local_name!("u"),
+];
+
+fn bench_policy_construction(c: &mut Criterion) {
+    c.bench_function("policy_construction_builder", |b| {
+        b.iter(|| {
+            RestrictivePolicy::builder()
+                .exclude_elements(black_box(&[
+                    "b", "del", "em", "i", "ins", "mark", "s", "small", "strong", "u",
+                ]))
+                .build()
+        })
+    });
+    c.bench_function("policy_construction_from_static", |b| {
+        b.iter(|| RestrictivePolicy::from_static(black_box(HIGHLIGHT_ELEMENTS)))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_restrictive,
+    bench_restrictive_plugin_policy,
+    bench_restrictive_plugin_policy_mixed_cost,
+    bench_permissive_plugin_policy_attrs_only,
+    bench_policy_construction,
+    bench_restrictive_plugin_policy_many_checkers
+);
 criterion_main!(benches);
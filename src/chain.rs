@@ -0,0 +1,91 @@
+use dom_query::{Document, NodeRef};
+use tendril::StrTendril;
+
+use crate::traits::Sanitizer;
+
+/// A sequence of policies applied one after another over the same document.
+///
+/// Policies may be of different types and directives: e.g. a structural cleanup
+/// [`crate::Policy`], followed by an attribute policy, followed by a
+/// [`crate::plugin_policy::PluginPolicy`].
+///
+/// # Ordering
+///
+/// Policies are applied in the order they were added. Each policy processes the whole
+/// tree (including text normalization) before the next one starts, so a later policy
+/// only sees what was left by the previous ones. Nodes removed by an earlier policy
+/// are never passed to later policies.
+///
+/// # Examples
+///
+/// ```rust
+/// use dom_sanitizer::plugin_policy::{preset, PluginPolicy};
+/// use dom_sanitizer::{AllowAllPolicy, DenyAllPolicy, Permissive, PolicyChain};
+///
+/// let structure = DenyAllPolicy::builder()
+///     .exclude_elements(&["p", "a"])
+///     .exclude_attrs(&["href", "onclick"])
+///     .remove_elements(&["script"])
+///     .build();
+/// let attrs = AllowAllPolicy::builder().exclude_attrs(&["onclick"]).build();
+/// let plugin: PluginPolicy<Permissive> = PluginPolicy::builder()
+///     .exclude(preset::LocalNameMatcher::new("a"))
+///     .build();
+///
+/// let chain = PolicyChain::new().then(&structure).then(&attrs).then(&plugin);
+///
+/// let html = chain.sanitize_html(r#"<div><p onclick="x()"><a href="/">link</a></p><script></script></div>"#);
+/// assert!(html.contains("<p>link</p>"));
+/// ```
+#[derive(Default)]
+pub struct PolicyChain<'a> {
+    policies: Vec<Box<dyn Sanitizer + 'a>>,
+}
+
+impl<'a> PolicyChain<'a> {
+    /// Creates a new empty [`PolicyChain`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a policy to the end of the chain.
+    pub fn then<S: Sanitizer + 'a>(mut self, policy: S) -> Self {
+        self.policies.push(Box::new(policy));
+        self
+    }
+
+    /// Returns the number of policies in the chain.
+    pub fn len(&self) -> usize {
+        self.policies.len()
+    }
+
+    /// Returns `true` if the chain contains no policies.
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Sanitizes a node by applying all policies of the chain in order.
+    pub fn sanitize_node(&self, node: &NodeRef) {
+        for policy in &self.policies {
+            policy.sanitize_node(node);
+        }
+    }
+
+    /// Sanitizes the [`dom_query::Document`] by applying all policies of the chain in order.
+    pub fn sanitize_document(&self, document: &Document) {
+        self.sanitize_node(&document.root());
+    }
+
+    /// Sanitizes the HTML content by applying all policies of the chain in order.
+    pub fn sanitize_html<S: Into<StrTendril>>(&self, html: S) -> StrTendril {
+        let doc = Document::from(html);
+        self.sanitize_document(&doc);
+        doc.html()
+    }
+}
+
+impl Sanitizer for PolicyChain<'_> {
+    fn sanitize_node(&self, node: &NodeRef) {
+        PolicyChain::sanitize_node(self, node);
+    }
+}
@@ -0,0 +1,82 @@
+//! Best-effort filtering of CSS source, e.g. the contents of `style` elements.
+//!
+//! This is not a CSS parser: the source is split into statements and declarations at `;`, `{` and `}`,
+//! and every piece is checked for known dangerous constructs after normalization.
+
+/// Patterns that make a declaration or a statement dangerous. They are searched in the piece
+/// after it was lowercased and stripped of whitespace and control characters.
+const DANGEROUS_PATTERNS: &[&str] = &[
+    "@import",
+    "expression(",
+    "javascript:",
+    "vbscript:",
+    "livescript:",
+    "data:text",
+    "-moz-binding",
+    "behavior:",
+    // CSS escapes may hide any of the above, e.g. `\6a avascript:`
+    "\\",
+    // markup, e.g. `</style>`, would close the `style` element when the output is parsed again
+    "<",
+];
+
+fn is_dangerous(piece: &str) -> bool {
+    let normalized: String = piece
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    DANGEROUS_PATTERNS
+        .iter()
+        .any(|pattern| normalized.contains(pattern))
+}
+
+/// Replaces comments in the CSS source with a space, like CSS tokenization does, so the text around
+/// a comment is never joined (e.g. `</sty/**/le>` into `</style>`).
+/// An unterminated comment lasts until the end of the source.
+fn strip_comments(css: &str) -> String {
+    let mut result = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        result.push(' ');
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Filters the CSS source, removing comments, `@import` statements and declarations containing
+/// `expression()`, script URLs (`javascript:`, `vbscript:`, `data:text/...`), bindings or escapes.
+///
+/// Block delimiters are kept as is, so a block may be left empty. Selectors are kept too,
+/// unless they contain markup (`<`), which is dropped from every piece of the source.
+pub(crate) fn sanitize_css(css: &str) -> String {
+    let css = strip_comments(css);
+    let mut result = String::with_capacity(css.len());
+    let mut rest = css.as_str();
+    while !rest.is_empty() {
+        let end = rest.find([';', '{', '}']).map_or(rest.len(), |i| i + 1);
+        let (piece, tail) = rest.split_at(end);
+        rest = tail;
+        if let Some(selector) = piece.strip_suffix('{') {
+            if !selector.contains('<') {
+                result.push_str(selector);
+            }
+            result.push('{');
+            continue;
+        }
+        let (body, delimiter) = match piece.strip_suffix('}') {
+            Some(body) => (body, "}"),
+            None => (piece, ""),
+        };
+        if !is_dangerous(body) {
+            result.push_str(body);
+        }
+        result.push_str(delimiter);
+    }
+    result
+}
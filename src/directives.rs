@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use dom_query::NodeRef;
 
-use html5ever::local_name;
+use html5ever::{local_name, LocalName};
 
-use crate::dom_helpers::next_child_or_sibling;
+use crate::dom_helpers::{is_misplaced_table_part, is_preformatted, next_child_or_sibling};
+use crate::report::{RemovalKind, SanitizeReport};
 use crate::traits::{SanitizeDirective, SanitizePolicy};
 
 /// A base sanitization directive, which allows all elements and attributes,
@@ -10,40 +13,58 @@ use crate::traits::{SanitizeDirective, SanitizePolicy};
 #[derive(Debug, Clone, Copy)]
 pub struct Permissive;
 
-impl SanitizeDirective for Permissive {
-    /// Removes matching elements from the DOM keeping their children.
-    /// Removes matching attributes from the element node.
-    fn sanitize_node(policy: &impl SanitizePolicy, node: &NodeRef) {
+impl Permissive {
+    fn sanitize_node_inner(
+        policy: &impl SanitizePolicy,
+        node: &NodeRef,
+        mut report: Option<&mut SanitizeReport>,
+    ) {
         if policy.is_empty() {
             return;
         }
-        let mut next_node = node.first_element_child();
-        while let Some(child) = next_node {
-            if policy.should_remove(&child) {
-                next_node = next_child_or_sibling(&child, true, node);
-                child.remove_from_parent();
-                continue;
-            }
+        if !policy.has_element_rules() {
+            // Every element is kept, so only the attributes need to be sanitized.
+            sanitize_attrs_only(
+                node,
+                &|child| Self::sanitize_node_attrs(policy, child),
+                report.as_deref_mut(),
+            );
+        } else {
+            sanitize_children(
+                policy,
+                node,
+                &|child| !policy.should_exclude(child),
+                &|child| Self::sanitize_node_attrs(policy, child),
+                report.as_deref_mut(),
+            );
+        }
+        truncate_content(policy, node, report);
+    }
+}
 
-            next_node = next_child_or_sibling(&child, false, node);
-            if !policy.should_exclude(&child) {
-                Self::sanitize_node_attrs(policy, &child);
-                continue;
-            }
+impl SanitizeDirective for Permissive {
+    /// Removes matching elements from the DOM keeping their children.
+    /// Removes matching attributes from the element node.
+    fn sanitize_node(policy: &impl SanitizePolicy, node: &NodeRef) {
+        Self::sanitize_node_inner(policy, node, None);
+    }
 
-            if let Some(first_inline) = child.first_child() {
-                child.insert_siblings_before(&first_inline);
-            }
-            child.remove_from_parent();
-        }
+    fn sanitize_node_report(
+        policy: &impl SanitizePolicy,
+        node: &NodeRef,
+        report: &mut SanitizeReport,
+    ) {
+        Self::sanitize_node_inner(policy, node, Some(report));
     }
 
     /// Removes matching attributes from the element node.
     fn sanitize_node_attrs(policy: &impl SanitizePolicy, node: &dom_query::NodeRef) {
-        if !policy.has_attrs_to_exclude() {
-            return;
+        policy.prepare_attrs(node);
+        if policy.has_attrs_to_exclude() {
+            policy.exclude_attrs(node, |node, attrs| node.remove_attrs(attrs));
         }
-        policy.exclude_attrs(node, |node, attrs| node.remove_attrs(attrs));
+        strip_attrs(policy, node);
+        policy.rewrite_attrs(node);
     }
 }
 
@@ -54,7 +75,12 @@ pub struct Restrictive;
 
 impl Restrictive {
     /// Checks if the node should be skipped during sanitization and never be removed.
-    fn should_skip(node: &NodeRef) -> bool {
+    ///
+    /// `html`, `head` and `body` are skipped, unless the policy allows stripping them.
+    fn should_skip(policy: &impl SanitizePolicy, node: &NodeRef) -> bool {
+        if policy.allow_strip_structural() {
+            return false;
+        }
         node.qual_name_ref().is_some_and(|qual_name| {
             matches!(
                 qual_name.local,
@@ -62,6 +88,21 @@ impl Restrictive {
             )
         })
     }
+
+    fn sanitize_node_inner(
+        policy: &impl SanitizePolicy,
+        node: &NodeRef,
+        mut report: Option<&mut SanitizeReport>,
+    ) {
+        sanitize_children(
+            policy,
+            node,
+            &|child| Self::should_skip(policy, child) || policy.should_exclude(child),
+            &|child| Self::sanitize_node_attrs(policy, child),
+            report.as_deref_mut(),
+        );
+        truncate_content(policy, node, report);
+    }
 }
 
 impl SanitizeDirective for Restrictive {
@@ -70,35 +111,482 @@ impl SanitizeDirective for Restrictive {
     /// Removes attributes from the element node with exception of
     /// attributes listed in policy.
     fn sanitize_node(policy: &impl SanitizePolicy, node: &NodeRef) {
-        let mut next_node = node.first_element_child();
-        while let Some(child) = next_node {
-            if policy.should_remove(&child) {
-                next_node = next_child_or_sibling(&child, true, node);
-                child.remove_from_parent();
-                continue;
-            }
-
-            next_node = next_child_or_sibling(&child, false, node);
-
-            if Self::should_skip(&child) || policy.should_exclude(&child) {
-                Self::sanitize_node_attrs(policy, &child);
-                continue;
-            }
+        Self::sanitize_node_inner(policy, node, None);
+    }
 
-            if let Some(first_inline) = child.first_child() {
-                child.insert_siblings_before(&first_inline);
-            }
-            child.remove_from_parent();
-        }
+    fn sanitize_node_report(
+        policy: &impl SanitizePolicy,
+        node: &NodeRef,
+        report: &mut SanitizeReport,
+    ) {
+        Self::sanitize_node_inner(policy, node, Some(report));
     }
 
     /// Removes all attributes from the element node with exception of
     /// attributes listed in policy.
     fn sanitize_node_attrs(policy: &impl SanitizePolicy, node: &dom_query::NodeRef) {
+        policy.prepare_attrs(node);
         if !policy.has_attrs_to_exclude() {
             node.remove_all_attrs();
             return;
         }
         policy.exclude_attrs(node, |node, attrs| node.retain_attrs(attrs));
+        strip_attrs(policy, node);
+        policy.rewrite_attrs(node);
+    }
+}
+
+/// Walks the element descendants of `node` in document order and applies the policy:
+/// - elements trusted by [`SanitizePolicy::is_trusted`] are skipped with their children, unchanged;
+/// - elements matching [`SanitizePolicy::should_remove`] are removed with their children;
+/// - elements matching [`SanitizePolicy::should_escape`] are replaced with their markup as text;
+/// - elements for which `should_keep` returns `true` are kept and their attributes are sanitized;
+/// - other elements are removed, keeping their children.
+///
+/// Table parts (`tr`, `td`, `tbody`, etc.) left outside of their table by unwrapping are unwrapped as well,
+/// even if `should_keep` returns `true`: the parser would drop their tags when reading the output.
+///
+/// The contents of kept `template` elements, which live in a separate document fragment,
+/// are sanitized the same way, including the contents of nested templates.
+/// The contents of removed or unwrapped templates are dropped with them.
+///
+/// If the report has a deadline that passed, the walk stops, leaving the rest of the tree unsanitized.
+///
+/// The `node` itself is never modified.
+fn sanitize_children<P, K, A>(
+    policy: &P,
+    node: &NodeRef,
+    should_keep: &K,
+    sanitize_attrs: &A,
+    mut report: Option<&mut SanitizeReport>,
+) where
+    P: SanitizePolicy,
+    K: Fn(&NodeRef) -> bool,
+    A: Fn(&NodeRef),
+{
+    let mut next_node = node.first_element_child();
+    while let Some(child) = next_node {
+        if report
+            .as_deref_mut()
+            .is_some_and(|report| report.is_past_deadline())
+        {
+            return;
+        }
+        if policy.is_trusted(&child) {
+            next_node = next_child_or_sibling(&child, true, node);
+            continue;
+        }
+        policy.rename_element(&child);
+        if policy.should_remove(&child) {
+            next_node = next_child_or_sibling(&child, true, node);
+            if let Some(report) = report.as_deref_mut() {
+                report.record_element(&child, RemovalKind::Removed);
+                if policy.collect_removed_scripts() {
+                    report.capture_raw_text(&child);
+                }
+            }
+            child.remove_from_parent();
+            continue;
+        }
+
+        let force_unwrap = policy.should_unwrap(&child);
+        if !force_unwrap && policy.should_escape(&child) {
+            next_node = next_child_or_sibling(&child, true, node);
+            if let Some(report) = report.as_deref_mut() {
+                report.record_element(&child, RemovalKind::Escaped);
+            }
+            let text = child.tree.new_text(child.html());
+            child.replace_with(&text);
+            continue;
+        }
+
+        next_node = next_child_or_sibling(&child, false, node);
+
+        if !force_unwrap && should_keep(&child) && !is_misplaced_table_part(&child) {
+            sanitize_attrs_reported(&child, sanitize_attrs, report.as_deref_mut());
+            if let Some(contents) = template_contents(&child) {
+                sanitize_children(
+                    policy,
+                    &contents,
+                    should_keep,
+                    sanitize_attrs,
+                    report.as_deref_mut(),
+                );
+            }
+            continue;
+        }
+
+        if let Some(report) = report.as_deref_mut() {
+            report.record_element(&child, RemovalKind::Unwrapped);
+        }
+        policy.before_unwrap(&child);
+        if let Some(first_inline) = child.first_child() {
+            child.insert_siblings_before(&first_inline);
+        }
+        child.remove_from_parent();
+    }
+    limit_elements(policy, node, report.as_deref_mut());
+    prune_empty_elements(policy, node, report.as_deref_mut());
+    collapse_nested_elements(policy, node, report);
+}
+
+/// Walks the element descendants of `node` in document order, only sanitizing their attributes.
+///
+/// This is a fast path for policies without element rules (see [`SanitizePolicy::has_element_rules`])
+/// under the [`Permissive`] directive, where [`sanitize_children`] would keep every element anyway.
+/// The contents of `template` elements are walked as well.
+fn sanitize_attrs_only<A: Fn(&NodeRef)>(
+    node: &NodeRef,
+    sanitize_attrs: &A,
+    mut report: Option<&mut SanitizeReport>,
+) {
+    let mut next_node = node.first_element_child();
+    while let Some(child) = next_node {
+        if report
+            .as_deref_mut()
+            .is_some_and(|report| report.is_past_deadline())
+        {
+            return;
+        }
+        next_node = next_child_or_sibling(&child, false, node);
+        sanitize_attrs_reported(&child, sanitize_attrs, report.as_deref_mut());
+        if let Some(contents) = template_contents(&child) {
+            sanitize_attrs_only(&contents, sanitize_attrs, report.as_deref_mut());
+        }
+    }
+}
+
+/// Sanitizes the attributes of a kept element, counting removed and modified attributes into the report.
+fn sanitize_attrs_reported<A: Fn(&NodeRef)>(
+    node: &NodeRef,
+    sanitize_attrs: &A,
+    report: Option<&mut SanitizeReport>,
+) {
+    let Some(report) = report else {
+        sanitize_attrs(node);
+        return;
+    };
+    let attrs_before = node.attrs();
+    sanitize_attrs(node);
+    let attrs_after = node.attrs();
+    // rules like attribute promotion may add attributes, so the names are compared, not the counts
+    report.removed_attrs += attrs_before
+        .iter()
+        .filter(|b| !attrs_after.iter().any(|a| a.name == b.name))
+        .count();
+    report.modified_attrs += attrs_after
+        .iter()
+        .filter(|a| {
+            attrs_before
+                .iter()
+                .find(|b| b.name == a.name)
+                .map_or(true, |b| b.value != a.value)
+        })
+        .count();
+}
+
+/// Returns the document fragment holding the contents of a `template` element.
+fn template_contents<'a>(node: &NodeRef<'a>) -> Option<NodeRef<'a>> {
+    let contents_id = node.query(|n| n.as_element().and_then(|el| el.template_contents))??;
+    Some(NodeRef::new(contents_id, node.tree))
+}
+
+/// Removes attributes matched by [`SanitizePolicy::should_strip_attr`] from the node.
+fn strip_attrs(policy: &impl SanitizePolicy, node: &NodeRef) {
+    if !policy.has_attrs_to_strip() {
+        return;
+    }
+    let node_attrs = node.attrs();
+    let attrs: Vec<&str> = node_attrs
+        .iter()
+        .map(|a| a.name.local.as_ref())
+        .filter(|name| policy.should_strip_attr(node, name))
+        .collect();
+    if !attrs.is_empty() {
+        node.remove_attrs(&attrs);
+    }
+}
+
+/// Removes descendant elements, with their children, once their number exceeds the limit
+/// of [`SanitizePolicy::element_limit`]. Elements are counted per name in document order,
+/// so the first ones are kept.
+fn limit_elements(
+    policy: &impl SanitizePolicy,
+    node: &NodeRef,
+    mut report: Option<&mut SanitizeReport>,
+) {
+    if !policy.has_element_limits() {
+        return;
+    }
+    let mut counts: HashMap<LocalName, usize> = HashMap::new();
+    let mut next_node = node.first_element_child();
+    while let Some(child) = next_node {
+        if policy.is_trusted(&child) {
+            next_node = next_child_or_sibling(&child, true, node);
+            continue;
+        }
+        let limit = policy.element_limit(&child);
+        let name = child
+            .qual_name_ref()
+            .map(|qual_name| qual_name.local.clone());
+        let Some((limit, name)) = limit.zip(name) else {
+            next_node = next_child_or_sibling(&child, false, node);
+            continue;
+        };
+        let count = counts.entry(name).or_default();
+        if *count < limit {
+            *count += 1;
+            next_node = next_child_or_sibling(&child, false, node);
+            continue;
+        }
+        next_node = next_child_or_sibling(&child, true, node);
+        if let Some(report) = report.as_deref_mut() {
+            report.record_element(&child, RemovalKind::Removed);
+        }
+        child.remove_from_parent();
+    }
+}
+
+/// Returns `true` if the node is one of the structural elements: `html`, `head` or `body`.
+fn is_structural(node: &NodeRef) -> bool {
+    node.qual_name_ref().is_some_and(|qual_name| {
+        matches!(
+            qual_name.local,
+            local_name!("html") | local_name!("head") | local_name!("body")
+        )
+    })
+}
+
+/// Applies the document-wide limits of the policy ([`SanitizePolicy::max_elements`] and
+/// [`SanitizePolicy::max_total_text`]) to the descendants of `node`, once its whole subtree was sanitized.
+///
+/// The contents of `template` elements are measured in place, as if they were the children
+/// of the template, so the limits apply to the document as serialized.
+fn truncate_content(
+    policy: &impl SanitizePolicy,
+    node: &NodeRef,
+    mut report: Option<&mut SanitizeReport>,
+) {
+    if let Some(max) = policy.max_elements() {
+        truncate_elements(policy, node, &mut 0, max, report.as_deref_mut());
+    }
+    if let Some(max) = policy.max_total_text() {
+        truncate_text(node, &mut 0, max, report);
+    }
+}
+
+/// Keeps the descendant elements of `node` in document order while `count` is below `max`,
+/// and removes the first element over the limit together with all the nodes following it.
+/// Returns `true` if the content was truncated.
+///
+/// Structural elements are not counted. Trusted subtrees count as one element.
+fn truncate_elements(
+    policy: &impl SanitizePolicy,
+    node: &NodeRef,
+    count: &mut usize,
+    max: usize,
+    mut report: Option<&mut SanitizeReport>,
+) -> bool {
+    let mut next_node = node.first_element_child();
+    while let Some(child) = next_node {
+        if is_structural(&child) {
+            next_node = next_child_or_sibling(&child, false, node);
+            continue;
+        }
+        if *count < max {
+            *count += 1;
+            let is_trusted = policy.is_trusted(&child);
+            if let Some(contents) = template_contents(&child).filter(|_| !is_trusted) {
+                if truncate_elements(policy, &contents, count, max, report.as_deref_mut()) {
+                    remove_following(&child, node, report);
+                    return true;
+                }
+            }
+            next_node = next_child_or_sibling(&child, is_trusted, node);
+            continue;
+        }
+        truncate_from(&child, node, report);
+        return true;
+    }
+    false
+}
+
+/// Keeps the text of `node` in document order while `total` is below `max` bytes, cutting the text node
+/// crossing the limit at a character boundary and removing all the nodes following it.
+/// Returns `true` if the content was truncated.
+fn truncate_text(
+    node: &NodeRef,
+    total: &mut usize,
+    max: usize,
+    mut report: Option<&mut SanitizeReport>,
+) -> bool {
+    let nodes: Vec<NodeRef> = node
+        .descendants_it()
+        .filter(|n| n.is_text() || n.has_name("template"))
+        .collect();
+    for current in nodes {
+        if let Some(contents) = template_contents(&current) {
+            if truncate_text(&contents, total, max, report.as_deref_mut()) {
+                remove_following(&current, node, report);
+                return true;
+            }
+            continue;
+        }
+        if !current.is_text() {
+            continue;
+        }
+        let text = current.text();
+        if *total + text.len() <= max {
+            *total += text.len();
+            continue;
+        }
+        let mut end = max - *total;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        remove_following(&current, node, report);
+        if end == 0 {
+            current.remove_from_parent();
+        } else {
+            current.update(|n| {
+                if let dom_query::NodeData::Text { ref mut contents } = n.data {
+                    *contents = text[..end].into();
+                }
+            });
+        }
+        return true;
+    }
+    false
+}
+
+/// Removes the `cut` node and every node following it in document order, up to the end of `root`.
+/// Structural elements following it are kept, but their children are removed.
+fn truncate_from(cut: &NodeRef, root: &NodeRef, mut report: Option<&mut SanitizeReport>) {
+    remove_following(cut, root, report.as_deref_mut());
+    if let Some(report) = report {
+        report.record_element(cut, RemovalKind::Removed);
+    }
+    cut.remove_from_parent();
+}
+
+/// Removes every node following `node` in document order (not its descendants), up to the end of `root`.
+/// Structural elements following it are kept, but their children are removed.
+fn remove_following(node: &NodeRef, root: &NodeRef, mut report: Option<&mut SanitizeReport>) {
+    let mut remove = |node: &NodeRef| {
+        if let Some(report) = report.as_deref_mut() {
+            report.record_element(node, RemovalKind::Removed);
+        }
+        node.remove_from_parent();
+    };
+    let mut current = *node;
+    while current.id != root.id {
+        let mut next_sibling = current.next_sibling();
+        while let Some(sibling) = next_sibling {
+            next_sibling = sibling.next_sibling();
+            if is_structural(&sibling) {
+                for child in sibling.children() {
+                    remove(&child);
+                }
+            } else {
+                remove(&sibling);
+            }
+        }
+        let Some(parent) = current.parent() else {
+            break;
+        };
+        current = parent;
+    }
+}
+
+/// Removes descendant elements matched by [`SanitizePolicy::should_prune`] that have no meaningful
+/// content: no child elements and no text except whitespace (see [`is_empty_content`]).
+///
+/// Candidates are visited in reverse document order, so children are always evaluated
+/// before their parents, and a wrapper that becomes empty after its children were pruned
+/// is pruned as well.
+fn prune_empty_elements(
+    policy: &impl SanitizePolicy,
+    node: &NodeRef,
+    mut report: Option<&mut SanitizeReport>,
+) {
+    if !policy.has_elements_to_prune() {
+        return;
+    }
+    let candidates: Vec<NodeRef> = node
+        .descendants_it()
+        .filter(|n| policy.should_prune(n) && !is_within_trusted(policy, n))
+        .collect();
+    for candidate in candidates.iter().rev() {
+        if is_empty_content(candidate) {
+            if let Some(report) = report.as_deref_mut() {
+                report.record_element(candidate, RemovalKind::Removed);
+            }
+            candidate.remove_from_parent();
+        }
+    }
+}
+
+/// Checks if the node or one of its ancestors is trusted by the policy (see [`SanitizePolicy::is_trusted`]).
+fn is_within_trusted(policy: &impl SanitizePolicy, node: &NodeRef) -> bool {
+    policy.has_trust_marker()
+        && (policy.is_trusted(node) || node.ancestors_it(None).any(|a| policy.is_trusted(&a)))
+}
+
+/// Returns `true` if the element has no meaningful content: no child elements and no text except whitespace.
+/// In preformatted content (see [`is_preformatted`]), whitespace is meaningful as well.
+fn is_empty_content(node: &NodeRef) -> bool {
+    node.is_empty_element()
+        && !(node.children_it(false).any(|child| child.is_text()) && is_preformatted(node))
+}
+
+/// Returns the only child element of the node with the same qualified name,
+/// ignoring whitespace-only text around it, unless the node is preformatted;
+/// `None` if there are other children.
+fn only_same_name_child<'a>(node: &NodeRef<'a>) -> Option<NodeRef<'a>> {
+    let keep_whitespace = is_preformatted(node);
+    let mut only_child = None;
+    for child in node.children_it(false) {
+        if !keep_whitespace
+            && child.is_text()
+            && child.text().chars().all(|c| c.is_ascii_whitespace())
+        {
+            continue;
+        }
+        if only_child.is_some() || !child.is_element() {
+            return None;
+        }
+        only_child = Some(child);
+    }
+    let only_child = only_child?;
+    let is_same_name = *only_child.qual_name_ref()? == *node.qual_name_ref()?;
+    is_same_name.then_some(only_child)
+}
+
+/// Collapses descendant elements matched by [`SanitizePolicy::should_collapse`] whose only child
+/// is an element with the same name, by unwrapping them.
+///
+/// Candidates are visited in reverse document order, so a chain of nested wrappers
+/// collapses from the inside out, leaving only the innermost element.
+fn collapse_nested_elements(
+    policy: &impl SanitizePolicy,
+    node: &NodeRef,
+    mut report: Option<&mut SanitizeReport>,
+) {
+    if !policy.has_elements_to_collapse() {
+        return;
+    }
+    let candidates: Vec<NodeRef> = node
+        .descendants_it()
+        .filter(|n| policy.should_collapse(n) && !is_within_trusted(policy, n))
+        .collect();
+    for candidate in candidates.iter().rev() {
+        let Some(child) = only_same_name_child(candidate) else {
+            continue;
+        };
+        if let Some(report) = report.as_deref_mut() {
+            report.record_element(candidate, RemovalKind::Unwrapped);
+        }
+        candidate.insert_before(&child);
+        candidate.remove_from_parent();
     }
 }
@@ -1,4 +1,5 @@
 use dom_query::NodeRef;
+use html5ever::{local_name, ns};
 
 pub(crate) fn next_child_or_sibling<'a>(
     node: &NodeRef<'a>,
@@ -26,3 +27,165 @@ pub(crate) fn next_child_or_sibling<'a>(
     }
     None
 }
+
+fn is_html_element(node: &NodeRef, name: html5ever::LocalName) -> bool {
+    node.qual_name_ref()
+        .is_some_and(|qual_name| qual_name.ns == ns!(html) && qual_name.local == name)
+}
+
+/// Returns `true` if whitespace is significant in the node: it is, or is inside, an HTML element
+/// whose text is rendered as is (`pre`, `textarea`, `xmp`, `listing` or `plaintext`),
+/// e.g. a `code` inside `pre`.
+pub(crate) fn is_preformatted(node: &NodeRef) -> bool {
+    std::iter::once(*node)
+        .chain(node.ancestors_it(None))
+        .any(|n| {
+            n.qual_name_ref().is_some_and(|qual_name| {
+                qual_name.ns == ns!(html)
+                    && matches!(
+                        qual_name.local,
+                        local_name!("pre")
+                            | local_name!("textarea")
+                            | local_name!("xmp")
+                            | local_name!("listing")
+                            | local_name!("plaintext")
+                    )
+            })
+        })
+}
+
+/// Returns `true` if the node is a table part (`tr`, `td`, `tbody`, etc.) whose parent element
+/// can't contain it, e.g. because the `table` around it was unwrapped.
+/// The parser ignores the tags of such elements, so they can't survive serialization.
+///
+/// Nodes whose parent is not an element (e.g. the contents of a `template`) are never misplaced.
+pub(crate) fn is_misplaced_table_part(node: &NodeRef) -> bool {
+    let Some(qual_name) = node.qual_name_ref() else {
+        return false;
+    };
+    if qual_name.ns != ns!(html) {
+        return false;
+    }
+    let allowed_parents: &[html5ever::LocalName] = match qual_name.local {
+        local_name!("caption")
+        | local_name!("colgroup")
+        | local_name!("thead")
+        | local_name!("tbody")
+        | local_name!("tfoot") => &[local_name!("table")],
+        local_name!("col") => &[local_name!("colgroup"), local_name!("table")],
+        local_name!("tr") => &[
+            local_name!("table"),
+            local_name!("thead"),
+            local_name!("tbody"),
+            local_name!("tfoot"),
+        ],
+        local_name!("td") | local_name!("th") => &[local_name!("tr")],
+        _ => return false,
+    };
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    let Some(parent_name) = parent.qual_name_ref() else {
+        return false;
+    };
+    parent_name.ns != ns!(html) || !allowed_parents.contains(&parent_name.local)
+}
+
+/// Returns `true` if the parser would not keep the node inside `head`
+/// (or inside `noscript` in `head`, if `in_noscript`): text other than whitespace
+/// and elements other than metadata close `head` and go to `body`.
+fn is_misplaced_in_head(node: &NodeRef, in_noscript: bool) -> bool {
+    if node.is_text() {
+        return !node.text().chars().all(|c| c.is_ascii_whitespace());
+    }
+    node.qual_name_ref().is_some_and(|qual_name| {
+        if qual_name.ns != ns!(html) {
+            return true;
+        }
+        if in_noscript {
+            return !matches!(
+                qual_name.local,
+                local_name!("link")
+                    | local_name!("meta")
+                    | local_name!("noframes")
+                    | local_name!("style")
+            );
+        }
+        !matches!(
+            qual_name.local,
+            local_name!("base")
+                | local_name!("basefont")
+                | local_name!("bgsound")
+                | local_name!("link")
+                | local_name!("meta")
+                | local_name!("noframes")
+                | local_name!("noscript")
+                | local_name!("script")
+                | local_name!("style")
+                | local_name!("template")
+                | local_name!("title")
+        )
+    })
+}
+
+/// Returns the first node the parser would not keep in `parent` and all nodes after it,
+/// splitting the leading whitespace off a text node, which stays in place.
+fn split_misplaced<'a>(parent: &NodeRef<'a>, in_noscript: bool) -> Option<Vec<NodeRef<'a>>> {
+    let first = parent
+        .children_it(false)
+        .find(|child| is_misplaced_in_head(child, in_noscript))?;
+    if first.is_text() {
+        let text = first.text();
+        let ws_len = text.len()
+            - text
+                .trim_start_matches(|c: char| c.is_ascii_whitespace())
+                .len();
+        if ws_len > 0 {
+            let ws = parent.tree.new_text(&text[..ws_len]);
+            first.insert_before(&ws);
+            first.set_text(&text[ws_len..]);
+        }
+    }
+    Some(std::iter::successors(Some(first), |n| n.next_sibling()).collect())
+}
+
+/// Moves the content of `head` that the parser would not keep there to the beginning of `body`,
+/// together with everything after it, as the parser does when it reads the serialized document.
+///
+/// Unwrapping an element inside `head` (e.g. `style` under the [`crate::Restrictive`] directive)
+/// leaves its text there, so without this, sanitizing the output again would move it and produce a different document.
+pub(crate) fn move_misplaced_head_content(node: &NodeRef) {
+    let heads: Vec<NodeRef> = std::iter::once(*node)
+        .chain(node.descendants_it())
+        .filter(|n| is_html_element(n, local_name!("head")))
+        .collect();
+    for head in heads {
+        let Some(body) = head
+            .next_element_sibling()
+            .filter(|n| is_html_element(n, local_name!("body")))
+        else {
+            continue;
+        };
+        let mut misplaced = Vec::new();
+        for child in head.children() {
+            if !misplaced.is_empty() {
+                misplaced.push(child);
+            } else if is_html_element(&child, local_name!("noscript")) {
+                // content after the misplaced node inside `noscript` goes to `body` as well
+                if let Some(nodes) = split_misplaced(&child, true) {
+                    misplaced = nodes;
+                }
+            } else if is_misplaced_in_head(&child, false) {
+                misplaced = split_misplaced(&head, false).unwrap_or_default();
+                break;
+            }
+        }
+        let first_body_child = body.first_child();
+        for misplaced_node in misplaced {
+            match first_body_child {
+                Some(ref first_body_child) => first_body_child.insert_before(&misplaced_node),
+                None => body.append_child(&misplaced_node),
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+use std::time::Duration;
+
+use dom_query::Matcher;
+
+/// An error returned when a CSS selector can't be compiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    /// The selector that failed to compile.
+    pub selector: String,
+    /// The 1-based column of the position where the parsing failed.
+    pub column: u32,
+    /// A description of the failure.
+    pub message: String,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid selector `{}` at column {}: {}",
+            self.selector, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+/// An error returned when an attribute matcher string can't be parsed,
+/// see [`crate::plugin_policy::preset::AttrMatcher::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrMatcherError {
+    /// The input that failed to parse.
+    pub input: String,
+    /// The 1-based column (in characters) of the position where the parsing failed.
+    pub column: u32,
+    /// A human-readable description of the failure.
+    pub message: String,
+}
+
+impl fmt::Display for AttrMatcherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid attribute matcher `{}` at column {}: {}",
+            self.input, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for AttrMatcherError {}
+
+/// An error returned when sanitization didn't finish within the time limit.
+///
+/// The document is left partially sanitized and must not be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeoutError {
+    /// The time limit that was exceeded.
+    pub timeout: Duration,
+}
+
+impl fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sanitization timed out after {:?}", self.timeout)
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// Compiles the CSS selector into a [`Matcher`].
+pub(crate) fn compile_selector(selector: &str) -> Result<Matcher, SelectorError> {
+    Matcher::new(selector).map_err(|err| SelectorError {
+        selector: selector.to_string(),
+        column: err.location.column,
+        message: format!("{:?}", err.kind),
+    })
+}
@@ -0,0 +1,44 @@
+use dom_query::Document;
+use html5ever::tendril::TendrilSink;
+use html5ever::tree_builder::TreeBuilderOpts;
+use html5ever::{ns, parse_fragment, LocalName, ParseOpts, QualName};
+use tendril::StrTendril;
+
+/// Parses the HTML fragment with the HTML fragment parsing algorithm, as if it was
+/// the contents of a `context` element (e.g. `table`).
+///
+/// The parsed nodes are placed inside an element named after the context, which is
+/// the first element child of the document root.
+pub(crate) fn parse_fragment_in_context<S: Into<StrTendril>>(html: S, context: &str) -> Document {
+    let context_name = QualName::new(
+        None,
+        ns!(html),
+        LocalName::from(context.to_ascii_lowercase()),
+    );
+    let opts = ParseOpts {
+        tokenizer: Default::default(),
+        tree_builder: TreeBuilderOpts {
+            scripting_enabled: false,
+            drop_doctype: true,
+            ..Default::default()
+        },
+    };
+    let doc = parse_fragment(
+        Document::fragment_sink(),
+        opts,
+        context_name.clone(),
+        Vec::new(),
+        false,
+    )
+    .one(html);
+    // the parser puts the fragment into an `html` element, standing in for the context;
+    // `NodeRef::rename` would drop the HTML namespace, so the name is replaced as a whole
+    if let Some(wrapper) = doc.root().first_element_child() {
+        wrapper.update(|n| {
+            if let Some(el) = n.as_element_mut() {
+                el.name = context_name;
+            }
+        });
+    }
+    doc
+}
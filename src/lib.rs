@@ -1,17 +1,69 @@
 //! Flexible HTML sanitization for Rust — build policies and sanitize documents easily.
+//!
+//! For one-shot sanitization of an HTML string, build a policy and pass it to [`clean`].
 
 #![doc = include_str!("../Examples.md")]
 
+pub mod chain;
+mod css;
 pub mod directives;
 mod dom_helpers;
+pub mod error;
+mod fragment;
 pub mod plugin_policy;
 pub mod policy;
+mod positions;
+mod pretty;
 /// Re-exports of commonly used types from dependencies for convenience.
 pub mod re_exports;
+pub mod registry;
+pub mod report;
+pub mod serialize;
 pub mod traits;
+pub mod transform;
+pub mod url;
 
 pub(crate) mod macros;
 
+#[doc(inline)]
+pub use chain::PolicyChain;
 #[doc(inline)]
 pub use directives::{Permissive, Restrictive};
 pub use policy::*;
+#[doc(inline)]
+pub use registry::PolicyRegistry;
+#[doc(inline)]
+pub use report::SanitizeReport;
+#[doc(inline)]
+pub use serialize::SerializeOptions;
+
+use tendril::StrTendril;
+
+use crate::traits::Sanitizer;
+
+/// Parses the HTML, sanitizes it with the policy and returns the serialized document.
+///
+/// The policy may be any type implementing [`Sanitizer`]: a [`Policy`], a
+/// [`plugin_policy::PluginPolicy`], a [`PolicyChain`] or a `&dyn Sanitizer`.
+/// This is a shortcut for [`Policy::sanitize_html`] and its counterparts.
+///
+/// # Example
+/// ```
+/// use dom_sanitizer::RestrictivePolicy;
+///
+/// let policy = RestrictivePolicy::builder()
+///     .exclude_elements(&["p", "b"])
+///     .build();
+///
+/// let html = dom_sanitizer::clean(r#"<p onclick="alert(1)">Hello, <b>world</b><script>x()</script>!</p>"#, &policy);
+/// assert!(html.contains("<p>Hello, <b>world</b>x()!</p>"));
+/// ```
+pub fn clean<S, P>(html: S, policy: &P) -> StrTendril
+where
+    S: Into<StrTendril>,
+    P: Sanitizer + ?Sized,
+{
+    let doc = dom_query::Document::from(html);
+    policy.sanitize_document(&doc);
+    doc.html()
+}
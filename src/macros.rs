@@ -2,13 +2,139 @@ macro_rules! sanitize_methods {
     // This macro generates sanitization methods for a type implementing a sanitization trait T.
     // T must provide a `sanitize_node` method that the generated methods will delegate to.
     () => {
+        /// Applies the rules that run on the whole node after its elements were sanitized.
+        fn finish_node(&self, node: &dom_query::NodeRef) {
+            crate::traits::SanitizePolicy::sanitize_comments(self, node);
+            crate::dom_helpers::move_misplaced_head_content(node);
+            // merging adjacent text nodes never changes the text, so it is safe for `pre` contents
+            node.normalize();
+            crate::traits::SanitizePolicy::sanitize_styles(self, node);
+            crate::traits::SanitizePolicy::sanitize_text(self, node);
+            crate::traits::SanitizePolicy::finalize(self, node);
+        }
+
         /// Sanitizes a node by applying the policy rules according to the directive type.
         ///
         /// For [Permissive] directive: Removes elements and attributes specified in the policy.
         /// For [Restrictive] directive: Keeps only elements and attributes specified in the policy.
         pub fn sanitize_node(&self, node: &dom_query::NodeRef) {
             T::sanitize_node(self, node);
-            node.normalize();
+            self.finish_node(node);
+        }
+
+        /// Sanitizes only the descendants of the node, making the scope of `sanitize_node` explicit.
+        ///
+        /// The contract is:
+        /// - the node itself is never removed, unwrapped or renamed, and its attributes are not sanitized,
+        ///   even if it matches the policy rules;
+        /// - nodes outside the subtree (siblings and ancestors of the node) are never touched;
+        /// - the `html`, `head` and `body` protection of the [Restrictive] directive only applies
+        ///   to those elements inside the subtree.
+        pub fn sanitize_subtree(&self, node: &dom_query::NodeRef) {
+            self.sanitize_node(node);
+        }
+
+        /// Sanitizes a node like `sanitize_node`, returning a [`crate::SanitizeReport`]
+        /// about the removed elements and attributes.
+        pub fn sanitize_node_report(&self, node: &dom_query::NodeRef) -> crate::SanitizeReport {
+            let mut report = crate::SanitizeReport::new();
+            T::sanitize_node_report(self, node, &mut report);
+            self.finish_node(node);
+            report
+        }
+
+        /// Sanitizes the [`dom_query::Document`], returning a [`crate::SanitizeReport`]
+        /// about the removed elements and attributes.
+        pub fn sanitize_document_report(
+            &self,
+            document: &dom_query::Document,
+        ) -> crate::SanitizeReport {
+            self.sanitize_node_report(&document.root())
+        }
+
+        /// Returns a [`crate::SanitizeReport`] of everything the policy would change in the [`dom_query::Document`],
+        /// without modifying it, e.g. to validate a new policy against production traffic.
+        ///
+        /// The document is guaranteed not to be mutated: the policy runs on a deep copy of its tree,
+        /// which costs a copy of every node. Node ids are preserved by the copy, so the report
+        /// refers to the elements of the original document.
+        pub fn sanitize_document_dry_run(
+            &self,
+            document: &dom_query::Document,
+        ) -> crate::SanitizeReport {
+            let copy = document.clone();
+            self.sanitize_document_report(&copy)
+        }
+
+        /// Sanitizes the [`dom_query::Document`], returning the markup of removed elements,
+        /// captured before their removal, e.g. to quarantine what was submitted for a security review.
+        ///
+        /// For removed (and escaped) elements, it is the whole subtree. For unwrapped elements,
+        /// whose children were kept, it is the element's own start tag with its attributes.
+        ///
+        /// Adversarial input may be large and contain many removed elements, so the captured markup
+        /// is limited to `max_bytes` in total; elements removed after the limit was reached are not captured.
+        pub fn sanitize_document_quarantine(
+            &self,
+            document: &dom_query::Document,
+            max_bytes: usize,
+        ) -> Vec<StrTendril> {
+            let mut report = crate::SanitizeReport::with_markup(max_bytes);
+            let root = document.root();
+            T::sanitize_node_report(self, &root, &mut report);
+            self.finish_node(&root);
+            report
+                .removed_elements
+                .into_iter()
+                .filter_map(|removed| removed.markup)
+                .collect()
+        }
+
+        /// Sanitizes the [`dom_query::Document`], aborting if it takes longer than `timeout`,
+        /// e.g. to protect a request handler from adversarial input.
+        ///
+        /// The elapsed time is checked periodically during the traversal, every 256 elements,
+        /// so the limit may be exceeded by the time needed to process that many elements.
+        /// On timeout, the document is left **partially sanitized**: the elements after
+        /// the point of abortion are not sanitized at all, so the document must be discarded.
+        pub fn sanitize_document_timeout(
+            &self,
+            document: &dom_query::Document,
+            timeout: std::time::Duration,
+        ) -> Result<(), crate::error::TimeoutError> {
+            let mut report =
+                crate::SanitizeReport::with_deadline(std::time::Instant::now() + timeout);
+            let root = document.root();
+            T::sanitize_node_report(self, &root, &mut report);
+            if report.timed_out {
+                return Err(crate::error::TimeoutError { timeout });
+            }
+            self.finish_node(&root);
+            Ok(())
+        }
+
+        /// Sanitizes the [`dom_query::Document`] and returns `true` if any element or attribute
+        /// was removed or altered.
+        ///
+        /// This is cheaper than comparing the serialized HTML before and after sanitization.
+        /// Only the policy's own changes count: the attribute order established by the parser
+        /// and text node merging during normalization are not considered a change.
+        pub fn sanitize_document_changed(&self, document: &dom_query::Document) -> bool {
+            self.sanitize_document_report(document).is_changed()
+        }
+
+        /// Returns `true` if the HTML already conforms to the policy: sanitizing it wouldn't change anything,
+        /// e.g. to reject dirty input outright instead of silently fixing it.
+        ///
+        /// The HTML is parsed the same way as by `sanitize_html`, and the check is made on the parsed document:
+        /// markup fixed by the parser itself (e.g. unclosed tags or misnested elements) doesn't make it dirty.
+        /// The parsed document is sanitized and its serialization compared with the one before sanitization,
+        /// so every rule counts, including the ones not tracked by reports (comments, text patterns, finalizer).
+        pub fn is_clean<S: Into<StrTendril>>(&self, html: S) -> bool {
+            let doc = dom_query::Document::from(html);
+            let before = doc.html();
+            self.sanitize_document(&doc);
+            doc.html() == before
         }
 
         /// Sanitizes the [`dom_query::Document`].
@@ -23,12 +149,203 @@ macro_rules! sanitize_methods {
             }
         }
 
+        /// Sanitizes only the subtrees of the [`dom_query::Document`] rooted at elements matching the gate,
+        /// e.g. to run an expensive policy only inside `<article>`.
+        ///
+        /// Content outside of the gates is left untouched, and so are the gate elements themselves
+        /// (see `sanitize_subtree`). Gates nested in another gate are sanitized once, as part of
+        /// the outermost one; the contents of `template` elements outside of the gates are not searched.
+        pub fn sanitize_gated<C: crate::plugin_policy::NodeChecker>(
+            &self,
+            document: &dom_query::Document,
+            gate: C,
+        ) {
+            let root = document.root();
+            let mut gates = vec![];
+            let mut next_node = root.first_element_child();
+            while let Some(node) = next_node {
+                let is_gate = gate.is_match(&node);
+                next_node = crate::dom_helpers::next_child_or_sibling(&node, is_gate, &root);
+                if is_gate {
+                    gates.push(node);
+                }
+            }
+            for gate_node in gates {
+                self.sanitize_subtree(&gate_node);
+            }
+        }
+
+        /// Sanitizes every node of the [`dom_query::Selection`], returning a [`crate::SanitizeReport`]
+        /// for each of them, e.g. to find out which section of a page had the most changes.
+        ///
+        /// Reports are in the same order as the nodes returned by [`dom_query::Selection::nodes`].
+        /// If selected nodes are nested, the inner node is sanitized (and reported) as part of the outer one,
+        /// and then again on its own.
+        pub fn sanitize_selection_reports(
+            &self,
+            sel: &dom_query::Selection,
+        ) -> Vec<crate::SanitizeReport> {
+            sel.nodes()
+                .iter()
+                .map(|node| self.sanitize_node_report(node))
+                .collect()
+        }
+
         /// Sanitizes the HTML content by applying the policy rules according to the directive type.
+        ///
+        /// Sanitizing the output again doesn't change it, unless unwrapping leaves elements where
+        /// the parser would restructure them (e.g. table parts outside a table, or a heading directly inside another one).
         pub fn sanitize_html<S: Into<StrTendril>>(&self, html: S) -> StrTendril {
             let doc = dom_query::Document::from(html);
             self.sanitize_document(&doc);
             doc.html()
         }
+
+        /// Sanitizes an HTML fragment meant to be inserted into a `context` element,
+        /// returning the sanitized fragment (without the context element).
+        ///
+        /// Unlike `sanitize_html`, which parses a full document, the fragment is parsed with
+        /// html5ever's fragment parsing algorithm in the given context, the way browsers parse `innerHTML`.
+        /// E.g. `<tr><td>x` in a `table` context keeps its rows (inside an implied `tbody`), while
+        /// in a document they are dropped. The context element is treated as the parent of the fragment
+        /// and is never sanitized itself.
+        ///
+        /// `context` is the name of an HTML element, compared ASCII case-insensitively. Any name is accepted,
+        /// but only some change the parsing: the table elements (`table`, `caption`, `colgroup`, `tbody`,
+        /// `thead`, `tfoot` and `tr`), `select`, `template`, `frameset`, `html` and the elements
+        /// with text-only contents (`title`, `textarea`, `style`, `script`, `xmp`, `iframe`, `noembed`,
+        /// `noframes` and `plaintext`). Other names, like `div`, `ul` or `td`, parse the fragment
+        /// as the contents of `body`.
+        pub fn sanitize_fragment_in_context<S: Into<StrTendril>>(
+            &self,
+            html: S,
+            context: &str,
+        ) -> StrTendril {
+            let doc = crate::fragment::parse_fragment_in_context(html, context);
+            let Some(context_element) = doc.root().first_element_child() else {
+                return StrTendril::new();
+            };
+            self.sanitize_subtree(&context_element);
+            context_element.inner_html()
+        }
+
+        /// Sanitizes the HTML content like `sanitize_html`, serializing the result
+        /// according to the [`crate::SerializeOptions`].
+        pub fn sanitize_html_with<S: Into<StrTendril>>(
+            &self,
+            html: S,
+            options: crate::SerializeOptions,
+        ) -> StrTendril {
+            let doc = dom_query::Document::from(html);
+            self.sanitize_document(&doc);
+            options.serialize(&doc)
+        }
+
+        /// Reads the HTML content from the reader to the end and sanitizes it.
+        ///
+        /// The input must be UTF-8 encoded: no encoding detection is performed, and invalid
+        /// UTF-8 results in an [`std::io::ErrorKind::InvalidData`] error.
+        /// This is not true streaming: sanitization works on the complete DOM tree,
+        /// so the whole input is buffered and parsed before any node is processed.
+        pub fn sanitize_reader<R: std::io::Read>(
+            &self,
+            mut reader: R,
+        ) -> std::io::Result<StrTendril> {
+            let mut html = String::new();
+            reader.read_to_string(&mut html)?;
+            Ok(self.sanitize_html(html))
+        }
+
+        /// Sanitizes the HTML content and serializes the result with indentation:
+        /// block-level elements are placed on their own lines, indented by their depth.
+        ///
+        /// This is meant for readability (debugging, reviews, test fixtures), not for byte-exact
+        /// round-tripping: whitespace-only text between blocks is dropped and inline content
+        /// is trimmed. The contents of `pre` and `textarea` are preserved verbatim.
+        pub fn sanitize_html_pretty<S: Into<StrTendril>>(&self, html: S) -> StrTendril {
+            let doc = dom_query::Document::from(html);
+            self.sanitize_document(&doc);
+            crate::pretty::pretty_html(&doc.root())
+        }
+
+        /// Sanitizes the HTML content, returning the sanitized HTML and a [`crate::SanitizeReport`].
+        ///
+        /// If the policy tracks positions (`track_positions(true)`), every removed element
+        /// in the report has the source line of its start tag. This only works here,
+        /// where the policy parses the HTML itself, not when it is handed a pre-built document.
+        pub fn sanitize_html_report<S: Into<StrTendril>>(
+            &self,
+            html: S,
+        ) -> (StrTendril, crate::SanitizeReport) {
+            if !self.track_positions {
+                let doc = dom_query::Document::from(html);
+                let report = self.sanitize_document_report(&doc);
+                return (doc.html(), report);
+            }
+            let (doc, lines) = crate::positions::parse_document_with_positions(html);
+            let mut report = self.sanitize_document_report(&doc);
+            for removed in report.removed_elements.iter_mut() {
+                removed.line = lines.get(&removed.id).copied();
+            }
+            (doc.html(), report)
+        }
+
+        /// Sanitizes the HTML content, returning the document title together with the sanitized
+        /// inner HTML of the `body`.
+        ///
+        /// The title is read before sanitization, so it is available even if the policy removes `head`
+        /// or `title`. It is the trimmed text of the first `title` element inside `head`; `None` if there is none.
+        /// Further `title` elements are ignored. Titles cannot be nested: the parser treats
+        /// the contents of `title` as text. The title is plain text and must be escaped by the caller
+        /// before being inserted into HTML. If the policy strips `body`, the whole document is returned.
+        pub fn extract_title<S: Into<StrTendril>>(&self, html: S) -> (Option<String>, StrTendril) {
+            let doc = dom_query::Document::from(html);
+            let title = doc
+                .select("head title")
+                .nodes()
+                .first()
+                .map(|title| title.text().trim().to_string());
+            self.sanitize_document(&doc);
+            let body = doc
+                .body()
+                .map_or_else(|| doc.html(), |body| body.inner_html());
+            (title, body)
+        }
+
+        /// Sanitizes the HTML content, returning the inner HTML of `head` and `body` separately,
+        /// e.g. to place the allowed `meta` and `title` elements of untrusted input into a template.
+        ///
+        /// Both regions are taken from the parsed document, so content that doesn't belong in `head`
+        /// (text or elements other than metadata) is in `body`: the parser moves it there,
+        /// as well as everything after it, even if the input placed it inside `<head>`.
+        /// Content left in `head` by unwrapping (e.g. the text of an unwrapped `style`) is moved the same way.
+        /// If the policy strips `head`, its part is empty; if it strips `body`, the whole document is returned
+        /// as the second part.
+        pub fn sanitize_split<S: Into<StrTendril>>(&self, html: S) -> (StrTendril, StrTendril) {
+            let doc = dom_query::Document::from(html);
+            self.sanitize_document(&doc);
+            let head = doc
+                .head()
+                .map_or_else(StrTendril::new, |head| head.inner_html());
+            let body = doc
+                .body()
+                .map_or_else(|| doc.html(), |body| body.inner_html());
+            (head, body)
+        }
+
+        /// Sanitizes the HTML content and returns the text content of the resulting `body`.
+        ///
+        /// Unlike calling `.text()` on unsanitized input, dangerous elements are processed first.
+        /// Note that unwrapped elements leave their text behind: to keep CSS or JavaScript
+        /// source out of the output, `style` and `script` must be removed
+        /// (e.g. with `remove_elements(&["style", "script"])`), not just excluded.
+        pub fn sanitize_to_text<S: Into<StrTendril>>(&self, html: S) -> String {
+            let doc = dom_query::Document::from(html);
+            self.sanitize_document(&doc);
+            doc.body()
+                .map_or_else(|| doc.text(), |body| body.text())
+                .to_string()
+        }
     };
 }
 
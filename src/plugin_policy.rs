@@ -1,3 +1,4 @@
+pub mod adblock;
 pub mod builder;
 pub mod core;
 pub mod preset;
@@ -5,6 +6,6 @@ pub mod preset;
 #[doc(inline)]
 pub use builder::PluginPolicyBuilder;
 #[doc(inline)]
-pub use core::{AttrChecker, NodeChecker, PluginPolicy};
+pub use core::{AttrChecker, CheckerCost, NodeChecker, PluginPolicy};
 #[doc(inline)]
 pub use core::{PermissivePluginPolicy, RestrictivePluginPolicy};
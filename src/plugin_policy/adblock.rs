@@ -0,0 +1,91 @@
+//! Element hiding rules of ad-block filter lists (EasyList and compatible), as [`NodeChecker`]s.
+//!
+//! Only a subset of the filter syntax is supported: generic element hiding rules,
+//! i.e. lines starting with `##` followed by a CSS selector:
+//!
+//! ```text
+//! ! a comment
+//! ##.ad
+//! ###sidebar-banner
+//! ##div[id^="banner"]
+//! ##.sponsored > a
+//! ```
+//!
+//! Other lines are skipped:
+//! - comments (`!`) and headers (`[Adblock Plus 2.0]`);
+//! - network rules (e.g. `||ads.example.com^`), which filter requests rather than elements;
+//! - domain-specific rules (`example.com##.ad`), since the document's domain is unknown;
+//! - exceptions (`#@#`), extended CSS (`#?#`), CSS injection (`#$#`), scriptlets (`##+js(...)`)
+//!   and HTML filters (`##^`);
+//! - rules whose selector is not supported by [`dom_query::Matcher`], such as procedural
+//!   pseudo-classes like `:has-text()`.
+
+use dom_query::{Matcher, NodeRef};
+
+use super::{CheckerCost, NodeChecker};
+use crate::error::{compile_selector, SelectorError};
+
+/// Matches elements by the CSS selector of an element hiding rule.
+pub struct ElementHidingRule {
+    /// The compiled selector of the rule.
+    pub selector: Matcher,
+}
+
+impl NodeChecker for ElementHidingRule {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.is_element() && node.is_match(&self.selector)
+    }
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::High
+    }
+}
+
+impl ElementHidingRule {
+    /// Creates a new `ElementHidingRule` instance from a CSS selector, without the `##` prefix.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SelectorError`] if the selector is invalid.
+    pub fn new(selector: &str) -> Result<Self, SelectorError> {
+        Ok(Self {
+            selector: compile_selector(selector)?,
+        })
+    }
+}
+
+/// Parses the generic element hiding rules of a filter list into node checkers,
+/// one per rule, skipping every unsupported line (see the [module documentation](self)).
+///
+/// The checkers are meant to be passed to [`PluginPolicyBuilder::remove`](super::PluginPolicyBuilder::remove),
+/// removing the hidden elements with their contents.
+///
+/// # Examples
+///
+/// ```rust
+/// use dom_sanitizer::plugin_policy::{adblock, PermissivePluginPolicy, PluginPolicy};
+///
+/// let rules = "! Ads\n##.ad\n##div[id^=\"banner\"]\n||ads.example.com^";
+/// let policy: PermissivePluginPolicy = adblock::parse_hiding_rules(rules)
+///     .into_iter()
+///     .fold(PluginPolicy::builder(), |builder, checker| builder.remove(checker))
+///     .build();
+/// let html = policy.sanitize_html(r#"<div id="banner-top">Ad</div><p class="ad">Ad</p><p>Text</p>"#);
+/// assert!(html.contains("<body><p>Text</p></body>"));
+/// ```
+pub fn parse_hiding_rules(rules: &str) -> Vec<Box<dyn NodeChecker>> {
+    rules
+        .lines()
+        .filter_map(|line| hiding_rule_selector(line.trim()))
+        .filter_map(|selector| ElementHidingRule::new(selector).ok())
+        .map(|rule| Box::new(rule) as Box<dyn NodeChecker>)
+        .collect()
+}
+
+/// Returns the selector of a generic element hiding rule, or `None` for any other line.
+fn hiding_rule_selector(line: &str) -> Option<&str> {
+    let selector = line.strip_prefix("##")?.trim();
+    if selector.is_empty() || selector.starts_with(['+', '^']) {
+        return None;
+    }
+    Some(selector)
+}
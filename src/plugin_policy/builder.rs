@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use super::core::{AttrChecker, NodeChecker, PluginPolicy};
+use super::core::{AttrChecker, CheckerIndex, NamedChecker, NodeChecker, PluginPolicy};
 use crate::traits::SanitizeDirective;
 
 use crate::Restrictive;
@@ -40,18 +40,41 @@ use crate::Restrictive;
 ///   .remove(preset::LocalNamesMatcher::new(&["style", "script"]))
 ///   .build();
 /// ```
+///
+/// The builder is `Clone`: checkers are shared between the clones, so a base builder
+/// can be cloned and specialized into several policies.
 pub struct PluginPolicyBuilder<T: SanitizeDirective = Restrictive> {
-    exclude_checkers: Vec<Box<dyn NodeChecker>>,
-    remove_checkers: Vec<Box<dyn NodeChecker>>,
-    attr_exclude_checkers: Vec<Box<dyn AttrChecker>>,
+    exclude_checkers: Vec<NamedChecker<dyn NodeChecker>>,
+    remove_checkers: Vec<NamedChecker<dyn NodeChecker>>,
+    unwrap_checkers: Vec<NamedChecker<dyn NodeChecker>>,
+    attr_exclude_checkers: Vec<NamedChecker<dyn AttrChecker>>,
+    track_positions: bool,
+    allow_strip_structural: bool,
     _directive: std::marker::PhantomData<T>,
 }
+impl<T: SanitizeDirective> Clone for PluginPolicyBuilder<T> {
+    fn clone(&self) -> Self {
+        Self {
+            exclude_checkers: self.exclude_checkers.clone(),
+            remove_checkers: self.remove_checkers.clone(),
+            unwrap_checkers: self.unwrap_checkers.clone(),
+            attr_exclude_checkers: self.attr_exclude_checkers.clone(),
+            track_positions: self.track_positions,
+            allow_strip_structural: self.allow_strip_structural,
+            _directive: std::marker::PhantomData,
+        }
+    }
+}
+
 impl<T: SanitizeDirective> Default for PluginPolicyBuilder<T> {
     fn default() -> Self {
         Self {
             exclude_checkers: vec![],
             remove_checkers: vec![],
+            unwrap_checkers: vec![],
             attr_exclude_checkers: vec![],
+            track_positions: false,
+            allow_strip_structural: false,
             _directive: std::marker::PhantomData,
         }
     }
@@ -64,27 +87,140 @@ impl<T: SanitizeDirective> PluginPolicyBuilder<T> {
     }
     /// Creates a new `PluginPolicyBuilder` instance with the specified sanitization directive.
     pub fn exclude<C: NodeChecker + 'static>(mut self, checker: C) -> Self {
-        self.exclude_checkers.push(Box::new(checker));
+        self.exclude_checkers
+            .push(NamedChecker::new(None, Arc::new(checker)));
         self
     }
     /// Adds a node checker to the list of checkers that will be used to remove nodes.
     pub fn remove<C: NodeChecker + 'static>(mut self, checker: C) -> Self {
-        self.remove_checkers.push(Box::new(checker));
+        self.remove_checkers
+            .push(NamedChecker::new(None, Arc::new(checker)));
+        self
+    }
+
+    /// Adds a node checker to the list of checkers that will be used to unwrap elements,
+    /// replacing them with their children (e.g. downgrading unsafe links to their text).
+    ///
+    /// A matching element is unwrapped even if it is excluded from the [`crate::Restrictive`] directive
+    /// or allowed by the [`crate::Permissive`] one. Elements matched by a remove checker are still removed.
+    pub fn conditional_unwrap<C: NodeChecker + 'static>(mut self, checker: C) -> Self {
+        self.unwrap_checkers
+            .push(NamedChecker::new(None, Arc::new(checker)));
         self
     }
 
     /// Adds an attribute checker to the list of checkers that will be used to exclude attributes from the base policy.
     pub fn exclude_attr<C: AttrChecker + 'static>(mut self, checker: C) -> Self {
-        self.attr_exclude_checkers.push(Box::new(checker));
+        self.attr_exclude_checkers
+            .push(NamedChecker::new(None, Arc::new(checker)));
+        self
+    }
+
+    /// Adds a node checker like [`PluginPolicyBuilder::exclude`], under a name that allows
+    /// disabling it in the built policy with [`PluginPolicy::with_disabled`].
+    /// Several checkers may share a name, so they are disabled together.
+    pub fn exclude_named<C: NodeChecker + 'static>(mut self, name: &str, checker: C) -> Self {
+        self.exclude_checkers
+            .push(NamedChecker::new(Some(name), Arc::new(checker)));
         self
     }
 
+    /// Adds a node checker like [`PluginPolicyBuilder::remove`], under a name
+    /// (see [`PluginPolicyBuilder::exclude_named`]).
+    pub fn remove_named<C: NodeChecker + 'static>(mut self, name: &str, checker: C) -> Self {
+        self.remove_checkers
+            .push(NamedChecker::new(Some(name), Arc::new(checker)));
+        self
+    }
+
+    /// Adds an attribute checker like [`PluginPolicyBuilder::exclude_attr`], under a name
+    /// (see [`PluginPolicyBuilder::exclude_named`]).
+    pub fn exclude_attr_named<C: AttrChecker + 'static>(mut self, name: &str, checker: C) -> Self {
+        self.attr_exclude_checkers
+            .push(NamedChecker::new(Some(name), Arc::new(checker)));
+        self
+    }
+
+    /// Merges the checkers of a borrowed [`PluginPolicy`] into the builder, after the ones already added.
+    ///
+    /// The checkers are shared with the policy, not copied, so a canonical policy can be kept around
+    /// and extended into several variants. Checker names are preserved, and the boolean options
+    /// are enabled if they are enabled in either of them.
+    pub fn merge_ref(mut self, other: &PluginPolicy<T>) -> Self {
+        self.exclude_checkers
+            .extend(in_added_order(&other.exclude_checkers));
+        self.remove_checkers
+            .extend(in_added_order(&other.remove_checkers));
+        self.unwrap_checkers
+            .extend(in_added_order(&other.unwrap_checkers));
+        self.attr_exclude_checkers
+            .extend(other.attr_exclude_checkers.iter().cloned());
+        self.track_positions |= other.track_positions;
+        self.allow_strip_structural |= other.allow_strip_structural;
+        self
+    }
+
+    /// Allows the [`crate::Restrictive`] directive to remove `html`, `head` and `body`
+    /// like any other element that isn't excluded. Disabled by default: these elements are always kept.
+    ///
+    /// This is useful for fragments (e.g. parsed with [`dom_query::Document::fragment`]),
+    /// where the structural wrappers are unwanted in the output and only the bare content should remain.
+    /// Note that the `head` contents are kept too, unless they are removed by the policy.
+    pub fn allow_strip_structural(mut self, allow: bool) -> Self {
+        self.allow_strip_structural = allow;
+        self
+    }
+
+    /// Enables tracking of the source line for every removed element in the report
+    /// returned by [`PluginPolicy::sanitize_html_report`].
+    ///
+    /// Positions are only available when the policy parses the HTML itself,
+    /// not when it is handed a pre-built [`dom_query::Document`]. Disabled by default.
+    pub fn track_positions(mut self, track: bool) -> Self {
+        self.track_positions = track;
+        self
+    }
+
+    /// Builds the [`PluginPolicy`] using the current configuration.
+    ///
+    /// Node checkers are ordered by their [`CheckerCost`](super::CheckerCost), cheapest first,
+    /// keeping the insertion order for checkers of the same cost. Checkers limited to elements
+    /// of one name (see [`NodeChecker::scope_local_name`]) are indexed by it.
     pub fn build(self) -> PluginPolicy<T> {
+        let exclude_checkers = into_sorted(self.exclude_checkers);
+        let remove_checkers = into_sorted(self.remove_checkers);
+        let unwrap_checkers = into_sorted(self.unwrap_checkers);
         PluginPolicy {
-            exclude_checkers: Arc::from(self.exclude_checkers),
-            remove_checkers: Arc::from(self.remove_checkers),
-            attr_exclude_checkers: Arc::from(self.attr_exclude_checkers),
+            exclude_index: Arc::new(CheckerIndex::new(&exclude_checkers)),
+            remove_index: Arc::new(CheckerIndex::new(&remove_checkers)),
+            unwrap_index: Arc::new(CheckerIndex::new(&unwrap_checkers)),
+            exclude_checkers,
+            remove_checkers,
+            unwrap_checkers,
+            attr_exclude_checkers: self.attr_exclude_checkers.into(),
+            track_positions: self.track_positions,
+            allow_strip_structural: self.allow_strip_structural,
             _directive: std::marker::PhantomData,
         }
     }
 }
+
+/// Records the insertion order of the checkers, then sorts them by cost.
+fn into_sorted(
+    mut checkers: Vec<NamedChecker<dyn NodeChecker>>,
+) -> Arc<[NamedChecker<dyn NodeChecker>]> {
+    for (position, checker) in checkers.iter_mut().enumerate() {
+        checker.position = position;
+    }
+    checkers.sort_by_key(|checker| checker.cost());
+    checkers.into()
+}
+
+/// Returns the checkers of a built policy in the order they were added, undoing the sort by cost.
+fn in_added_order(
+    checkers: &[NamedChecker<dyn NodeChecker>],
+) -> Vec<NamedChecker<dyn NodeChecker>> {
+    let mut checkers = checkers.to_vec();
+    checkers.sort_by_key(|checker| checker.position);
+    checkers
+}
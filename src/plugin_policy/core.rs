@@ -1,15 +1,32 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 
-use dom_query::NodeRef;
-use html5ever::Attribute;
+use dom_query::{Document, NodeRef};
+use html5ever::{Attribute, LocalName};
 use tendril::StrTendril;
 
 use super::builder::PluginPolicyBuilder;
 use crate::macros::sanitize_methods;
-use crate::traits::{SanitizeDirective, SanitizePolicy};
+use crate::traits::{SanitizeDirective, SanitizePolicy, Sanitizer};
 use crate::{Permissive, Restrictive};
 
+/// A hint about how expensive a [`NodeChecker`] is to evaluate.
+///
+/// [`PluginPolicyBuilder::build`] orders the checkers from the cheapest to the most expensive,
+/// keeping the insertion order for checkers of the same cost. Since matching stops at the first
+/// checker that matches, cheap checkers running first save evaluations of expensive ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum CheckerCost {
+    /// Cheap checks, like comparing the element name or namespace.
+    Low,
+    /// Checks of moderate cost, like inspecting attributes. This is the default.
+    #[default]
+    Medium,
+    /// Expensive checks, like regular expressions, selector matching or walking the tree.
+    High,
+}
+
 /// A trait for checking whether a node matches certain criteria.
 ///
 /// This trait is used to determine whether a node should be excluded from a basic policy rule
@@ -18,6 +35,21 @@ use crate::{Permissive, Restrictive};
 pub trait NodeChecker: Send + Sync {
     /// Returns `true` if the node is excluded by the basic policy or needs to be removed; otherwise, returns `false`.
     fn is_match(&self, _node: &NodeRef) -> bool;
+
+    /// Returns a hint about how expensive the checker is, used to order the checkers of a policy.
+    /// Defaults to [`CheckerCost::Medium`].
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::Medium
+    }
+
+    /// Returns the local name of the only elements the checker can match, if it is limited to one.
+    ///
+    /// [`PluginPolicyBuilder::build`] indexes such checkers by the name, so they are only consulted
+    /// for elements having it, instead of for every element. The checker must never match an element
+    /// with another local name, or the index will skip matches. Defaults to `None`: the checker runs on every element.
+    fn scope_local_name(&self) -> Option<LocalName> {
+        None
+    }
 }
 
 /// A trait for checking whether an attribute matches certain criteria.
@@ -27,12 +59,208 @@ pub trait AttrChecker: Send + Sync {
     fn is_match_attr(&self, _node: &NodeRef, _attr: &Attribute) -> bool;
 }
 
+impl<C: NodeChecker + ?Sized> NodeChecker for Arc<C> {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        (**self).is_match(node)
+    }
+
+    fn cost(&self) -> CheckerCost {
+        (**self).cost()
+    }
+
+    fn scope_local_name(&self) -> Option<LocalName> {
+        (**self).scope_local_name()
+    }
+}
+
+/// Indices of the node checkers to consult for an element, keyed by its local name,
+/// see [`NodeChecker::scope_local_name`].
+///
+/// The indices keep the order of the checkers, so the cheaper ones are still evaluated first.
+#[derive(Debug, Default)]
+pub(crate) struct CheckerIndex {
+    /// The scoped checkers of a name followed by the unscoped ones, merged in order.
+    by_name: HashMap<LocalName, Box<[usize]>>,
+    /// The checkers without a scope, consulted for the elements of all other names.
+    unscoped: Box<[usize]>,
+}
+
+impl CheckerIndex {
+    pub(crate) fn new<C: NodeChecker>(checkers: &[C]) -> Self {
+        let scopes: Vec<Option<LocalName>> = checkers
+            .iter()
+            .map(|checker| checker.scope_local_name())
+            .collect();
+        let mut by_name: HashMap<LocalName, Box<[usize]>> = HashMap::new();
+        for name in scopes.iter().flatten() {
+            if by_name.contains_key(name) {
+                continue;
+            }
+            let indices = scopes
+                .iter()
+                .enumerate()
+                .filter(|(_, scope)| scope.as_ref().map_or(true, |scope| scope == name))
+                .map(|(i, _)| i)
+                .collect();
+            by_name.insert(name.clone(), indices);
+        }
+        let unscoped = scopes
+            .iter()
+            .enumerate()
+            .filter(|(_, scope)| scope.is_none())
+            .map(|(i, _)| i)
+            .collect();
+        Self { by_name, unscoped }
+    }
+
+    /// Returns the indices of the checkers relevant for the node, in evaluation order.
+    fn indices(&self, node: &NodeRef) -> &[usize] {
+        match node.qual_name_ref() {
+            Some(qual_name) => self.by_name.get(&qual_name.local).unwrap_or(&self.unscoped),
+            None => &self.unscoped,
+        }
+    }
+
+    /// Returns `true` if any of the checkers relevant for the node matches it.
+    ///
+    /// This is the sanitization path: it stops at the first matching checker.
+    fn is_match<C: NodeChecker>(&self, checkers: &[C], node: &NodeRef) -> bool {
+        self.indices(node)
+            .iter()
+            .any(|&i| checkers[i].is_match(node))
+    }
+
+    /// Returns the indices of all the checkers relevant for the node that match it.
+    ///
+    /// Unlike [`CheckerIndex::is_match`], it doesn't stop at the first match, so every relevant
+    /// checker is evaluated. It is meant for diagnostics only, sanitization never uses it.
+    fn matches<'a, C: NodeChecker>(
+        &'a self,
+        checkers: &'a [C],
+        node: &'a NodeRef<'a>,
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.indices(node)
+            .iter()
+            .copied()
+            .filter(move |&i| checkers[i].is_match(node))
+    }
+}
+
+impl<C: AttrChecker + ?Sized> AttrChecker for Arc<C> {
+    fn is_match_attr(&self, node: &NodeRef, attr: &Attribute) -> bool {
+        (**self).is_match_attr(node, attr)
+    }
+}
+
+impl<C: NodeChecker + ?Sized> NodeChecker for Box<C> {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        (**self).is_match(node)
+    }
+
+    fn cost(&self) -> CheckerCost {
+        (**self).cost()
+    }
+
+    fn scope_local_name(&self) -> Option<LocalName> {
+        (**self).scope_local_name()
+    }
+}
+
+impl<C: AttrChecker + ?Sized> AttrChecker for Box<C> {
+    fn is_match_attr(&self, node: &NodeRef, attr: &Attribute) -> bool {
+        (**self).is_match_attr(node, attr)
+    }
+}
+
+/// A checker of a [`PluginPolicy`], along with the optional name it was added with,
+/// see [`PluginPolicy::with_disabled`].
+pub(crate) struct NamedChecker<C: ?Sized> {
+    pub(crate) name: Option<Box<str>>,
+    pub(crate) checker: Arc<C>,
+    /// The index of the checker in the order it was added, reported by [`PluginPolicy::profile`]
+    /// while the checkers are evaluated in the order of their cost. Assigned when the policy is built.
+    pub(crate) position: usize,
+}
+
+impl<C: ?Sized> Clone for NamedChecker<C> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            checker: Arc::clone(&self.checker),
+            position: self.position,
+        }
+    }
+}
+
+impl<C: ?Sized> NamedChecker<C> {
+    pub(crate) fn new(name: Option<&str>, checker: Arc<C>) -> Self {
+        Self {
+            name: name.map(Box::from),
+            checker,
+            position: 0,
+        }
+    }
+
+    /// Returns `true` if the checker was added with one of the names.
+    fn is_named_in(&self, names: &[&str]) -> bool {
+        self.name
+            .as_deref()
+            .is_some_and(|name| names.contains(&name))
+    }
+}
+
+impl<C: NodeChecker + ?Sized> NodeChecker for NamedChecker<C> {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        self.checker.is_match(node)
+    }
+
+    fn cost(&self) -> CheckerCost {
+        self.checker.cost()
+    }
+
+    fn scope_local_name(&self) -> Option<LocalName> {
+        self.checker.scope_local_name()
+    }
+}
+
+impl<C: AttrChecker + ?Sized> AttrChecker for NamedChecker<C> {
+    fn is_match_attr(&self, node: &NodeRef, attr: &Attribute) -> bool {
+        self.checker.is_match_attr(node, attr)
+    }
+}
+
+/// Keeps the checkers that were not added with one of the names,
+/// renumbering their positions so that they stay contiguous.
+fn without_named<C: ?Sized>(
+    checkers: &[NamedChecker<C>],
+    names: &[&str],
+) -> Arc<[NamedChecker<C>]> {
+    let mut kept: Vec<NamedChecker<C>> = checkers
+        .iter()
+        .filter(|checker| !checker.is_named_in(names))
+        .cloned()
+        .collect();
+    let mut positions: Vec<usize> = kept.iter().map(|checker| checker.position).collect();
+    positions.sort_unstable();
+    for checker in &mut kept {
+        checker.position = positions.partition_point(|&p| p < checker.position);
+    }
+    kept.into()
+}
+
 /// A plugin based policy for sanitizing HTML documents.
 #[derive(Clone)]
 pub struct PluginPolicy<T: SanitizeDirective = Restrictive> {
-    pub(crate) exclude_checkers: Arc<[Box<dyn NodeChecker>]>,
-    pub(crate) remove_checkers: Arc<[Box<dyn NodeChecker>]>,
-    pub(crate) attr_exclude_checkers: Arc<[Box<dyn AttrChecker>]>,
+    pub(crate) exclude_checkers: Arc<[NamedChecker<dyn NodeChecker>]>,
+    pub(crate) remove_checkers: Arc<[NamedChecker<dyn NodeChecker>]>,
+    pub(crate) unwrap_checkers: Arc<[NamedChecker<dyn NodeChecker>]>,
+    pub(crate) exclude_index: Arc<CheckerIndex>,
+    pub(crate) remove_index: Arc<CheckerIndex>,
+    pub(crate) unwrap_index: Arc<CheckerIndex>,
+    pub(crate) attr_exclude_checkers: Arc<[NamedChecker<dyn AttrChecker>]>,
+    pub(crate) track_positions: bool,
+    /// Whether `html`, `head` and `body` may be removed by the [Restrictive] directive.
+    pub(crate) allow_strip_structural: bool,
     pub(crate) _directive: std::marker::PhantomData<T>,
 }
 
@@ -42,24 +270,33 @@ impl<T: SanitizeDirective> fmt::Debug for PluginPolicy<T> {
             .field(
                 "exclude_checkers",
                 &format_args!(
-                    "Arc<[Box<dyn NodeChecker>]> ({} elements)",
+                    "Arc<[NamedChecker<dyn NodeChecker>]> ({} elements)",
                     self.exclude_checkers.len()
                 ),
             )
             .field(
                 "remove_checkers",
                 &format_args!(
-                    "Arc<[Box<dyn NodeChecker>]> ({} elements)",
+                    "Arc<[NamedChecker<dyn NodeChecker>]> ({} elements)",
                     self.remove_checkers.len()
                 ),
             )
+            .field(
+                "unwrap_checkers",
+                &format_args!(
+                    "Arc<[NamedChecker<dyn NodeChecker>]> ({} elements)",
+                    self.unwrap_checkers.len()
+                ),
+            )
             .field(
                 "attr_exclude_checkers",
                 &format_args!(
-                    "Arc<[Box<dyn AttrChecker>]> ({} elements)",
+                    "Arc<[NamedChecker<dyn AttrChecker>]> ({} elements)",
                     self.attr_exclude_checkers.len()
                 ),
             )
+            .field("track_positions", &self.track_positions)
+            .field("allow_strip_structural", &self.allow_strip_structural)
             .field("_directive", &self._directive)
             .finish()
     }
@@ -67,15 +304,15 @@ impl<T: SanitizeDirective> fmt::Debug for PluginPolicy<T> {
 
 impl<T: SanitizeDirective> SanitizePolicy for PluginPolicy<T> {
     fn should_exclude(&self, node: &NodeRef) -> bool {
-        self.exclude_checkers
-            .iter()
-            .any(|checker| checker.is_match(node))
+        self.exclude_index.is_match(&self.exclude_checkers, node)
     }
 
     fn should_remove(&self, node: &NodeRef) -> bool {
-        self.remove_checkers
-            .iter()
-            .any(|checker| checker.is_match(node))
+        self.remove_index.is_match(&self.remove_checkers, node)
+    }
+
+    fn should_unwrap(&self, node: &NodeRef) -> bool {
+        self.unwrap_index.is_match(&self.unwrap_checkers, node)
     }
 
     fn has_attrs_to_exclude(&self) -> bool {
@@ -95,9 +332,20 @@ impl<T: SanitizeDirective> SanitizePolicy for PluginPolicy<T> {
         exclude_fn(node, &attrs);
     }
 
+    fn allow_strip_structural(&self) -> bool {
+        self.allow_strip_structural
+    }
+
+    fn has_element_rules(&self) -> bool {
+        !self.exclude_checkers.is_empty()
+            || !self.remove_checkers.is_empty()
+            || !self.unwrap_checkers.is_empty()
+    }
+
     fn is_empty(&self) -> bool {
         self.exclude_checkers.is_empty()
             && self.remove_checkers.is_empty()
+            && self.unwrap_checkers.is_empty()
             && self.attr_exclude_checkers.is_empty()
     }
 }
@@ -115,6 +363,82 @@ impl<T: SanitizeDirective> PluginPolicy<T> {
 
 impl<T: SanitizeDirective> PluginPolicy<T> {
     sanitize_methods!();
+
+    /// Counts how many elements of the document each node checker matches, without modifying the document.
+    ///
+    /// Returns `(index, count)` pairs: exclude checkers come first, in the order they were added
+    /// (not the order they are evaluated in, see [`CheckerCost`]), followed by remove checkers
+    /// and then by the checkers added with [`PluginPolicyBuilder::conditional_unwrap`]. Unlike sanitization, which stops at the first
+    /// matching checker, every checker relevant for an element is tested against it independently,
+    /// so an element matched by several checkers counts for each of them. The counts don't reflect
+    /// the changes a sanitization pass would make (e.g. elements inside removed subtrees are still counted).
+    ///
+    /// Attribute checkers are profiled separately by [`PluginPolicy::profile_attrs`].
+    pub fn profile(&self, document: &Document) -> Vec<(usize, usize)> {
+        let exclude_len = self.exclude_checkers.len();
+        let remove_end = exclude_len + self.remove_checkers.len();
+        let mut counts = vec![0; remove_end + self.unwrap_checkers.len()];
+        for node in document.root().descendants_it().filter(|n| n.is_element()) {
+            for i in self.exclude_index.matches(&self.exclude_checkers, &node) {
+                counts[self.exclude_checkers[i].position] += 1;
+            }
+            for i in self.remove_index.matches(&self.remove_checkers, &node) {
+                counts[exclude_len + self.remove_checkers[i].position] += 1;
+            }
+            for i in self.unwrap_index.matches(&self.unwrap_checkers, &node) {
+                counts[remove_end + self.unwrap_checkers[i].position] += 1;
+            }
+        }
+        counts.into_iter().enumerate().collect()
+    }
+
+    /// Counts how many attributes of the document each attribute checker matches,
+    /// without modifying the document.
+    ///
+    /// Returns `(index, count)` pairs, in the order the checkers were added.
+    pub fn profile_attrs(&self, document: &Document) -> Vec<(usize, usize)> {
+        let mut counts = vec![0; self.attr_exclude_checkers.len()];
+        for node in document.root().descendants_it().filter(|n| n.is_element()) {
+            for attr in node.attrs() {
+                for (count, checker) in counts.iter_mut().zip(self.attr_exclude_checkers.iter()) {
+                    if checker.is_match_attr(&node, &attr) {
+                        *count += 1;
+                    }
+                }
+            }
+        }
+        counts.into_iter().enumerate().collect()
+    }
+
+    /// Returns a copy of the policy without the checkers added with one of the names
+    /// (see [`PluginPolicyBuilder::exclude_named`]), e.g. to toggle checkers for A/B testing or a gradual rollout.
+    ///
+    /// The policy itself is immutable and left unchanged: the new policy shares the remaining checkers
+    /// with it, and only its checker lists and indices are rebuilt. Unnamed checkers are always kept,
+    /// and names that don't match any checker are ignored.
+    pub fn with_disabled(&self, names: &[&str]) -> Self {
+        let exclude_checkers = without_named(&self.exclude_checkers, names);
+        let remove_checkers = without_named(&self.remove_checkers, names);
+        let unwrap_checkers = without_named(&self.unwrap_checkers, names);
+        Self {
+            exclude_index: Arc::new(CheckerIndex::new(&exclude_checkers)),
+            remove_index: Arc::new(CheckerIndex::new(&remove_checkers)),
+            unwrap_index: Arc::new(CheckerIndex::new(&unwrap_checkers)),
+            exclude_checkers,
+            remove_checkers,
+            unwrap_checkers,
+            attr_exclude_checkers: without_named(&self.attr_exclude_checkers, names),
+            track_positions: self.track_positions,
+            allow_strip_structural: self.allow_strip_structural,
+            _directive: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: SanitizeDirective> Sanitizer for PluginPolicy<T> {
+    fn sanitize_node(&self, node: &NodeRef) {
+        PluginPolicy::sanitize_node(self, node);
+    }
 }
 
 impl<T: SanitizeDirective> PluginPolicy<T> {
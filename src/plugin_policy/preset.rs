@@ -1,7 +1,8 @@
-use dom_query::NodeRef;
+use dom_query::{Matcher, NodeRef};
 use html5ever::{Attribute, LocalName, Namespace};
 
-use super::{core::NodeChecker, AttrChecker};
+use super::{core::NodeChecker, AttrChecker, CheckerCost};
+use crate::error::{compile_selector, AttrMatcherError, SelectorError};
 
 /// Matches nodes with a specific local name.
 pub struct LocalNameMatcher(pub LocalName);
@@ -10,6 +11,12 @@ impl NodeChecker for LocalNameMatcher {
         node.qual_name_ref()
             .is_some_and(|qual_name| self.0 == qual_name.local)
     }
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::Low
+    }
+    fn scope_local_name(&self) -> Option<LocalName> {
+        Some(self.0.clone())
+    }
 }
 
 impl LocalNameMatcher {
@@ -30,6 +37,9 @@ impl NodeChecker for LocalNamesMatcher {
         node.qual_name_ref()
             .is_some_and(|qual_name| self.0.contains(&qual_name.local))
     }
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::Low
+    }
 }
 
 impl LocalNamesMatcher {
@@ -43,18 +53,37 @@ impl LocalNamesMatcher {
     }
 }
 
+/// Compares attribute names ASCII case-insensitively, unless `case_sensitive` is set.
+///
+/// Attribute checkers compare names case-insensitively by default, so a rule for `viewbox` also matches
+/// the camelCased `viewBox` of an SVG element, and a rule for `onClick` matches `onclick`.
+fn attr_name_eq(expected: &LocalName, name: &LocalName, case_sensitive: bool) -> bool {
+    expected == name || (!case_sensitive && expected.eq_ignore_ascii_case(name))
+}
+
+/// Checks if the attribute name is one of the names, see [`attr_name_eq`].
+fn attr_name_in(names: &[LocalName], name: &LocalName, case_sensitive: bool) -> bool {
+    names
+        .iter()
+        .any(|expected| attr_name_eq(expected, name, case_sensitive))
+}
+
 /// Matches nodes with a specific local name and checks if the attribute matches.
+///
+/// Attribute names are compared ASCII case-insensitively, unless [`AttrMatcher::case_sensitive`] is set.
 pub struct AttrMatcher {
     /// The local name of the element to match. If `None`, matches any element.
     pub element_scope: Option<LocalName>,
     /// The local names of the attributes to match.
     pub attr_names: Vec<LocalName>,
+    /// Whether attribute names are compared case-sensitively.
+    pub case_sensitive: bool,
 }
 
 impl AttrChecker for AttrMatcher {
     fn is_match_attr(&self, node: &NodeRef, attr: &Attribute) -> bool {
         let Some(ref element_scope) = self.element_scope else {
-            return self.attr_names.contains(&attr.name.local);
+            return attr_name_in(&self.attr_names, &attr.name.local, self.case_sensitive);
         };
         // Only proceed if node's local name matches the element scope
         if !node
@@ -63,7 +92,7 @@ impl AttrChecker for AttrMatcher {
         {
             return false;
         }
-        self.attr_names.contains(&attr.name.local)
+        attr_name_in(&self.attr_names, &attr.name.local, self.case_sensitive)
     }
 }
 
@@ -81,16 +110,157 @@ impl AttrMatcher {
                 .iter()
                 .map(|name| LocalName::from(*name))
                 .collect(),
+            case_sensitive: false,
+        }
+    }
+
+    /// Sets whether attribute names are compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Parses an `AttrMatcher` from a string, e.g. supplied by users in a configuration file.
+    ///
+    /// The syntax is an optional element name followed by a list of attribute names in brackets,
+    /// separated by commas or whitespace: `a[href, title]` matches `href` and `title` of `a` elements,
+    /// `[class]` matches `class` of any element. Attribute names may be quoted with `"` or `'`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AttrMatcherError`] with the column of the failure, e.g. for a missing `[` or `]`,
+    /// an unterminated quote, an empty attribute list or unexpected input after the closing bracket.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dom_sanitizer::plugin_policy::preset::AttrMatcher;
+    ///
+    /// let matcher = AttrMatcher::parse("a[href, 'title']").unwrap();
+    /// assert_eq!(matcher.element_scope.as_deref(), Some("a"));
+    /// assert_eq!(matcher.attr_names.len(), 2);
+    ///
+    /// let err = AttrMatcher::parse("a[href").err().unwrap();
+    /// assert_eq!(err.to_string(), "invalid attribute matcher `a[href` at column 7: missing closing `]`");
+    /// ```
+    pub fn parse(input: &str) -> Result<Self, AttrMatcherError> {
+        AttrMatcherParser::new(input).parse()
+    }
+}
+
+/// A parser for the syntax of [`AttrMatcher::parse`].
+struct AttrMatcherParser<'a> {
+    input: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl<'a> AttrMatcherParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            chars: input.char_indices().peekable(),
+        }
+    }
+
+    fn error(&self, offset: usize, message: &str) -> AttrMatcherError {
+        AttrMatcherError {
+            input: self.input.to_string(),
+            column: self.input[..offset].chars().count() as u32 + 1,
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns the byte offset of the next character, or the length of the input at its end.
+    fn offset(&mut self) -> usize {
+        self.chars.peek().map_or(self.input.len(), |&(i, _)| i)
+    }
+
+    fn skip_while(&mut self, f: impl Fn(char) -> bool) {
+        while self.chars.next_if(|&(_, c)| f(c)).is_some() {}
+    }
+
+    fn take_name(&mut self) -> &'a str {
+        let start = self.offset();
+        self.skip_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'));
+        let end = self.offset();
+        &self.input[start..end]
+    }
+
+    fn parse(mut self) -> Result<AttrMatcher, AttrMatcherError> {
+        self.skip_while(char::is_whitespace);
+        let element = self.take_name();
+        self.skip_while(char::is_whitespace);
+        let offset = self.offset();
+        match self.chars.next() {
+            Some((_, '[')) => {}
+            Some(_) if element.is_empty() => {
+                return Err(self.error(offset, "expected an element name or `[`"))
+            }
+            Some(_) => return Err(self.error(offset, "expected `[` after the element name")),
+            None => return Err(self.error(offset, "missing `[`")),
+        }
+        let mut attr_names = vec![];
+        loop {
+            self.skip_while(|c| c.is_whitespace() || c == ',');
+            let offset = self.offset();
+            match self.chars.peek().map(|&(_, c)| c) {
+                Some(']') => {
+                    self.chars.next();
+                    break;
+                }
+                None => return Err(self.error(offset, "missing closing `]`")),
+                Some(quote @ ('"' | '\'')) => {
+                    self.chars.next();
+                    let start = self.offset();
+                    self.skip_while(|c| c != quote);
+                    let end = self.offset();
+                    if self.chars.next().is_none() {
+                        return Err(self.error(offset, "unterminated quote"));
+                    }
+                    if start == end {
+                        return Err(self.error(offset, "empty attribute name"));
+                    }
+                    attr_names.push(&self.input[start..end]);
+                }
+                Some(_) => {
+                    let name = self.take_name();
+                    if name.is_empty() {
+                        return Err(
+                            self.error(offset, "unexpected character in the attribute list")
+                        );
+                    }
+                    attr_names.push(name);
+                }
+            }
+        }
+        if attr_names.is_empty() {
+            // points at the closing bracket
+            let offset = self.offset() - 1;
+            return Err(self.error(offset, "empty attribute list"));
         }
+        self.skip_while(char::is_whitespace);
+        if self.chars.peek().is_some() {
+            let offset = self.offset();
+            return Err(self.error(offset, "unexpected input after `]`"));
+        }
+        let element_scope = (!element.is_empty()).then_some(element);
+        Ok(AttrMatcher::new(element_scope, &attr_names))
     }
 }
 
 /// Matches nodes with a specific namespace and checks if the attribute matches.
+///
+/// Foreign content is parsed into its own namespace, so
+/// `NsAttrMatcher::new("http://www.w3.org/1998/Math/MathML", &["href"])` matches `href` on MathML elements only.
+///
+/// Attribute names are compared ASCII case-insensitively, unless [`NsAttrMatcher::case_sensitive`] is set.
 pub struct NsAttrMatcher {
     /// The namespace of the element to match.
     pub ns: Namespace,
     /// The local names of the attributes to match.
     pub attr_names: Vec<LocalName>,
+    /// Whether attribute names are compared case-sensitively.
+    pub case_sensitive: bool,
 }
 
 impl AttrChecker for NsAttrMatcher {
@@ -99,7 +269,7 @@ impl AttrChecker for NsAttrMatcher {
         if !node.qual_name_ref().is_some_and(|name| name.ns == self.ns) {
             return false;
         }
-        self.attr_names.contains(&attr.name.local)
+        attr_name_in(&self.attr_names, &attr.name.local, self.case_sensitive)
     }
 }
 
@@ -117,11 +287,87 @@ impl NsAttrMatcher {
                 .iter()
                 .map(|name| LocalName::from(*name))
                 .collect(),
+            case_sensitive: false,
+        }
+    }
+
+    /// Sets whether attribute names are compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+}
+
+/// Matches attributes of elements in a specific namespace with one of the specified local names,
+/// e.g. `href` on SVG `use` elements only.
+///
+/// An attribute matches if all of these hold:
+/// - the element is in the namespace;
+/// - the local name of the element is one of the element names (compared exactly, as the parser
+///   already normalizes the case of foreign element names, e.g. `linearGradient`);
+/// - the local name of the attribute is one of the attribute names, ignoring its namespace,
+///   so `href` matches `xlink:href` too.
+///
+/// This is finer-grained than [`NsAttrMatcher`], which matches the attributes on every element of the namespace.
+/// Attribute names are compared ASCII case-insensitively, unless [`NsScopedAttrMatcher::case_sensitive`] is set.
+pub struct NsScopedAttrMatcher {
+    /// The namespace of the element to match.
+    pub ns: Namespace,
+    /// The local names of the elements to match.
+    pub element_names: Vec<LocalName>,
+    /// The local names of the attributes to match.
+    pub attr_names: Vec<LocalName>,
+    /// Whether attribute names are compared case-sensitively.
+    pub case_sensitive: bool,
+}
+
+impl AttrChecker for NsScopedAttrMatcher {
+    fn is_match_attr(&self, node: &NodeRef, attr: &Attribute) -> bool {
+        if !node
+            .qual_name_ref()
+            .is_some_and(|name| name.ns == self.ns && self.element_names.contains(&name.local))
+        {
+            return false;
+        }
+        attr_name_in(&self.attr_names, &attr.name.local, self.case_sensitive)
+    }
+}
+
+impl NsScopedAttrMatcher {
+    /// Creates a new `NsScopedAttrMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `ns` - The namespace of the element to match.
+    /// * `element_names` - The local names of the elements to match.
+    /// * `attr_names` - The local names of the attributes to match.
+    pub fn new(ns: &str, element_names: &[&str], attr_names: &[&str]) -> Self {
+        Self {
+            ns: Namespace::from(ns),
+            element_names: element_names
+                .iter()
+                .map(|name| LocalName::from(*name))
+                .collect(),
+            attr_names: attr_names
+                .iter()
+                .map(|name| LocalName::from(*name))
+                .collect(),
+            case_sensitive: false,
         }
     }
+
+    /// Sets whether attribute names are compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
 }
 
 /// A matcher that checks if a node's namespace matches the specified namespace.
+///
+/// The parser places `svg` and `math` subtrees in the SVG and MathML namespaces,
+/// so this matcher covers every element of such a subtree, but not the HTML elements
+/// that break out of it (e.g. a `p` inside `math`).
 pub struct NamespaceMatcher(pub Namespace);
 
 impl NamespaceMatcher {
@@ -133,7 +379,12 @@ impl NamespaceMatcher {
     ///
     /// # Examples
     ///
+    /// ```
+    /// use dom_sanitizer::plugin_policy::preset::NamespaceMatcher;
+    ///
     /// let svg_matcher = NamespaceMatcher::new("http://www.w3.org/2000/svg");
+    /// let mathml_matcher = NamespaceMatcher::new("http://www.w3.org/1998/Math/MathML");
+    /// ```
     pub fn new(namespace: &str) -> Self {
         Self(Namespace::from(namespace))
     }
@@ -143,4 +394,1555 @@ impl NodeChecker for NamespaceMatcher {
     fn is_match(&self, node: &NodeRef) -> bool {
         node.qual_name_ref().is_some_and(|name| name.ns == self.0)
     }
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::Low
+    }
+}
+
+/// Matches nodes by their qualified name: both namespace and local name must match.
+///
+/// Unlike [`LocalNameMatcher`], which ignores the namespace, it distinguishes elements sharing
+/// a local name across namespaces, e.g. the HTML `<title>` in `<head>` and the SVG `<title>` inside `<svg>`.
+/// It is equivalent to a single check combining [`LocalNameMatcher`] and [`NamespaceMatcher`],
+/// which cannot be expressed with separate checkers, since any matching checker matches the node.
+pub struct QualNameMatcher {
+    /// The namespace of the element to match.
+    pub ns: Namespace,
+    /// The local name of the element to match.
+    pub local: LocalName,
+}
+
+impl NodeChecker for QualNameMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.qual_name_ref()
+            .is_some_and(|qual_name| qual_name.ns == self.ns && qual_name.local == self.local)
+    }
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::Low
+    }
+    fn scope_local_name(&self) -> Option<LocalName> {
+        Some(self.local.clone())
+    }
+}
+
+impl QualNameMatcher {
+    /// Creates a new `QualNameMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `ns` - The namespace of the element to match.
+    /// * `local` - The local name of the element to match.
+    pub fn new(ns: &str, local: &str) -> Self {
+        Self {
+            ns: Namespace::from(ns),
+            local: LocalName::from(local),
+        }
+    }
+}
+
+/// Matches element nodes that have more attributes than the specified threshold.
+///
+/// This is a cheap heuristic for flagging suspicious (obfuscated or machine-generated) nodes.
+/// Attributes are counted after parsing, so duplicate attributes are already collapsed
+/// into one by the parser.
+pub struct AttrCountMatcher {
+    /// The local name of the element to match. If `None`, matches any element.
+    pub element_scope: Option<LocalName>,
+    /// The maximum number of attributes an element may have without being matched.
+    pub threshold: usize,
+}
+
+impl NodeChecker for AttrCountMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.query_or(false, |n| {
+            let Some(el) = n.as_element() else {
+                return false;
+            };
+            if let Some(ref element_scope) = self.element_scope {
+                if &el.name.local != element_scope {
+                    return false;
+                }
+            }
+            el.attrs.len() > self.threshold
+        })
+    }
+}
+
+impl AttrCountMatcher {
+    /// Creates a new `AttrCountMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `element_scope` - The name of the element to match. If `None`, matches any element.
+    /// * `threshold` - The maximum number of attributes allowed; elements with more attributes are matched.
+    pub fn new(element_scope: Option<&str>, threshold: usize) -> Self {
+        Self {
+            element_scope: element_scope.map(LocalName::from),
+            threshold,
+        }
+    }
+}
+
+/// Matches element nodes having an attribute with a value longer than the specified threshold,
+/// a cheap heuristic for flagging nodes carrying encoded (e.g. base64) or obfuscated payloads.
+///
+/// It matches the whole element, e.g. to remove it with its contents, rather than the attribute:
+/// unlike capping the attribute value (see [`crate::PolicyBuilder::cap_attr_tokens`]), no part of
+/// the suspicious node is kept. The length is measured in bytes. By default every attribute is checked;
+/// [`LongAttrMatcher::scoped`] limits the check to some attribute names, compared ASCII case-insensitively.
+///
+/// # Examples
+///
+/// ```rust
+/// use dom_sanitizer::plugin_policy::preset::LongAttrMatcher;
+///
+/// // matches elements with any attribute value longer than 2048 bytes
+/// let any_attr = LongAttrMatcher::new(2048);
+/// // only checks `href` and `src`
+/// let urls = LongAttrMatcher::scoped(&["href", "src"], 2048);
+/// ```
+pub struct LongAttrMatcher {
+    /// The local names of the attributes to check. If empty, all attributes are checked.
+    pub attr_names: Vec<LocalName>,
+    /// The maximum length of an attribute value, in bytes, without the element being matched.
+    pub max_len: usize,
+}
+
+impl NodeChecker for LongAttrMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.query_or(false, |n| {
+            let Some(el) = n.as_element() else {
+                return false;
+            };
+            el.attrs.iter().any(|a| {
+                a.value.len() > self.max_len
+                    && (self.attr_names.is_empty()
+                        || attr_name_in(&self.attr_names, &a.name.local, false))
+            })
+        })
+    }
+}
+
+impl LongAttrMatcher {
+    /// Creates a new `LongAttrMatcher` instance checking all attributes.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_len` - The maximum length of an attribute value; elements with a longer value are matched.
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            attr_names: vec![],
+            max_len,
+        }
+    }
+
+    /// Creates a new `LongAttrMatcher` instance checking only the specified attributes.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr_names` - The local names of the attributes to check.
+    /// * `max_len` - The maximum length of an attribute value; elements with a longer value are matched.
+    pub fn scoped(attr_names: &[&str], max_len: usize) -> Self {
+        Self {
+            attr_names: attr_names
+                .iter()
+                .map(|name| LocalName::from(*name))
+                .collect(),
+            max_len,
+        }
+    }
+}
+
+/// Matches an attribute by its value against a fixed vocabulary of allowed values,
+/// e.g. `dir` ∈ {`ltr`, `rtl`, `auto`}.
+///
+/// Values are compared ASCII case-insensitively, after trimming surrounding whitespace,
+/// as HTML enumerated attributes are case-insensitive.
+///
+/// Since a checker only returns a boolean and the directive decides what it means,
+/// the matcher has two modes:
+/// - [`AttrValueAllowlistMatcher::new`] matches attributes with an **allowed** value.
+///   Use it with the [`crate::Restrictive`] directive, which keeps matched attributes,
+///   so an invalid value is dropped.
+/// - [`AttrValueAllowlistMatcher::invalid`] matches attributes with a value **not** in the allowlist.
+///   Use it with the [`crate::Permissive`] directive, which removes matched attributes.
+pub struct AttrValueAllowlistMatcher {
+    /// The local name of the attribute to match.
+    pub attr_name: LocalName,
+    /// The list of allowed values.
+    pub allowed_values: Vec<String>,
+    /// If `true`, matches attributes whose value is not in the allowlist.
+    pub match_invalid: bool,
+    /// Whether the attribute name is compared case-sensitively.
+    pub case_sensitive: bool,
+}
+
+impl AttrChecker for AttrValueAllowlistMatcher {
+    fn is_match_attr(&self, _node: &NodeRef, attr: &Attribute) -> bool {
+        if !attr_name_eq(&self.attr_name, &attr.name.local, self.case_sensitive) {
+            return false;
+        }
+        let value = attr.value.trim();
+        let is_allowed = self
+            .allowed_values
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(value));
+        is_allowed != self.match_invalid
+    }
+}
+
+impl AttrValueAllowlistMatcher {
+    /// Creates a new `AttrValueAllowlistMatcher` instance that matches attributes with an allowed value.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr_name` - The local name of the attribute to match.
+    /// * `allowed_values` - The list of allowed values.
+    pub fn new(attr_name: &str, allowed_values: &[&str]) -> Self {
+        Self {
+            attr_name: LocalName::from(attr_name),
+            allowed_values: allowed_values.iter().map(|v| v.to_string()).collect(),
+            match_invalid: false,
+            case_sensitive: false,
+        }
+    }
+
+    /// Sets whether the attribute name is compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Creates a new `AttrValueAllowlistMatcher` instance that matches attributes with a value
+    /// which is not in the allowlist.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr_name` - The local name of the attribute to match.
+    /// * `allowed_values` - The list of allowed values.
+    pub fn invalid(attr_name: &str, allowed_values: &[&str]) -> Self {
+        Self {
+            match_invalid: true,
+            ..Self::new(attr_name, allowed_values)
+        }
+    }
+}
+
+/// Matches event handler attributes: any attribute whose name starts with `on`
+/// (compared ASCII case-insensitively), e.g. `onclick`, `onerror`, `onload`.
+///
+/// It applies to elements of any namespace, including SVG and MathML.
+/// Use it with the [`crate::Permissive`] directive to remove event handlers.
+/// Under the [`crate::Restrictive`] directive, event handlers are removed unless they are kept by other checkers.
+pub struct EventHandlerMatcher;
+
+impl AttrChecker for EventHandlerMatcher {
+    fn is_match_attr(&self, _node: &NodeRef, attr: &Attribute) -> bool {
+        attr.name
+            .local
+            .as_bytes()
+            .get(..2)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(b"on"))
+    }
+}
+
+/// An operation used by [`AttrValue`] to compare an attribute value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttrValueOp {
+    /// The attribute value is equal to the specified value.
+    Equals,
+    /// The attribute value starts with the specified value.
+    Prefix,
+    /// The attribute value ends with the specified value.
+    Suffix,
+    /// The attribute value contains the specified value.
+    Contains,
+}
+
+/// A predicate over an attribute value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrValue {
+    /// The comparison operation.
+    pub op: AttrValueOp,
+    /// The value to compare with.
+    pub value: String,
+}
+
+impl AttrValue {
+    /// Creates a new `AttrValue` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `op` - The comparison operation.
+    /// * `value` - The value to compare with.
+    pub fn new(op: AttrValueOp, value: &str) -> Self {
+        Self {
+            op,
+            value: value.to_string(),
+        }
+    }
+
+    /// Returns `true` if the attribute value satisfies the predicate. Comparison is case-sensitive.
+    pub fn is_match(&self, value: &str) -> bool {
+        match self.op {
+            AttrValueOp::Equals => value == self.value,
+            AttrValueOp::Prefix => value.starts_with(self.value.as_str()),
+            AttrValueOp::Suffix => value.ends_with(self.value.as_str()),
+            AttrValueOp::Contains => value.contains(self.value.as_str()),
+        }
+    }
+}
+
+/// Matches elements with a specific local name, having an attribute whose value
+/// satisfies the [`AttrValue`] predicate.
+///
+/// Attribute names are compared ASCII case-insensitively, unless [`ElementWithAttr::case_sensitive`] is set.
+///
+/// # Examples
+///
+/// ```rust
+/// use dom_sanitizer::plugin_policy::preset::{AttrValue, AttrValueOp, ElementWithAttr};
+///
+/// // matches `<a href="https://...">`
+/// let secure_links = ElementWithAttr::new("a", "href", AttrValue::new(AttrValueOp::Prefix, "https://"));
+/// ```
+pub struct ElementWithAttr {
+    /// The local name of the element to match.
+    pub element: LocalName,
+    /// The local name of the attribute to check.
+    pub attr_name: LocalName,
+    /// The predicate over the attribute value.
+    pub value: AttrValue,
+    /// Whether attribute names are compared case-sensitively.
+    pub case_sensitive: bool,
+}
+
+impl NodeChecker for ElementWithAttr {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.query_or(false, |n| {
+            let Some(el) = n.as_element() else {
+                return false;
+            };
+            if el.name.local != self.element {
+                return false;
+            }
+            el.attrs
+                .iter()
+                .find(|a| attr_name_eq(&self.attr_name, &a.name.local, self.case_sensitive))
+                .is_some_and(|a| self.value.is_match(&a.value))
+        })
+    }
+
+    fn scope_local_name(&self) -> Option<LocalName> {
+        Some(self.element.clone())
+    }
+}
+
+impl ElementWithAttr {
+    /// Creates a new `ElementWithAttr` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `element` - The local name of the element to match.
+    /// * `attr_name` - The local name of the attribute to check.
+    /// * `value` - The predicate over the attribute value.
+    pub fn new(element: &str, attr_name: &str, value: AttrValue) -> Self {
+        Self {
+            element: LocalName::from(element),
+            attr_name: LocalName::from(attr_name),
+            value,
+            case_sensitive: false,
+        }
+    }
+
+    /// Sets whether attribute names are compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+}
+
+/// Matches elements with a specific local name, having an attribute whose value matches a regular expression,
+/// e.g. links pointing to tracking domains.
+///
+/// Only the named attribute of the scoped elements is checked, so it is cheaper than matching
+/// a pattern over every attribute. The matcher implements both checker traits:
+/// - as a [`NodeChecker`], it matches the element, e.g. to remove a tracking link;
+/// - as an [`AttrChecker`], it matches only the attribute, e.g. to drop a tracking `href`
+///   under the [`crate::Permissive`] directive while keeping the link text.
+///
+/// Attribute names are compared ASCII case-insensitively, unless [`RegexScopedAttrMatcher::case_sensitive`] is set.
+/// Only available with the `regex` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use dom_sanitizer::plugin_policy::preset::RegexScopedAttrMatcher;
+/// use regex::Regex;
+///
+/// // matches `<a href="https://tracker.example/...">`
+/// let tracking_links = RegexScopedAttrMatcher::new(
+///     "a",
+///     "href",
+///     Regex::new(r"^https?://([^/]+\.)?tracker\.example/").unwrap(),
+/// );
+/// ```
+#[cfg(feature = "regex")]
+pub struct RegexScopedAttrMatcher {
+    /// The local name of the element to match.
+    pub element: LocalName,
+    /// The local name of the attribute to check.
+    pub attr_name: LocalName,
+    /// The regular expression the attribute value must match.
+    pub regex: regex::Regex,
+    /// Whether attribute names are compared case-sensitively.
+    pub case_sensitive: bool,
+}
+
+#[cfg(feature = "regex")]
+impl NodeChecker for RegexScopedAttrMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.query_or(false, |n| {
+            let Some(el) = n.as_element() else {
+                return false;
+            };
+            if el.name.local != self.element {
+                return false;
+            }
+            el.attrs
+                .iter()
+                .find(|a| attr_name_eq(&self.attr_name, &a.name.local, self.case_sensitive))
+                .is_some_and(|a| self.regex.is_match(&a.value))
+        })
+    }
+
+    fn scope_local_name(&self) -> Option<LocalName> {
+        Some(self.element.clone())
+    }
+}
+
+#[cfg(feature = "regex")]
+impl AttrChecker for RegexScopedAttrMatcher {
+    fn is_match_attr(&self, node: &NodeRef, attr: &Attribute) -> bool {
+        attr_name_eq(&self.attr_name, &attr.name.local, self.case_sensitive)
+            && node.has_name(&self.element)
+            && self.regex.is_match(&attr.value)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl RegexScopedAttrMatcher {
+    /// Creates a new `RegexScopedAttrMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `element` - The local name of the element to match.
+    /// * `attr_name` - The local name of the attribute to check.
+    /// * `regex` - The regular expression the attribute value must match.
+    pub fn new(element: &str, attr_name: &str, regex: regex::Regex) -> Self {
+        Self {
+            element: LocalName::from(element),
+            attr_name: LocalName::from(attr_name),
+            regex,
+            case_sensitive: false,
+        }
+    }
+
+    /// Sets whether attribute names are compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+}
+
+/// Matches elements having a specific `data-*` attribute, optionally with a value
+/// satisfying the [`AttrValue`] predicate, e.g. to remove nodes flagged by the source's own data attributes.
+///
+/// The key is given without the `data-` prefix. Attribute names are compared ASCII case-insensitively,
+/// unless [`DataAttrMatcher::case_sensitive`] is set: `DataAttrMatcher::new("tracking", None)` matches `data-tracking`
+/// and `DATA-Tracking` (which the parser lowercases anyway on HTML elements). The matcher iterates the attributes of the element looking for the one
+/// named `data-<key>` without a namespace; if there is a value predicate, the attribute must satisfy it.
+///
+/// # Examples
+///
+/// ```rust
+/// use dom_sanitizer::plugin_policy::preset::{AttrValue, AttrValueOp, DataAttrMatcher};
+///
+/// // matches `<div data-tracking="true">`
+/// let tracked = DataAttrMatcher::new("tracking", Some(AttrValue::new(AttrValueOp::Equals, "true")));
+/// ```
+pub struct DataAttrMatcher {
+    /// The full name of the attribute to look for, including the `data-` prefix.
+    pub attr_name: LocalName,
+    /// The predicate over the attribute value; `None` matches any value.
+    pub value: Option<AttrValue>,
+    /// Whether attribute names are compared case-sensitively.
+    pub case_sensitive: bool,
+}
+
+impl NodeChecker for DataAttrMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.query_or(false, |n| {
+            let Some(el) = n.as_element() else {
+                return false;
+            };
+            el.attrs
+                .iter()
+                .find(|a| {
+                    a.name.ns == html5ever::ns!()
+                        && attr_name_eq(&self.attr_name, &a.name.local, self.case_sensitive)
+                })
+                .is_some_and(|a| {
+                    self.value
+                        .as_ref()
+                        .map_or(true, |value| value.is_match(&a.value))
+                })
+        })
+    }
+}
+
+impl DataAttrMatcher {
+    /// Creates a new `DataAttrMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the `data-*` attribute, without the `data-` prefix, e.g. `"tracking"`.
+    /// * `value` - The predicate over the attribute value; `None` matches any value.
+    pub fn new(key: &str, value: Option<AttrValue>) -> Self {
+        Self {
+            attr_name: LocalName::from(format!("data-{key}")),
+            value,
+            case_sensitive: false,
+        }
+    }
+
+    /// Sets whether attribute names are compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+}
+
+/// Matches nodes having an ancestor that matches the CSS selector, e.g. `WithinMatcher::new(".comment")`
+/// for any node inside a comment.
+///
+/// The checkers added to a builder are alternatives: a node matching any of them is matched.
+/// To remove only `<img>` inside comments, the name check and this matcher must be combined
+/// in a wrapping [`NodeChecker`] that requires both, or expressed with [`ScopedSelectorMatcher`]:
+/// adding a separate name checker would remove every `<img>` of the document.
+///
+/// The selector is compiled once, at construction. Matching walks all ancestors of every checked node
+/// up to the document root and tests each of them against the selector, so its cost grows with the depth
+/// of the tree and the complexity of the selector. Prefer name-based checkers when they are sufficient.
+pub struct WithinMatcher {
+    /// The compiled selector for the ancestor.
+    pub ancestor: Matcher,
+}
+
+impl NodeChecker for WithinMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.ancestors_it(None)
+            .any(|ancestor| ancestor.is_element() && ancestor.is_match(&self.ancestor))
+    }
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::High
+    }
+}
+
+impl WithinMatcher {
+    /// Creates a new `WithinMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The CSS selector an ancestor of the node must match.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SelectorError`] if the selector is invalid.
+    pub fn new(selector: &str) -> Result<Self, SelectorError> {
+        Ok(Self {
+            ancestor: compile_selector(selector)?,
+        })
+    }
+}
+
+/// Matches elements that match a CSS selector and have an ancestor matching another one,
+/// e.g. `ScopedSelectorMatcher::new(".comment", "img")` for images inside comments.
+///
+/// Both selectors are compiled once, at construction. The self selector is tested first, so only
+/// matching elements pay for the ancestor walk, which tests every ancestor up to the document root
+/// like [`WithinMatcher`]. Unlike a descendant combinator (`.comment img`) in a single selector,
+/// the ancestor may be matched by any selector, and the two parts can be reused separately.
+pub struct ScopedSelectorMatcher {
+    /// The compiled selector for the ancestor.
+    pub ancestor: Matcher,
+    /// The compiled selector for the element itself.
+    pub selector: Matcher,
+}
+
+impl NodeChecker for ScopedSelectorMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.is_element()
+            && node.is_match(&self.selector)
+            && node
+                .ancestors_it(None)
+                .any(|ancestor| ancestor.is_element() && ancestor.is_match(&self.ancestor))
+    }
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::High
+    }
+}
+
+impl ScopedSelectorMatcher {
+    /// Creates a new `ScopedSelectorMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `ancestor` - The CSS selector an ancestor of the node must match.
+    /// * `selector` - The CSS selector the node itself must match.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SelectorError`] if either selector is invalid.
+    pub fn new(ancestor: &str, selector: &str) -> Result<Self, SelectorError> {
+        Ok(Self {
+            ancestor: compile_selector(ancestor)?,
+            selector: compile_selector(selector)?,
+        })
+    }
+}
+
+/// The position of an element among its element siblings, used by [`NthChildMatcher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NthChild {
+    /// The first element child of its parent.
+    First,
+    /// The last element child of its parent.
+    Last,
+    /// The element child at the specified 1-based position, like the CSS `:nth-child(n)`.
+    Nth(usize),
+}
+
+/// Matches elements by their position among element siblings.
+///
+/// Only element siblings are counted, text and comments are ignored.
+/// [`NthChild::First`] and [`NthChild::Last`] need a single sibling lookup, while
+/// [`NthChild::Nth`] walks the previous siblings of every checked element to compute its index,
+/// which makes it linear in the number of preceding siblings.
+///
+/// Positions are computed on the live tree while it is being sanitized. Siblings are visited
+/// in document order, so removing or unwrapping a matched element shifts the positions of the
+/// following siblings, which are checked afterwards: e.g. removing [`NthChild::First`] elements
+/// removes every sibling one after another. [`NthChild::Last`] is not affected, as the following
+/// siblings are still untouched when an element is checked.
+pub struct NthChildMatcher {
+    /// The local name of the element to match. If `None`, matches any element.
+    pub element_scope: Option<LocalName>,
+    /// The position to match.
+    pub position: NthChild,
+}
+
+impl NodeChecker for NthChildMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        if !node.is_element() {
+            return false;
+        }
+        if let Some(ref element_scope) = self.element_scope {
+            if !node
+                .qual_name_ref()
+                .is_some_and(|name| &name.local == element_scope)
+            {
+                return false;
+            }
+        }
+        match self.position {
+            NthChild::First => node.prev_element_sibling().is_none(),
+            NthChild::Last => node.next_element_sibling().is_none(),
+            NthChild::Nth(n) => {
+                let mut index = 1;
+                let mut prev = node.prev_element_sibling();
+                while let Some(sibling) = prev {
+                    index += 1;
+                    if index > n {
+                        return false;
+                    }
+                    prev = sibling.prev_element_sibling();
+                }
+                index == n
+            }
+        }
+    }
+}
+
+impl NthChildMatcher {
+    /// Creates a new `NthChildMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `element_scope` - The name of the element to match. If `None`, matches any element.
+    /// * `position` - The position of the element among its element siblings.
+    pub fn new(element_scope: Option<&str>, position: NthChild) -> Self {
+        Self {
+            element_scope: element_scope.map(LocalName::from),
+            position,
+        }
+    }
+}
+
+/// Matches URL attributes (e.g. `href`, `src`, `xlink:href`) by the scheme of their value.
+///
+/// The scheme is extracted like browsers do, after removing ASCII whitespace and control characters,
+/// so obfuscated values like `java\tscript:` are recognized. Values without a scheme
+/// (relative URLs and fragments) are always considered safe.
+///
+/// - [`UrlSchemeMatcher::new`] matches attributes with a safe value: relative, or with an allowed scheme.
+///   Use it with the [`crate::Restrictive`] directive, which keeps matched attributes.
+/// - [`UrlSchemeMatcher::disallowed`] matches attributes with a scheme that is not allowed.
+///   Use it with the [`crate::Permissive`] directive, which removes matched attributes.
+pub struct UrlSchemeMatcher {
+    /// The local names of the attributes to match.
+    pub attr_names: Vec<LocalName>,
+    /// The list of allowed schemes, in lowercase.
+    pub allowed_schemes: Vec<String>,
+    /// If `true`, matches attributes with a scheme that is not allowed.
+    pub match_disallowed: bool,
+    /// Whether attribute names are compared case-sensitively.
+    pub case_sensitive: bool,
+}
+
+impl AttrChecker for UrlSchemeMatcher {
+    fn is_match_attr(&self, _node: &NodeRef, attr: &Attribute) -> bool {
+        if !attr_name_in(&self.attr_names, &attr.name.local, self.case_sensitive) {
+            return false;
+        }
+        let is_allowed = crate::url::normalized_scheme(&attr.value)
+            .map_or(true, |scheme| self.allowed_schemes.contains(&scheme));
+        is_allowed != self.match_disallowed
+    }
+}
+
+impl UrlSchemeMatcher {
+    /// Creates a new `UrlSchemeMatcher` instance that matches attributes with a safe value.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr_names` - The local names of the attributes to match.
+    /// * `allowed_schemes` - The list of allowed schemes, e.g. `&["http", "https"]`, compared case-insensitively.
+    pub fn new(attr_names: &[&str], allowed_schemes: &[&str]) -> Self {
+        Self {
+            attr_names: attr_names
+                .iter()
+                .map(|name| LocalName::from(*name))
+                .collect(),
+            allowed_schemes: allowed_schemes
+                .iter()
+                .map(|scheme| scheme.to_ascii_lowercase())
+                .collect(),
+            match_disallowed: false,
+            case_sensitive: false,
+        }
+    }
+
+    /// Sets whether attribute names are compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Creates a new `UrlSchemeMatcher` instance that matches attributes with a scheme that is not allowed.
+    ///
+    /// # Arguments
+    ///
+    /// * `attr_names` - The local names of the attributes to match.
+    /// * `allowed_schemes` - The list of allowed schemes, e.g. `&["http", "https"]`, compared case-insensitively.
+    pub fn disallowed(attr_names: &[&str], allowed_schemes: &[&str]) -> Self {
+        Self {
+            match_disallowed: true,
+            ..Self::new(attr_names, allowed_schemes)
+        }
+    }
+}
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+
+/// SVG elements kept by [`svg_safe_policy`].
+const SVG_SAFE_ELEMENTS: &[&str] = &[
+    "svg",
+    "g",
+    "defs",
+    "symbol",
+    "use",
+    "title",
+    "desc",
+    "path",
+    "rect",
+    "circle",
+    "ellipse",
+    "line",
+    "polyline",
+    "polygon",
+    "text",
+    "tspan",
+    "linearGradient",
+    "radialGradient",
+    "stop",
+    "clipPath",
+    "mask",
+    "pattern",
+];
+
+/// Attributes kept by [`svg_safe_policy`].
+const SVG_SAFE_ATTRS: &[&str] = &[
+    "id",
+    "class",
+    "viewBox",
+    "preserveAspectRatio",
+    "width",
+    "height",
+    "x",
+    "y",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "cx",
+    "cy",
+    "r",
+    "rx",
+    "ry",
+    "d",
+    "points",
+    "transform",
+    "offset",
+    "gradientUnits",
+    "gradientTransform",
+    "patternUnits",
+    "patternTransform",
+    "clipPathUnits",
+    "maskUnits",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "stroke",
+    "stroke-width",
+    "stroke-opacity",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "opacity",
+    "stop-color",
+    "stop-opacity",
+    "clip-path",
+    "clip-rule",
+    "mask",
+    "font-family",
+    "font-size",
+    "font-weight",
+    "text-anchor",
+    "dx",
+    "dy",
+];
+
+/// Creates a [`PluginPolicy`](super::PluginPolicy) for inline SVG, using the [`crate::Restrictive`] directive.
+///
+/// The policy keeps:
+/// - SVG namespace elements: `svg`, `g`, `defs`, `symbol`, `use`, `title`, `desc`, `path`, `rect`,
+///   `circle`, `ellipse`, `line`, `polyline`, `polygon`, `text`, `tspan`, `linearGradient`,
+///   `radialGradient`, `stop`, `clipPath`, `mask` and `pattern`;
+/// - geometry and presentation attributes: `id`, `class`, `viewBox`, `preserveAspectRatio`,
+///   `width`, `height`, `x`, `y`, `x1`, `y1`, `x2`, `y2`, `cx`, `cy`, `r`, `rx`, `ry`, `d`, `points`,
+///   `transform`, `offset`, `dx`, `dy`, gradient, pattern, clip and mask units and transforms,
+///   `fill*`, `stroke*`, `opacity`, `stop-color`, `stop-opacity`, `clip-path`, `clip-rule`, `mask`,
+///   `font-family`, `font-size`, `font-weight` and `text-anchor`;
+/// - `href` and `xlink:href` only with a relative value (e.g. a `#fragment` reference), never with a scheme.
+///
+/// `script`, `style` and `foreignObject` elements are removed with their contents.
+/// All other elements, including HTML elements, are unwrapped, keeping their text,
+/// and all other attributes are removed: event handlers (`on*`), `style`, etc.
+pub fn svg_safe_policy() -> super::PluginPolicy<crate::Restrictive> {
+    let mut builder = super::PluginPolicy::builder()
+        .remove(LocalNamesMatcher::new(&[
+            "script",
+            "style",
+            "foreignObject",
+        ]))
+        .exclude_attr(AttrMatcher::new(None, SVG_SAFE_ATTRS))
+        .exclude_attr(UrlSchemeMatcher::new(&["href"], &[]));
+    for name in SVG_SAFE_ELEMENTS {
+        builder = builder.exclude(QualNameMatcher::new(SVG_NS, name));
+    }
+    builder.build()
+}
+
+const MATHML_NS: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// MathML elements kept by [`mathml_safe_policy`].
+const MATHML_SAFE_ELEMENTS: &[&str] = &[
+    "math",
+    "mi",
+    "mn",
+    "mo",
+    "ms",
+    "mtext",
+    "mspace",
+    "mrow",
+    "mfrac",
+    "msqrt",
+    "mroot",
+    "mstyle",
+    "merror",
+    "mpadded",
+    "mphantom",
+    "msub",
+    "msup",
+    "msubsup",
+    "munder",
+    "mover",
+    "munderover",
+    "mmultiscripts",
+    "mprescripts",
+    "none",
+    "mtable",
+    "mtr",
+    "mtd",
+    "semantics",
+    "annotation",
+];
+
+/// Attributes kept by [`mathml_safe_policy`].
+const MATHML_SAFE_ATTRS: &[&str] = &[
+    "id",
+    "class",
+    "dir",
+    "display",
+    "displaystyle",
+    "scriptlevel",
+    "mathvariant",
+    "mathsize",
+    "fence",
+    "separator",
+    "stretchy",
+    "symmetric",
+    "largeop",
+    "movablelimits",
+    "accent",
+    "accentunder",
+    "lspace",
+    "rspace",
+    "minsize",
+    "maxsize",
+    "linethickness",
+    "width",
+    "height",
+    "depth",
+    "voffset",
+    "rowspan",
+    "columnspan",
+    "rowalign",
+    "columnalign",
+    "encoding",
+];
+
+/// Creates a [`PluginPolicy`](super::PluginPolicy) for inline MathML, using the [`crate::Restrictive`] directive.
+///
+/// The policy keeps:
+/// - MathML namespace elements: `math`, token elements (`mi`, `mn`, `mo`, `ms`, `mtext`, `mspace`),
+///   layout elements (`mrow`, `mfrac`, `msqrt`, `mroot`, `mstyle`, `merror`, `mpadded`, `mphantom`),
+///   scripts (`msub`, `msup`, `msubsup`, `munder`, `mover`, `munderover`, `mmultiscripts`,
+///   `mprescripts`, `none`), tables (`mtable`, `mtr`, `mtd`), `semantics` and `annotation`;
+/// - layout and presentation attributes: `id`, `class`, `dir`, `display`, `displaystyle`, `scriptlevel`,
+///   `mathvariant`, `mathsize`, operator attributes (`fence`, `separator`, `stretchy`, `symmetric`,
+///   `largeop`, `movablelimits`, `accent`, `accentunder`, `lspace`, `rspace`, `minsize`, `maxsize`),
+///   `linethickness`, `width`, `height`, `depth`, `voffset`, `rowspan`, `columnspan`, `rowalign`,
+///   `columnalign` and `encoding`.
+///
+/// `script`, `style` and `annotation-xml` elements are removed with their contents:
+/// `annotation-xml` may embed HTML or SVG. All other elements, including `maction` and HTML elements,
+/// are unwrapped, keeping their text, and all other attributes are removed: event handlers (`on*`),
+/// `href`, `style`, `actiontype`, etc.
+pub fn mathml_safe_policy() -> super::PluginPolicy<crate::Restrictive> {
+    let mut builder = super::PluginPolicy::builder()
+        .remove(LocalNamesMatcher::new(&[
+            "script",
+            "style",
+            "annotation-xml",
+        ]))
+        .exclude_attr(NsAttrMatcher::new(MATHML_NS, MATHML_SAFE_ATTRS));
+    for name in MATHML_SAFE_ELEMENTS {
+        builder = builder.exclude(QualNameMatcher::new(MATHML_NS, name));
+    }
+    builder.build()
+}
+
+/// Matches an attribute only when its element also has (or lacks) another attribute,
+/// e.g. `src` on an `iframe` depending on the presence of `sandbox`.
+///
+/// The meaning of a match depends on the directive:
+/// - with [`crate::Restrictive`], matched attributes are kept, so
+///   `ConditionalAttrMatcher::when_present(Some("iframe"), "src", "sandbox")` keeps `src` only on sandboxed frames;
+/// - with [`crate::Permissive`], matched attributes are removed, so
+///   `ConditionalAttrMatcher::when_absent(Some("iframe"), "src", "sandbox")` drops `src` unless the frame is sandboxed.
+///
+/// The condition is checked against the attributes the element has when the checker runs.
+pub struct ConditionalAttrMatcher {
+    /// The local name of the element to match. If `None`, matches any element.
+    pub element_scope: Option<LocalName>,
+    /// The local name of the attribute to match.
+    pub attr_name: LocalName,
+    /// The local name of the attribute the condition is about.
+    pub condition_attr: LocalName,
+    /// If `true`, the condition attribute must be present; otherwise it must be absent.
+    pub require_present: bool,
+    /// Whether attribute names are compared case-sensitively.
+    pub case_sensitive: bool,
+}
+
+impl AttrChecker for ConditionalAttrMatcher {
+    fn is_match_attr(&self, node: &NodeRef, attr: &Attribute) -> bool {
+        if !attr_name_eq(&self.attr_name, &attr.name.local, self.case_sensitive) {
+            return false;
+        }
+        node.query_or(false, |n| {
+            let Some(el) = n.as_element() else {
+                return false;
+            };
+            if let Some(ref element_scope) = self.element_scope {
+                if &el.name.local != element_scope {
+                    return false;
+                }
+            }
+            let has_condition = el
+                .attrs
+                .iter()
+                .any(|a| attr_name_eq(&self.condition_attr, &a.name.local, self.case_sensitive));
+            has_condition == self.require_present
+        })
+    }
+}
+
+impl ConditionalAttrMatcher {
+    /// Creates a new `ConditionalAttrMatcher` instance that matches the attribute
+    /// when the element has the condition attribute.
+    ///
+    /// # Arguments
+    ///
+    /// * `element_scope` - The name of the element to match. If `None`, matches any element.
+    /// * `attr_name` - The local name of the attribute to match.
+    /// * `condition_attr` - The local name of the attribute that must be present.
+    pub fn when_present(
+        element_scope: Option<&str>,
+        attr_name: &str,
+        condition_attr: &str,
+    ) -> Self {
+        Self {
+            element_scope: element_scope.map(LocalName::from),
+            attr_name: LocalName::from(attr_name),
+            condition_attr: LocalName::from(condition_attr),
+            require_present: true,
+            case_sensitive: false,
+        }
+    }
+
+    /// Creates a new `ConditionalAttrMatcher` instance that matches the attribute
+    /// when the element lacks the condition attribute.
+    ///
+    /// # Arguments
+    ///
+    /// * `element_scope` - The name of the element to match. If `None`, matches any element.
+    /// * `attr_name` - The local name of the attribute to match.
+    /// * `condition_attr` - The local name of the attribute that must be absent.
+    pub fn when_absent(element_scope: Option<&str>, attr_name: &str, condition_attr: &str) -> Self {
+        Self {
+            require_present: false,
+            ..Self::when_present(element_scope, attr_name, condition_attr)
+        }
+    }
+
+    /// Sets whether attribute names are compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+}
+
+/// Matches elements having the specified attribute with an empty value,
+/// e.g. `<a href="">` or `<a href="  ">`, which is common in broken scraped pages.
+///
+/// The value is trimmed of ASCII whitespace before the check. Elements without the attribute
+/// are not matched. Attribute names are compared ASCII case-insensitively, unless
+/// [`EmptyAttrMatcher::case_sensitive`] is set.
+pub struct EmptyAttrMatcher {
+    /// The local name of the element to match. If `None`, matches any element.
+    pub element_scope: Option<LocalName>,
+    /// The local name of the attribute to check.
+    pub attr_name: LocalName,
+    /// Whether attribute names are compared case-sensitively.
+    pub case_sensitive: bool,
+}
+
+impl NodeChecker for EmptyAttrMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.query_or(false, |n| {
+            let Some(el) = n.as_element() else {
+                return false;
+            };
+            if let Some(ref element_scope) = self.element_scope {
+                if &el.name.local != element_scope {
+                    return false;
+                }
+            }
+            el.attrs
+                .iter()
+                .find(|a| attr_name_eq(&self.attr_name, &a.name.local, self.case_sensitive))
+                .is_some_and(|a| a.value.bytes().all(|b| b.is_ascii_whitespace()))
+        })
+    }
+}
+
+impl EmptyAttrMatcher {
+    /// Creates a new `EmptyAttrMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `element_scope` - The name of the element to match. If `None`, matches any element.
+    /// * `attr_name` - The local name of the attribute to check.
+    pub fn new(element_scope: Option<&str>, attr_name: &str) -> Self {
+        Self {
+            element_scope: element_scope.map(LocalName::from),
+            attr_name: LocalName::from(attr_name),
+            case_sensitive: false,
+        }
+    }
+
+    /// Sets whether attribute names are compared case-sensitively. Disabled by default.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+}
+
+/// Matches interactive elements (by default `a` and `button`) without an accessible name,
+/// e.g. to remove or report broken interactive markup.
+///
+/// This is a heuristic, not the full ARIA accessible name computation. An element has a name if any of these holds:
+/// - it contains non-whitespace text;
+/// - it has a non-empty `aria-label`, `aria-labelledby` or `title` attribute;
+/// - it contains an `img` with a non-empty `alt` attribute.
+///
+/// Elements are checked before their descendants are sanitized, so the text of descendants
+/// that the policy removes still counts as a name.
+pub struct InaccessibleMatcher {
+    /// The local names of the elements to check.
+    pub elements: Vec<LocalName>,
+}
+
+impl NodeChecker for InaccessibleMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        let is_checked = node
+            .qual_name_ref()
+            .is_some_and(|name| self.elements.contains(&name.local));
+        is_checked && !has_accessible_name(node)
+    }
+
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::High
+    }
+}
+
+impl InaccessibleMatcher {
+    /// Creates a new `InaccessibleMatcher` instance checking the specified elements.
+    ///
+    /// # Arguments
+    ///
+    /// * `elements` - The local names of the elements to check.
+    pub fn new(elements: &[&str]) -> Self {
+        Self {
+            elements: elements.iter().map(|name| LocalName::from(*name)).collect(),
+        }
+    }
+}
+
+impl Default for InaccessibleMatcher {
+    /// Checks `a` and `button` elements.
+    fn default() -> Self {
+        Self::new(&["a", "button"])
+    }
+}
+
+fn has_non_empty_attr(node: &NodeRef, names: &[&str]) -> bool {
+    names.iter().any(|name| {
+        node.attr(name)
+            .is_some_and(|value| !value.trim().is_empty())
+    })
+}
+
+fn has_accessible_name(node: &NodeRef) -> bool {
+    if has_non_empty_attr(node, &["aria-label", "aria-labelledby", "title"]) {
+        return true;
+    }
+    if !node.text().trim().is_empty() {
+        return true;
+    }
+    node.descendants_it().any(|descendant| {
+        descendant
+            .qual_name_ref()
+            .is_some_and(|name| name.local == html5ever::local_name!("img"))
+            && has_non_empty_attr(&descendant, &["alt"])
+    })
+}
+
+/// Matches elements having a direct child text node with non-whitespace content,
+/// e.g. to keep leaf containers that hold text themselves.
+///
+/// Unlike checking `node.text()`, which includes the text of all descendants,
+/// only the element's own text nodes are considered: `<div><p>Text</p></div>` matches for `p`,
+/// but not for `div`.
+pub struct DirectTextMatcher {
+    /// The local name of the element to match. If `None`, matches any element.
+    pub element_scope: Option<LocalName>,
+}
+
+impl NodeChecker for DirectTextMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        let in_scope = node.qual_name_ref().is_some_and(|name| {
+            self.element_scope
+                .as_ref()
+                .map_or(true, |scope| &name.local == scope)
+        });
+        in_scope
+            && node
+                .children_it(false)
+                .any(|child| child.is_text() && !child.text().trim().is_empty())
+    }
+}
+
+impl DirectTextMatcher {
+    /// Creates a new `DirectTextMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `element_scope` - The name of the element to match. If `None`, matches any element.
+    pub fn new(element_scope: Option<&str>) -> Self {
+        Self {
+            element_scope: element_scope.map(LocalName::from),
+        }
+    }
+}
+
+/// Matches attributes of any name whose value contains a script-like payload:
+/// by default `javascript:`, `vbscript:` or `data:text/html`.
+///
+/// This is a defense-in-depth check for schemes smuggled into unexpected attributes
+/// (e.g. `poster`, `formaction`, `background` or `xlink:href`). Before matching, ASCII whitespace
+/// and control characters are removed from the value and it is lowercased, so obfuscated payloads
+/// like `java\tscript:` or `JaVa\nScRiPt:` are caught. Character references are already decoded by the parser.
+///
+/// The patterns are searched anywhere in the value, so a plain text attribute like `title`
+/// mentioning `javascript:` matches too. Use it with the [`crate::Permissive`] directive to remove matched attributes.
+pub struct DangerousValueMatcher {
+    /// The patterns to search for, in lowercase and without whitespace.
+    pub patterns: Vec<String>,
+}
+
+impl AttrChecker for DangerousValueMatcher {
+    fn is_match_attr(&self, _node: &NodeRef, attr: &Attribute) -> bool {
+        let value: String = attr
+            .value
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace() && !c.is_ascii_control())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        self.patterns
+            .iter()
+            .any(|pattern| value.contains(pattern.as_str()))
+    }
+}
+
+impl DangerousValueMatcher {
+    /// Creates a new `DangerousValueMatcher` instance searching for the specified patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - The patterns to search for, e.g. `&["javascript:", "livescript:"]`,
+    ///   compared ASCII case-insensitively.
+    pub fn new(patterns: &[&str]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .map(|pattern| pattern.to_ascii_lowercase())
+                .collect(),
+        }
+    }
+}
+
+impl Default for DangerousValueMatcher {
+    /// Searches for `javascript:`, `vbscript:` and `data:text/html`.
+    fn default() -> Self {
+        Self::new(&["javascript:", "vbscript:", "data:text/html"])
+    }
+}
+
+/// Scopes any attribute checker to elements with the specified local name,
+/// e.g. to apply a [`DangerousValueMatcher`] only to attributes of `a` elements.
+///
+/// Attributes of other elements are never matched, and the inner checker is not consulted for them.
+pub struct ScopedAttr {
+    /// The local name of the element to match.
+    pub element: LocalName,
+    /// The checker the matching is delegated to.
+    pub inner: Box<dyn AttrChecker>,
+}
+
+impl AttrChecker for ScopedAttr {
+    fn is_match_attr(&self, node: &NodeRef, attr: &Attribute) -> bool {
+        node.qual_name_ref()
+            .is_some_and(|qual_name| qual_name.local == self.element)
+            && self.inner.is_match_attr(node, attr)
+    }
+}
+
+impl ScopedAttr {
+    /// Creates a new `ScopedAttr` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `element` - The local name of the element to match.
+    /// * `inner` - The checker to scope.
+    pub fn new<C: AttrChecker + 'static>(element: &str, inner: C) -> Self {
+        Self {
+            element: LocalName::from(element),
+            inner: Box::new(inner),
+        }
+    }
+}
+
+/// Matches elements nested deeper than a threshold, e.g. to flatten wrapper soup
+/// exported from word processors. Used with `exclude` under the [`crate::Permissive`] directive,
+/// it unwraps every element deeper than `max_depth`, keeping its content.
+///
+/// The depth of an element is the number of its element ancestors: `html` has depth 0, `body` has depth 1, and so on.
+/// For nodes of a document fragment, the count stops at the fragment root.
+/// The depth is computed on the live tree, so ancestors unwrapped earlier in the same pass no longer count.
+///
+/// Every check walks the parents of the node, so the cost grows with the depth of the document.
+pub struct DepthMatcher {
+    /// The maximum depth of an element that is not matched.
+    pub max_depth: usize,
+}
+
+impl NodeChecker for DepthMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.is_element()
+            && node
+                .ancestors_it(None)
+                .filter(|ancestor| ancestor.is_element())
+                .nth(self.max_depth)
+                .is_some()
+    }
+
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::High
+    }
+}
+
+impl DepthMatcher {
+    /// Creates a new `DepthMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth` - The maximum depth of an element that is not matched.
+    pub fn new(max_depth: usize) -> Self {
+        Self { max_depth }
+    }
+}
+
+/// Matches `meta` elements whose `http-equiv` directive is dangerous when it comes from untrusted input,
+/// so they can be removed. By default, the dangerous values are:
+/// - `refresh`: redirects the page to an arbitrary URL, e.g. `content="0;url=https://evil.example"`;
+/// - `set-cookie`: sets cookies in legacy browsers;
+/// - `content-security-policy`: an injected policy can block the page's own scripts and resources.
+///
+/// The value of `http-equiv` is trimmed and compared ASCII case-insensitively.
+/// Only HTML `meta` elements are matched, regardless of their `content`.
+pub struct MetaRefreshMatcher {
+    /// The `http-equiv` values to match, in lowercase.
+    pub values: Vec<String>,
+}
+
+impl NodeChecker for MetaRefreshMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.query_or(false, |n| {
+            let Some(el) = n.as_element() else {
+                return false;
+            };
+            if el.name.ns != html5ever::ns!(html) || el.name.local != html5ever::local_name!("meta")
+            {
+                return false;
+            }
+            el.attrs
+                .iter()
+                .find(|a| {
+                    a.name.ns == html5ever::ns!()
+                        && a.name.local == html5ever::local_name!("http-equiv")
+                })
+                .is_some_and(|a| {
+                    let value = a.value.trim();
+                    self.values.iter().any(|v| value.eq_ignore_ascii_case(v))
+                })
+        })
+    }
+
+    fn cost(&self) -> CheckerCost {
+        CheckerCost::Low
+    }
+
+    fn scope_local_name(&self) -> Option<LocalName> {
+        Some(html5ever::local_name!("meta"))
+    }
+}
+
+impl MetaRefreshMatcher {
+    /// Creates a new `MetaRefreshMatcher` instance matching the specified `http-equiv` values.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The `http-equiv` values to match, e.g. `&["refresh"]`, compared ASCII case-insensitively.
+    pub fn new(values: &[&str]) -> Self {
+        Self {
+            values: values
+                .iter()
+                .map(|v| v.trim().to_ascii_lowercase())
+                .collect(),
+        }
+    }
+}
+
+impl Default for MetaRefreshMatcher {
+    /// Matches `refresh`, `set-cookie` and `content-security-policy`.
+    fn default() -> Self {
+        Self::new(&["refresh", "set-cookie", "content-security-policy"])
+    }
+}
+
+/// Matches elements by their effective ARIA role: the explicit `role` attribute if present,
+/// otherwise the implicit role of the element, so `RoleMatcher::new("button")` matches
+/// both `<button>` and `<div role="button">`.
+///
+/// The explicit role is the first token of the `role` attribute, compared ASCII case-insensitively;
+/// fallback roles after it are ignored. An explicit role overrides the implicit one,
+/// so `<button role="link">` is matched by `"link"`, not by `"button"`.
+///
+/// Implicit roles are resolved from a small table covering HTML elements only:
+/// - `a` and `area` with `href`: `link`;
+/// - `button`: `button`; `input` by `type`: `button` (`button`, `submit`, `reset`, `image`),
+///   `checkbox`, `radio`, `slider` (`range`), `spinbutton` (`number`), `searchbox` (`search`)
+///   and `textbox` (`text`, `email`, `tel`, `url` or no `type`); `textarea`: `textbox`;
+///   `select`: `combobox`; `option`: `option`; `progress`: `progressbar`;
+/// - `h1`–`h6`: `heading`; `ul`, `ol` and `menu`: `list`; `li`: `listitem`; `hr`: `separator`;
+///   `img` with a non-empty or missing `alt`: `img`;
+/// - `table`: `table`; `thead`, `tbody` and `tfoot`: `rowgroup`; `tr`: `row`; `td`: `cell`; `th`: `columnheader`;
+/// - `article`: `article`; `aside`: `complementary`; `main`: `main`; `nav`: `navigation`;
+///   `dialog`: `dialog`; `details` and `fieldset`: `group`; `figure`: `figure`; `output`: `status`.
+///
+/// Roles that depend on the context or on the accessible name (e.g. `header`, `footer`, `section`, `form`,
+/// `th` as `rowheader`, `select` with `multiple`) are not computed; use the explicit `role` for them.
+pub struct RoleMatcher {
+    /// The role to match, in lowercase.
+    pub role: String,
+}
+
+impl NodeChecker for RoleMatcher {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.query_or(false, |n| {
+            let Some(el) = n.as_element() else {
+                return false;
+            };
+            let role_attr = el.attrs.iter().find(|a| {
+                a.name.ns == html5ever::ns!() && a.name.local == html5ever::local_name!("role")
+            });
+            if let Some(explicit) = role_attr.and_then(|a| a.value.split_ascii_whitespace().next())
+            {
+                return explicit.eq_ignore_ascii_case(&self.role);
+            }
+            implicit_role(&el.name, &el.attrs) == Some(self.role.as_str())
+        })
+    }
+}
+
+impl RoleMatcher {
+    /// Creates a new `RoleMatcher` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The ARIA role to match, e.g. `"button"`.
+    pub fn new(role: &str) -> Self {
+        Self {
+            role: role.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Returns the implicit ARIA role of an HTML element, as documented on [`RoleMatcher`].
+fn implicit_role(name: &html5ever::QualName, attrs: &[dom_query::Attr]) -> Option<&'static str> {
+    use html5ever::local_name;
+
+    if name.ns != html5ever::ns!(html) {
+        return None;
+    }
+    let attr = |attr_name: LocalName| {
+        attrs
+            .iter()
+            .find(|a| a.name.ns == html5ever::ns!() && a.name.local == attr_name)
+            .map(|a| &a.value)
+    };
+    let role = match name.local {
+        local_name!("a") | local_name!("area") => attr(local_name!("href")).map(|_| "link")?,
+        local_name!("button") => "button",
+        local_name!("input") => {
+            let input_type = attr(local_name!("type")).map_or("text", |t| t.trim());
+            match input_type.to_ascii_lowercase().as_str() {
+                "button" | "submit" | "reset" | "image" => "button",
+                "checkbox" => "checkbox",
+                "radio" => "radio",
+                "range" => "slider",
+                "number" => "spinbutton",
+                "search" => "searchbox",
+                "text" | "email" | "tel" | "url" | "" => "textbox",
+                _ => return None,
+            }
+        }
+        local_name!("textarea") => "textbox",
+        local_name!("select") => "combobox",
+        local_name!("option") => "option",
+        local_name!("progress") => "progressbar",
+        local_name!("h1")
+        | local_name!("h2")
+        | local_name!("h3")
+        | local_name!("h4")
+        | local_name!("h5")
+        | local_name!("h6") => "heading",
+        local_name!("ul") | local_name!("ol") | local_name!("menu") => "list",
+        local_name!("li") => "listitem",
+        local_name!("hr") => "separator",
+        local_name!("img") => match attr(local_name!("alt")) {
+            Some(alt) if alt.is_empty() => return None,
+            _ => "img",
+        },
+        local_name!("table") => "table",
+        local_name!("thead") | local_name!("tbody") | local_name!("tfoot") => "rowgroup",
+        local_name!("tr") => "row",
+        local_name!("td") => "cell",
+        local_name!("th") => "columnheader",
+        local_name!("article") => "article",
+        local_name!("aside") => "complementary",
+        local_name!("main") => "main",
+        local_name!("nav") => "navigation",
+        local_name!("dialog") => "dialog",
+        local_name!("details") | local_name!("fieldset") => "group",
+        local_name!("figure") => "figure",
+        local_name!("output") => "status",
+        _ => return None,
+    };
+    Some(role)
 }
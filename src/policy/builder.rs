@@ -1,8 +1,13 @@
-use html5ever::LocalName;
+use dom_query::Matcher;
+use html5ever::{LocalName, Namespace};
+#[cfg(feature = "regex")]
+use regex::Regex;
 
-use super::core::{AttributeRule, Policy};
+use super::core::{AttributeRule, Finalizer, Policy};
+use crate::error::{compile_selector, SelectorError};
 use crate::traits::SanitizeDirective;
-use crate::Restrictive;
+use crate::transform::AttrTransform;
+use crate::{Permissive, Restrictive};
 
 /// A builder for constructing a [`Policy`] with customizable sanitization rules.
 ///
@@ -36,6 +41,7 @@ use crate::Restrictive;
 ///     .remove_elements(&["script", "style"])
 ///     .build();
 /// ```
+#[derive(Clone)]
 pub struct PolicyBuilder<'a, T: SanitizeDirective = Restrictive> {
     /// A list of rules for excluding attributes.
     attrs_to_exclude: Vec<AttributeRule<'a>>,
@@ -43,6 +49,68 @@ pub struct PolicyBuilder<'a, T: SanitizeDirective = Restrictive> {
     elements_to_exclude: Vec<LocalName>,
     /// The list of element names to be fully removed from the DOM tree, including their children.
     elements_to_remove: Vec<LocalName>,
+    /// A list of attribute name prefixes to be removed regardless of the directive.
+    attr_prefixes_to_strip: Vec<&'a str>,
+    /// A list of transforms applied to the values of surviving attributes.
+    attr_transforms: Vec<(&'a str, &'a [AttrTransform])>,
+    /// A list of limits on the number of tokens kept in attribute values.
+    attr_token_caps: Vec<(&'a str, usize)>,
+    /// The maximum decoded size of `data:` images kept in `src` attributes.
+    data_images_max_bytes: Option<usize>,
+    /// The list of namespaces whose elements are fully removed from the DOM tree, including their children.
+    namespaces_to_remove: Vec<Namespace>,
+    /// The list of patterns to be removed from the text content.
+    #[cfg(feature = "regex")]
+    text_patterns_to_strip: Vec<Regex>,
+    /// The list of element names to be replaced with their escaped markup.
+    elements_to_escape: Vec<LocalName>,
+    /// The list of HTML element names to be renamed, paired with their new names.
+    elements_to_rename: Vec<(LocalName, LocalName)>,
+    /// The list of element names to be removed from the DOM tree if they are left empty.
+    elements_to_prune: Vec<LocalName>,
+    /// Whether to remove duplicate attributes.
+    dedupe_attrs: bool,
+    /// The exact contents of comments to keep; if set, all other comments are removed.
+    comments_to_keep: Option<Vec<&'a str>>,
+    /// The list of compiled selectors of elements to exclude from the base policy.
+    selectors_to_exclude: Vec<Matcher>,
+    /// The list of compiled selectors of elements to be fully removed from the DOM tree.
+    selectors_to_remove: Vec<Matcher>,
+    /// The maximum numbers of elements kept, keyed by element name.
+    element_limits: Vec<(LocalName, usize)>,
+    /// A function run on the sanitized node.
+    finalizer: Option<Finalizer<'a>>,
+    /// Whether to filter the CSS in kept `style` elements.
+    sanitize_style_contents: bool,
+    /// Whether to sort surviving attributes by name.
+    sort_attrs: bool,
+    /// Whether to append the `href` of unwrapped anchors as text.
+    unwrap_anchors_with_href: bool,
+    /// The attributes whose value is copied into another attribute, as `(element, from, to)`.
+    attrs_to_promote: Vec<(LocalName, LocalName, LocalName)>,
+    /// Whether to insert a space at the boundaries of unwrapped elements that would join two words.
+    insert_space_on_unwrap: bool,
+    /// The names of wrapper elements collapsed into a single child element with the same name.
+    elements_to_collapse: Vec<LocalName>,
+    /// Whether to collect the text of removed `script` and `style` elements in reports.
+    collect_removed_scripts: bool,
+    /// The attributes set on elements lacking them after sanitization, as `(element, name, value)`.
+    attrs_to_default: Vec<(LocalName, LocalName, String)>,
+    /// The attribute marking trusted elements, whose subtrees are not sanitized.
+    trust_marker: Option<LocalName>,
+    /// Whether to remove surviving attributes with an empty or whitespace-only value.
+    drop_empty_attrs: bool,
+    /// The maximum number of elements kept in document order, the rest of the content is removed.
+    max_elements: Option<usize>,
+    /// Whether to strip the text of `script` and `style` elements in foreign content.
+    strip_cdata: bool,
+    /// The maximum number of attributes kept on an element.
+    max_attrs_per_element: Option<usize>,
+    /// The maximum length of the text kept in the document, in bytes.
+    max_total_text: Option<usize>,
+    /// Whether to track source positions of removed elements in reports.
+    track_positions: bool,
+    allow_strip_structural: bool,
     _directive: std::marker::PhantomData<T>,
 }
 
@@ -52,6 +120,38 @@ impl<T: SanitizeDirective> Default for PolicyBuilder<'_, T> {
             attrs_to_exclude: vec![],
             elements_to_exclude: vec![],
             elements_to_remove: vec![],
+            attr_prefixes_to_strip: vec![],
+            attr_transforms: vec![],
+            attr_token_caps: vec![],
+            data_images_max_bytes: None,
+            namespaces_to_remove: vec![],
+            #[cfg(feature = "regex")]
+            text_patterns_to_strip: vec![],
+            elements_to_escape: vec![],
+            elements_to_rename: vec![],
+            elements_to_prune: vec![],
+            dedupe_attrs: false,
+            comments_to_keep: None,
+            selectors_to_exclude: vec![],
+            selectors_to_remove: vec![],
+            element_limits: vec![],
+            finalizer: None,
+            sanitize_style_contents: false,
+            sort_attrs: false,
+            unwrap_anchors_with_href: false,
+            attrs_to_promote: vec![],
+            insert_space_on_unwrap: false,
+            elements_to_collapse: vec![],
+            collect_removed_scripts: false,
+            attrs_to_default: vec![],
+            trust_marker: None,
+            drop_empty_attrs: false,
+            max_elements: None,
+            strip_cdata: false,
+            max_attrs_per_element: None,
+            max_total_text: None,
+            track_positions: false,
+            allow_strip_structural: false,
             _directive: std::marker::PhantomData,
         }
     }
@@ -78,6 +178,274 @@ impl<'a, T: SanitizeDirective> PolicyBuilder<'a, T> {
         self
     }
 
+    /// Excludes elements matching the CSS selector from the base sanitization directive,
+    /// like [`PolicyBuilder::exclude_elements`] does for element names.
+    ///
+    /// Returns an error if the selector is invalid.
+    ///
+    /// Each selector is matched against every element, which is noticeably slower than name-based rules
+    /// comparing interned names, especially for selectors with combinators (e.g. `article p`).
+    /// Prefer [`PolicyBuilder::exclude_elements`] when the element name is enough.
+    pub fn exclude_selector(mut self, selector: &str) -> Result<Self, SelectorError> {
+        self.selectors_to_exclude.push(compile_selector(selector)?);
+        Ok(self)
+    }
+
+    /// Removes elements matching the CSS selector from the DOM with their children during sanitization,
+    /// like [`PolicyBuilder::remove_elements`] does for element names.
+    ///
+    /// Returns an error if the selector is invalid.
+    /// See [`PolicyBuilder::exclude_selector`] for the performance cost.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::AllowAllPolicy;
+    ///
+    /// let policy = AllowAllPolicy::builder()
+    ///     .remove_selector("div.ad-block")
+    ///     .unwrap()
+    ///     .build();
+    /// let html = policy.sanitize_html(r#"<div class="ad-block">Ad</div><div>Content</div>"#);
+    /// assert!(!html.contains("Ad"));
+    /// assert!(html.contains("<div>Content</div>"));
+    /// ```
+    pub fn remove_selector(mut self, selector: &str) -> Result<Self, SelectorError> {
+        self.selectors_to_remove.push(compile_selector(selector)?);
+        Ok(self)
+    }
+
+    /// Same as [`PolicyBuilder::exclude_elements`], but takes the element names from a string list,
+    /// e.g. loaded from a config file.
+    ///
+    /// Names are separated by whitespace or newlines. Blank lines are ignored,
+    /// and `#` starts a comment that lasts until the end of the line.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::DenyAllPolicy;
+    ///
+    /// let policy = DenyAllPolicy::builder()
+    ///     .exclude_elements_str("# text\np span\n\na # links")
+    ///     .build();
+    /// let html = policy.sanitize_html(r#"<div><p><a href="/">Link</a></p></div>"#);
+    /// assert!(html.contains("<p><a>Link</a></p>"));
+    /// ```
+    pub fn exclude_elements_str(mut self, elements: &str) -> Self {
+        self.elements_to_exclude.extend(parse_names(elements));
+        self
+    }
+
+    /// Same as [`PolicyBuilder::remove_elements`], but takes the element names from a string list.
+    ///
+    /// See [`PolicyBuilder::exclude_elements_str`] for the list format.
+    pub fn remove_elements_str(mut self, elements: &str) -> Self {
+        self.elements_to_remove.extend(parse_names(elements));
+        self
+    }
+
+    /// Specifies the names of elements to replace with a text node containing their markup,
+    /// so that e.g. `<script>alert(1)</script>` is rendered as `&lt;script&gt;alert(1)&lt;/script&gt;`.
+    ///
+    /// Unlike removing or unwrapping, this keeps the element visible to the reader as source code,
+    /// while neutralizing it: the markup is serialized as escaped text and is never executed.
+    /// The element's subtree is escaped as is, before it is sanitized.
+    /// Elements matched by [`PolicyBuilder::remove_elements`] are removed instead.
+    pub fn escape_elements(mut self, elements: &'a [&str]) -> Self {
+        self.elements_to_escape.extend(intern_strings(elements));
+        self
+    }
+
+    /// Specifies the namespaces whose elements are removed from the DOM with their children during sanitization,
+    /// e.g. `&["http://www.w3.org/2000/svg", "http://www.w3.org/1998/Math/MathML"]` to drop all foreign content.
+    pub fn remove_namespaces(mut self, namespaces: &[&str]) -> Self {
+        self.namespaces_to_remove
+            .extend(namespaces.iter().map(|&ns| Namespace::from(ns)));
+        self
+    }
+
+    /// Renames HTML elements, e.g. `&[("center", "div"), ("b", "strong"), ("font", "span")]`
+    /// to replace deprecated tags. Each pair is `(old name, new name)`.
+    ///
+    /// A matched element keeps its attributes and children; only its name is changed.
+    /// Renaming happens before the other rules are applied to the element,
+    /// so they see the new name: e.g. with the [`crate::Restrictive`] directive
+    /// a `center` renamed to `div` is kept if `div` is excluded.
+    /// Only elements in the HTML namespace are renamed, SVG and MathML elements are left untouched.
+    ///
+    /// The new name must not be a void element (like `br` or `img`): void elements have no children
+    /// in serialized HTML, so the children of the renamed element would be lost.
+    pub fn rename_elements(mut self, elements: &[(&str, &str)]) -> Self {
+        self.elements_to_rename.extend(
+            elements.iter().map(|&(old_name, new_name)| {
+                (LocalName::from(old_name), LocalName::from(new_name))
+            }),
+        );
+        self
+    }
+
+    /// Copies the value of the `from` attribute into the `to` attribute of HTML elements named `element`,
+    /// if the element has no `to` attribute yet, e.g. `promote_attr("img", "data-src", "src")`
+    /// to render lazy-loaded images of scraped content statically.
+    ///
+    /// Promotion happens on kept elements before the attribute rules are applied, so the `to` attribute
+    /// must be allowed by the policy to survive, and its value goes through the same checks and transforms
+    /// as any other. The `from` attribute is left in place, subject to the attribute rules as well.
+    pub fn promote_attr(mut self, element: &str, from: &str, to: &str) -> Self {
+        self.attrs_to_promote.push((
+            LocalName::from(element),
+            LocalName::from(from),
+            LocalName::from(to),
+        ));
+        self
+    }
+
+    /// Sets the `name` attribute to `value` on HTML elements named `element` that don't have it,
+    /// e.g. `default_attr("img", "loading", "lazy")`.
+    ///
+    /// Defaults are applied in a finishing pass, after elements and attributes were sanitized,
+    /// so a default attribute is never stripped, even if the policy doesn't allow it.
+    /// An attribute already present on the element, with any value, is left untouched.
+    pub fn default_attr(mut self, element: &str, name: &str, value: &str) -> Self {
+        self.attrs_to_default.push((
+            LocalName::from(element),
+            LocalName::from(name),
+            value.to_string(),
+        ));
+        self
+    }
+
+    /// Specifies the names of elements to remove from the DOM if they have no meaningful content
+    /// (no child elements and no text except whitespace) after sanitization.
+    ///
+    /// Pruning runs bottom-up after the main pass, so a wrapper is evaluated after its children
+    /// were cleaned: e.g. `<div><p><a></a></p></div>` with `a` removed and `["div", "p"]` pruned
+    /// leaves nothing behind.
+    pub fn prune_empty_wrappers(mut self, elements: &'a [&str]) -> Self {
+        self.elements_to_prune.extend(intern_strings(elements));
+        self
+    }
+
+    /// Specifies the names of wrapper elements to collapse when nested, e.g. `&["div", "span"]`
+    /// to turn `<div><div><div>content</div></div></div>` produced by export tools into `<div>content</div>`.
+    ///
+    /// A wrapper is collapsed if its only child is an element with the same name and namespace;
+    /// whitespace-only text around that child doesn't count and is dropped. Collapsing unwraps
+    /// the outer element, so the innermost element of a chain is kept with its attributes,
+    /// while the attributes of the outer wrappers are discarded.
+    ///
+    /// Collapsing runs bottom-up after pruning (see [`PolicyBuilder::prune_empty_wrappers`]),
+    /// so a chain of any depth collapses in one pass.
+    pub fn collapse_nested(mut self, elements: &'a [&str]) -> Self {
+        self.elements_to_collapse.extend(intern_strings(elements));
+        self
+    }
+
+    /// Limits the number of elements with a given name kept in the document,
+    /// each tuple being `(element, max count)`, e.g. `&[("img", 3)]` to keep at most three images.
+    ///
+    /// Elements are counted in document order after the other rules were applied, so only kept elements count.
+    /// Elements beyond the limit are removed with their children (not unwrapped), so a limited wrapper
+    /// takes its content with it. If several limits are set for the same element, the lowest one applies.
+    pub fn limit_elements(mut self, limits: &[(&str, usize)]) -> Self {
+        self.element_limits.extend(
+            limits
+                .iter()
+                .map(|&(element, limit)| (LocalName::from(element), limit)),
+        );
+        self
+    }
+
+    /// Keeps only the first `max` elements in document order and removes the rest of the content,
+    /// e.g. to generate a "read more" preview of an article.
+    ///
+    /// Elements are counted in document order (a parent before its children) after the other rules
+    /// were applied, so only kept elements count. The first element over the limit is removed with
+    /// its children, and so is everything following it in document order, **including text**: the
+    /// output is truncated at that point. The structural `html`, `head` and `body` elements are
+    /// neither counted nor removed, but when the limit is reached in `head`, the contents of `body` are removed.
+    /// The contents of `template` elements count in place, so one budget covers the whole document.
+    /// Unlike [`PolicyBuilder::limit_elements`], the limit applies to elements of all names together.
+    /// If several limits are merged, the lowest one applies.
+    pub fn max_elements(mut self, max: usize) -> Self {
+        self.max_elements = Some(max);
+        self
+    }
+
+    /// Keeps only the first `max` bytes of text in document order and removes the rest of the content,
+    /// e.g. to guard against huge text payloads.
+    ///
+    /// The text is measured after the other rules were applied, like `root().text().len()`:
+    /// the contents of all text nodes count, including those of kept `style` or `script` elements,
+    /// while comments and attribute values don't. The text in `template` contents counts in place,
+    /// so one budget covers the whole document. The text node crossing the limit is cut
+    /// at a character boundary, and every node following it in document order is removed,
+    /// like with [`PolicyBuilder::max_elements`]. Unlike that limit, or the per-name limits of
+    /// [`PolicyBuilder::limit_elements`], it bounds the amount of text, which a few elements may hold.
+    /// If several limits are merged, the lowest one applies.
+    pub fn max_total_text(mut self, max: usize) -> Self {
+        self.max_total_text = Some(max);
+        self
+    }
+
+    /// When an `a` element is unwrapped, appends its URL in parentheses after its content,
+    /// e.g. `<a href="https://example.com">Example</a>` becomes `Example (https://example.com)`,
+    /// which is useful for plain-text contexts such as email.
+    ///
+    /// It only applies to HTML `a` elements with a non-empty `href` that are unwrapped
+    /// (e.g. not excluded under [`Restrictive`]), not to removed ones. Disabled by default.
+    pub fn unwrap_anchors_with_href(mut self, enable: bool) -> Self {
+        self.unwrap_anchors_with_href = enable;
+        self
+    }
+
+    /// When an element is unwrapped, inserts a single space at its start and end if its content
+    /// would otherwise join two words, e.g. `<p>foo</p><p>bar</p>` with `p` unwrapped becomes `foo bar`
+    /// instead of `foobar`, which matters for plain-text extraction.
+    ///
+    /// The heuristic only looks at the characters on both sides of each boundary: the last character
+    /// of the text before the element and the first character of its content (and likewise at its end).
+    /// A space is inserted only if both are word characters (alphanumeric or `_`), never next to
+    /// whitespace or punctuation. It doesn't know whether the element was rendered as a block,
+    /// so an inline element splitting a word, like `un<b>believ</b>able`, gets spaces too. Disabled by default.
+    pub fn insert_space_on_unwrap(mut self, enable: bool) -> Self {
+        self.insert_space_on_unwrap = enable;
+        self
+    }
+
+    /// Marks elements having the `attr_name` attribute (e.g. `data-trusted`) as trusted:
+    /// sanitization keeps them and doesn't descend into them, leaving the element,
+    /// its attributes and its whole subtree unchanged.
+    ///
+    /// # Security
+    ///
+    /// **Anyone who can add the marker attribute bypasses the policy.** Only use it when the marker
+    /// can't come from untrusted input, e.g. when the markup is produced by a trusted editor and the
+    /// marker attribute is stripped from user-supplied content beforehand. A marker on untrusted HTML
+    /// lets scripts, event handlers and any other content through.
+    ///
+    /// Trusted subtrees are skipped by the element and attribute rules, and by element limits,
+    /// pruning and collapsing. Steps running on the whole sanitized node, like comment stripping,
+    /// text patterns, CSS filtering, [`PolicyBuilder::default_attr`] and the finalizer, still apply to them.
+    /// If several builders with a marker are merged, the first marker is kept.
+    pub fn trust_marker(mut self, attr_name: &str) -> Self {
+        self.trust_marker = Some(LocalName::from(attr_name));
+        self
+    }
+
+    /// Collects the text of removed `script` and `style` elements into the report returned by
+    /// [`Policy::sanitize_document_report`], e.g. to log removed scripts for threat intelligence.
+    ///
+    /// The text is stored in [`crate::report::RemovedElement::raw_text`]. It is opt-in and only collected for
+    /// `script` and `style` elements removed with their contents (see [`PolicyBuilder::remove_elements`]),
+    /// not for unwrapped ones, and unlike [`Policy::sanitize_document_quarantine`]
+    /// the markup of other removed elements is not captured. Disabled by default.
+    pub fn collect_removed_scripts(mut self, collect: bool) -> Self {
+        self.collect_removed_scripts = collect;
+        self
+    }
+
     /// Excludes the specified attributes from the base sanitization directive.
     ///
     /// - If the sanitization directive is [`crate::Permissive`], these attributes will be removed from all elements where they appear.
@@ -86,6 +454,9 @@ impl<'a, T: SanitizeDirective> PolicyBuilder<'a, T> {
         let rule = AttributeRule {
             element: None,
             attributes: attrs,
+            attr_names: vec![],
+            all_attrs: false,
+            namespace: None,
         };
         self.attrs_to_exclude.push(rule);
         self
@@ -99,30 +470,571 @@ impl<'a, T: SanitizeDirective> PolicyBuilder<'a, T> {
         let rule = AttributeRule {
             element: Some(element.into()),
             attributes: attrs,
+            attr_names: vec![],
+            all_attrs: false,
+            namespace: None,
+        };
+        self.attrs_to_exclude.push(rule);
+        self
+    }
+
+    /// Excludes the specified attributes from the base sanitization directive for elements of a namespace,
+    /// e.g. `exclude_namespace_attrs("http://www.w3.org/2000/svg", &["viewBox", "d"])`.
+    ///
+    /// - If the sanitization directive is [`crate::Permissive`], these attributes will be removed from the elements of the namespace.
+    /// - If the sanitization directive is [`crate::Restrictive`], only these attributes will be kept for the elements of the namespace;
+    ///   all others will be removed.
+    ///
+    /// Attribute rules add up: an element keeps (or loses) the attributes listed by the global rules
+    /// ([`PolicyBuilder::exclude_attrs`]), by the rules of its namespace and by the rules of its local name
+    /// ([`PolicyBuilder::exclude_element_attrs`]), which apply in every namespace. Under [`crate::Restrictive`],
+    /// a global `id` rule keeps `id` on SVG elements too, and a namespace rule doesn't affect HTML elements.
+    pub fn exclude_namespace_attrs(mut self, ns: &str, attrs: &'a [&str]) -> Self {
+        let rule = AttributeRule {
+            element: None,
+            attributes: attrs,
+            attr_names: vec![],
+            all_attrs: false,
+            namespace: Some(Namespace::from(ns)),
         };
         self.attrs_to_exclude.push(rule);
         self
     }
 
+    /// Allows elements together with their attributes, each tuple being `(element, attributes)`.
+    ///
+    /// It is a shortcut for [`PolicyBuilder::exclude_elements`] with
+    /// [`PolicyBuilder::exclude_element_attrs`] for every element, and only makes sense
+    /// under the [`crate::Restrictive`] directive, where excluded elements and attributes are the kept ones.
+    /// Under [`crate::Permissive`], the same call would remove the listed elements and attributes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::DenyAllPolicy;
+    ///
+    /// let policy = DenyAllPolicy::builder()
+    ///     .allow(&[
+    ///         ("p", &[]),
+    ///         ("b", &[]),
+    ///         ("a", &["href", "title"]),
+    ///         ("img", &["src", "alt", "width", "height"]),
+    ///     ])
+    ///     .exclude_attrs(&["class"])
+    ///     .build();
+    ///
+    /// let html = policy.sanitize_html(
+    ///     r#"<div><p class="x" style="color:red"><a href="/" target="_blank">Link</a></p></div>"#,
+    /// );
+    /// assert!(html.contains(r#"<p class="x"><a href="/">Link</a></p>"#));
+    /// ```
+    pub fn allow(mut self, rules: &'a [(&'a str, &'a [&'a str])]) -> Self {
+        for &(element, attrs) in rules {
+            self.elements_to_exclude.push(LocalName::from(element));
+            if !attrs.is_empty() {
+                self.attrs_to_exclude.push(AttributeRule {
+                    element: Some(element.into()),
+                    attributes: attrs,
+                    attr_names: vec![],
+                    all_attrs: false,
+                    namespace: None,
+                });
+            }
+        }
+        self
+    }
+
+    /// Removes attributes whose names start with any of the specified prefixes
+    /// (compared ASCII case-insensitively) from all elements.
+    ///
+    /// Unlike [`PolicyBuilder::exclude_attrs`], this rule doesn't depend on the directive:
+    /// matching attributes are removed even under [`crate::Restrictive`], where they were explicitly excluded.
+    pub fn strip_attr_prefixes(mut self, prefixes: &'a [&'a str]) -> Self {
+        self.attr_prefixes_to_strip.extend(prefixes);
+        self
+    }
+
+    /// Removes all event handler attributes (`onclick`, `onerror`, `onload`, etc.) from all elements,
+    /// regardless of the directive and the element namespace (including SVG and MathML).
+    ///
+    /// It is a shortcut for `strip_attr_prefixes(&["on"])`.
+    pub fn remove_event_handlers(self) -> Self {
+        self.strip_attr_prefixes(&["on"])
+    }
+
+    /// Applies the listed transforms, in order, to the value of the specified attribute
+    /// on every element where the attribute survived the sanitization.
+    ///
+    /// See [`AttrTransform`] for the available transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::transform::AttrTransform;
+    /// use dom_sanitizer::AllowAllPolicy;
+    ///
+    /// let policy = AllowAllPolicy::builder()
+    ///     .transform_attr("href", &[AttrTransform::StripControlChars, AttrTransform::Trim])
+    ///     .build();
+    /// let html = policy.sanitize_html("<a href=\" /pa\tth \">Link</a>");
+    /// assert!(html.contains(r#"<a href="/path">Link</a>"#));
+    /// ```
+    pub fn transform_attr(mut self, attr: &'a str, transforms: &'a [AttrTransform]) -> Self {
+        self.attr_transforms.push((attr, transforms));
+        self
+    }
+
+    /// Normalizes the whitespace in the values of the specified attributes: runs of whitespace
+    /// are collapsed to a single space and the value is trimmed, which gives stable, diff-friendly output.
+    ///
+    /// This is meant for attributes holding whitespace-separated tokens, such as the ones listed
+    /// in [`WHITESPACE_SEPARATED_ATTRS`](crate::transform::WHITESPACE_SEPARATED_ATTRS).
+    /// It is a shortcut for [`PolicyBuilder::transform_attr`] with
+    /// [`NORMALIZE_WHITESPACE`](crate::transform::NORMALIZE_WHITESPACE) for every attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::transform::WHITESPACE_SEPARATED_ATTRS;
+    /// use dom_sanitizer::AllowAllPolicy;
+    ///
+    /// let policy = AllowAllPolicy::builder()
+    ///     .normalize_attr_whitespace(WHITESPACE_SEPARATED_ATTRS)
+    ///     .build();
+    /// let html = policy.sanitize_html("<p class=\"  lead\n  intro \">Text</p>");
+    /// assert!(html.contains(r#"<p class="lead intro">Text</p>"#));
+    /// ```
+    pub fn normalize_attr_whitespace(mut self, attrs: &'a [&'a str]) -> Self {
+        self.attr_transforms.extend(
+            attrs
+                .iter()
+                .map(|attr| (*attr, crate::transform::NORMALIZE_WHITESPACE)),
+        );
+        self
+    }
+
+    /// Canonicalizes the value of the listed boolean attributes to an empty string, so kept attributes
+    /// are always rendered the same way, e.g. `disabled="disabled"` and `disabled="true"` become `disabled=""`.
+    ///
+    /// In HTML, a boolean attribute is `true` when present, whatever its value (even `"false"`),
+    /// so the attributes are never dropped based on their value: that would change their meaning.
+    /// To remove them, use the attribute rules. Known boolean attributes are listed
+    /// in [`BOOLEAN_ATTRS`](crate::transform::BOOLEAN_ATTRS). It is a shortcut for
+    /// [`PolicyBuilder::transform_attr`] with [`CLEAR_VALUE`](crate::transform::CLEAR_VALUE) for every attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::transform::BOOLEAN_ATTRS;
+    /// use dom_sanitizer::AllowAllPolicy;
+    ///
+    /// let policy = AllowAllPolicy::builder()
+    ///     .normalize_boolean_attrs(BOOLEAN_ATTRS)
+    ///     .build();
+    /// let html = policy.sanitize_html(r#"<input type="checkbox" checked="checked" disabled="false">"#);
+    /// assert!(html.contains(r#"<input type="checkbox" checked="" disabled="">"#));
+    /// ```
+    pub fn normalize_boolean_attrs(mut self, attrs: &'a [&'a str]) -> Self {
+        self.attr_transforms.extend(
+            attrs
+                .iter()
+                .map(|attr| (*attr, crate::transform::CLEAR_VALUE)),
+        );
+        self
+    }
+
+    /// Removes all comments from the sanitized content, except the ones listed
+    /// with [`PolicyBuilder::preserve_comments_exact`].
+    ///
+    /// By default comments are kept.
+    pub fn strip_comments(mut self) -> Self {
+        self.comments_to_keep.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// Keeps comments whose contents exactly match one of the listed strings, e.g. signed integrity markers,
+    /// and removes all other comments, as with [`PolicyBuilder::strip_comments`].
+    ///
+    /// Only an exact match counts: the contents between `<!--` and `-->` are compared as is,
+    /// without trimming or case folding. Preserved comments take precedence over [`PolicyBuilder::strip_comments`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::AllowAllPolicy;
+    ///
+    /// let policy = AllowAllPolicy::builder()
+    ///     .preserve_comments_exact(&["sig:4f2a9c"])
+    ///     .build();
+    /// let html = policy.sanitize_html("<p><!--sig:4f2a9c-->Text<!-- note --></p>");
+    /// assert!(html.contains("<p><!--sig:4f2a9c-->Text</p>"));
+    /// ```
+    pub fn preserve_comments_exact(mut self, comments: &[&'a str]) -> Self {
+        self.comments_to_keep
+            .get_or_insert_with(Vec::new)
+            .extend(comments);
+        self
+    }
+
+    /// Filters the CSS in the `style` elements kept after sanitization, instead of dropping them,
+    /// e.g. for email HTML, where `style` blocks are common.
+    ///
+    /// Comments, `@import` statements and declarations containing `expression()`, script URLs
+    /// (`javascript:`, `vbscript:`, `data:text/...`), `-moz-binding`, `behavior` or CSS escapes are removed.
+    /// Comments are replaced with a space, and any piece (including selectors) containing `<` is removed,
+    /// so the filtered text can't close the `style` element when the output is parsed again.
+    /// This is best-effort filtering, not a full CSS parser: the source is only split at `;`, `{` and `}`.
+    /// Disabled by default.
+    pub fn sanitize_style_contents(mut self, sanitize: bool) -> Self {
+        self.sanitize_style_contents = sanitize;
+        self
+    }
+
+    /// Sorts the attributes of kept elements by their local name, so the output doesn't depend
+    /// on the source attribute order (e.g. for snapshot tests or caching).
+    ///
+    /// This is for determinism only: attribute order has no meaning in HTML.
+    /// Sorting happens after all other attribute rules were applied. Disabled by default.
+    pub fn sort_attrs(mut self, sort: bool) -> Self {
+        self.sort_attrs = sort;
+        self
+    }
+
+    /// Keeps at most `max` attributes on every element, dropping the rest, e.g. to bound
+    /// the attribute bloat of adversarial input. See [`AttrCountMatcher`](crate::plugin_policy::preset::AttrCountMatcher)
+    /// for matching such elements instead.
+    ///
+    /// The cap applies to the attributes surviving the other rules, including [`PolicyBuilder::drop_empty_attrs`],
+    /// and keeps the first ones in document (parse) order, before [`PolicyBuilder::sort_attrs`] reorders them.
+    /// If several merged policies set a cap, the smallest one applies.
+    pub fn cap_attrs_per_element(mut self, max: usize) -> Self {
+        self.max_attrs_per_element = Some(max);
+        self
+    }
+
+    /// Strips the CDATA sections of `script` and `style` elements in foreign content (SVG and MathML),
+    /// leaving the elements empty. Disabled by default.
+    ///
+    /// html5ever only recognizes `<![CDATA[...]]>` in foreign content, and turns it into an ordinary
+    /// text node, so the section boundaries are lost after parsing. In HTML content it is parsed as
+    /// a bogus comment and handled like any other comment. A CDATA section elsewhere in foreign content
+    /// becomes harmless text, escaped on serialization, but inside a kept SVG `script` or `style`
+    /// it is still run by browsers, e.g. `<svg><script><![CDATA[alert(1)]]></script></svg>`.
+    /// This option removes the whole text of such elements, whether it came from CDATA or not.
+    pub fn strip_cdata(mut self, strip: bool) -> Self {
+        self.strip_cdata = strip;
+        self
+    }
+
+    /// Removes every surviving attribute with an empty or whitespace-only value, e.g. `class=""`
+    /// or a `class` left empty by [`PolicyBuilder::cap_attr_tokens`]. Disabled by default.
+    ///
+    /// It runs last among the attribute value rules, after the value transforms, token caps and
+    /// the `data:` image check, so it also catches attributes emptied by them.
+    /// `alt` and the boolean attributes listed in [`BOOLEAN_ATTRS`](crate::transform::BOOLEAN_ATTRS)
+    /// are kept: an empty `alt` marks a decorative image, and a boolean attribute is set even
+    /// when empty (see [`PolicyBuilder::normalize_boolean_attrs`]).
+    pub fn drop_empty_attrs(mut self, drop: bool) -> Self {
+        self.drop_empty_attrs = drop;
+        self
+    }
+
+    /// Limits the number of whitespace-separated tokens in the value of the specified attribute
+    /// (e.g. `class` or `rel`) to `max_tokens`, keeping the first ones.
+    ///
+    /// The limit applies to every element where the attribute survived the sanitization,
+    /// after the value transforms set with [`PolicyBuilder::transform_attr`].
+    /// Truncated values are re-joined with single spaces.
+    pub fn cap_attr_tokens(mut self, attr: &'a str, max_tokens: usize) -> Self {
+        self.attr_token_caps.push((attr, max_tokens));
+        self
+    }
+
+    /// Restricts `data:` URIs in surviving `src` attributes to inline images
+    /// with a decoded size not larger than `max_bytes`.
+    ///
+    /// Only `image/*` media types are accepted, except `image/svg+xml`, which may carry scripts.
+    /// The size of base64 data is estimated from its length, without decoding.
+    /// `src` attributes with any other `data:` URI (e.g. `data:text/html`) are always dropped,
+    /// while other URLs are not affected.
+    pub fn allow_data_images(mut self, max_bytes: usize) -> Self {
+        self.data_images_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Removes substrings matching any of the patterns from the text nodes after sanitization,
+    /// e.g. leftover templating delimiters like `{{...}}` or `<%...%>`.
+    ///
+    /// This operates on the text content only: attribute values, comments and markup are not affected.
+    /// Patterns are applied in order to each text node, after adjacent text nodes were merged.
+    ///
+    /// Requires the `regex` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::AllowAllPolicy;
+    /// use regex::Regex;
+    ///
+    /// let patterns = [Regex::new(r"\{\{.*?\}\}").unwrap()];
+    /// let policy = AllowAllPolicy::builder().strip_text_patterns(&patterns).build();
+    /// let html = policy.sanitize_html("<p>Hello {{ user.name }}!</p>");
+    /// assert!(html.contains("<p>Hello !</p>"));
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn strip_text_patterns(mut self, patterns: &[Regex]) -> Self {
+        self.text_patterns_to_strip.extend_from_slice(patterns);
+        self
+    }
+
+    /// Ensures that no element keeps two attributes with the same local name,
+    /// keeping only the first one, like the HTML parser does for duplicate attributes. Disabled by default.
+    /// The tokens of duplicate token-list attributes (see [`crate::transform::WHITESPACE_SEPARATED_ATTRS`],
+    /// e.g. `class` or `rel`) are merged into the first one, so `rel="nofollow"` and `rel="noopener"`
+    /// become `rel="nofollow noopener"`.
+    ///
+    /// The parser already drops duplicates, and the policy updates existing attributes in place when
+    /// it rewrites values, but duplicates may still come from a tree built or modified by other code,
+    /// or from namespaced attributes sharing a local name (e.g. `href` and `xlink:href`).
+    /// Deduplication runs before the other attribute value rules, and again after the
+    /// [finalizer](PolicyBuilder::with_finalizer), which may write attributes of its own.
+    pub fn dedupe_attrs(mut self, dedupe: bool) -> Self {
+        self.dedupe_attrs = dedupe;
+        self
+    }
+
+    /// Allows the [`crate::Restrictive`] directive to remove `html`, `head` and `body`
+    /// like any other element that isn't excluded. Disabled by default: these elements are always kept.
+    ///
+    /// This is useful for fragments (e.g. parsed with [`dom_query::Document::fragment`]),
+    /// where the structural wrappers are unwanted in the output and only the bare content should remain.
+    /// Note that the `head` contents are kept too, unless they are removed by the policy.
+    pub fn allow_strip_structural(mut self, allow: bool) -> Self {
+        self.allow_strip_structural = allow;
+        self
+    }
+
+    /// Enables tracking of the source line for every removed element in the report
+    /// returned by [`Policy::sanitize_html_report`].
+    ///
+    /// Positions are only available when the policy parses the HTML itself,
+    /// not when it is handed a pre-built [`dom_query::Document`]. Disabled by default.
+    pub fn track_positions(mut self, track: bool) -> Self {
+        self.track_positions = track;
+        self
+    }
+
+    /// Sets a function to run on the sanitized node after all other rules were applied,
+    /// as an escape hatch for custom finishing steps (e.g. adding a wrapper or a nonce attribute).
+    ///
+    /// The function receives the node passed to the policy: the document root when sanitizing
+    /// a document or HTML. It runs on already sanitized content, and its changes are **not** sanitized,
+    /// so it must not introduce unsafe markup. When merging policies, the builder's own finalizer is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::AllowAllPolicy;
+    ///
+    /// let policy = AllowAllPolicy::builder()
+    ///     .remove_elements(&["script"])
+    ///     .with_finalizer(|node| {
+    ///         for link in node.find(&["a"]) {
+    ///             link.set_attr("rel", "nofollow");
+    ///         }
+    ///     })
+    ///     .build();
+    /// let html = policy.sanitize_html(r#"<a href="/">Link</a><script></script>"#);
+    /// assert!(html.contains(r#"<a href="/" rel="nofollow">Link</a>"#));
+    /// ```
+    pub fn with_finalizer<F>(mut self, finalizer: F) -> Self
+    where
+        F: Fn(&dom_query::NodeRef) + Send + Sync + 'a,
+    {
+        self.finalizer = Some(Finalizer(std::sync::Arc::new(finalizer)));
+        self
+    }
+
     /// Merges existing [`Policy`] into the builder, consuming it.
     pub fn merge(mut self, other: Policy<'a, T>) -> Self {
         self.attrs_to_exclude.extend(other.attrs_to_exclude);
-        self.elements_to_exclude.extend(other.elements_to_exclude);
+        self.elements_to_exclude
+            .extend(other.elements_to_exclude.into_owned());
         self.elements_to_remove.extend(other.elements_to_remove);
+        self.namespaces_to_remove.extend(other.namespaces_to_remove);
+        self.attr_prefixes_to_strip
+            .extend(other.attr_prefixes_to_strip);
+        self.attr_transforms.extend(other.attr_transforms);
+        self.attr_token_caps.extend(other.attr_token_caps);
+        self.data_images_max_bytes = self.data_images_max_bytes.or(other.data_images_max_bytes);
+        self.elements_to_escape.extend(other.elements_to_escape);
+        self.elements_to_rename.extend(other.elements_to_rename);
+        self.dedupe_attrs |= other.dedupe_attrs;
+        self.elements_to_prune.extend(other.elements_to_prune);
+        self.max_total_text = self
+            .max_total_text
+            .into_iter()
+            .chain(other.max_total_text)
+            .min();
+        self.max_attrs_per_element = self
+            .max_attrs_per_element
+            .into_iter()
+            .chain(other.max_attrs_per_element)
+            .min();
+        self.strip_cdata |= other.strip_cdata;
+        self.max_elements = self
+            .max_elements
+            .into_iter()
+            .chain(other.max_elements)
+            .min();
+        self.drop_empty_attrs |= other.drop_empty_attrs;
+        self.trust_marker = self.trust_marker.or(other.trust_marker);
+        self.attrs_to_default.extend(other.attrs_to_default);
+        self.collect_removed_scripts |= other.collect_removed_scripts;
+        self.elements_to_collapse.extend(other.elements_to_collapse);
+        self.insert_space_on_unwrap |= other.insert_space_on_unwrap;
+        self.attrs_to_promote.extend(other.attrs_to_promote);
+        self.unwrap_anchors_with_href |= other.unwrap_anchors_with_href;
+        self.sort_attrs |= other.sort_attrs;
+        self.sanitize_style_contents |= other.sanitize_style_contents;
+        self.finalizer = self.finalizer.or(other.finalizer);
+        self.element_limits.extend(other.element_limits);
+        self.selectors_to_remove.extend(other.selectors_to_remove);
+        self.selectors_to_exclude.extend(other.selectors_to_exclude);
+        self.track_positions |= other.track_positions;
+        self.allow_strip_structural |= other.allow_strip_structural;
+        if let Some(comments) = other.comments_to_keep {
+            self.comments_to_keep
+                .get_or_insert_with(Vec::new)
+                .extend(comments);
+        }
+        #[cfg(feature = "regex")]
+        self.text_patterns_to_strip
+            .extend(other.text_patterns_to_strip);
         self
     }
 
+    /// Merges the rules of a borrowed [`Policy`] into the builder, like [`PolicyBuilder::merge`].
+    ///
+    /// This allows keeping a shared preset around and deriving several policies from it.
+    /// The rules are cloned: borrowed slices, interned names and compiled patterns are cheap to clone,
+    /// so the cost is mostly in copying the rule lists.
+    pub fn merge_ref(self, other: &Policy<'a, T>) -> Self
+    where
+        T: Clone,
+    {
+        self.merge(other.clone())
+    }
+
     /// Builds the [`Policy`] using the current configuration.
     pub fn build(self) -> Policy<'a, T> {
         Policy {
             attrs_to_exclude: self.attrs_to_exclude,
-            elements_to_exclude: self.elements_to_exclude,
+            elements_to_exclude: self.elements_to_exclude.into(),
             elements_to_remove: self.elements_to_remove,
+            attr_prefixes_to_strip: self.attr_prefixes_to_strip,
+            attr_transforms: self.attr_transforms,
+            attr_token_caps: self.attr_token_caps,
+            data_images_max_bytes: self.data_images_max_bytes,
+            namespaces_to_remove: self.namespaces_to_remove,
+            #[cfg(feature = "regex")]
+            text_patterns_to_strip: self.text_patterns_to_strip,
+            elements_to_escape: self.elements_to_escape,
+            elements_to_rename: self.elements_to_rename,
+            elements_to_prune: self.elements_to_prune,
+            dedupe_attrs: self.dedupe_attrs,
+            comments_to_keep: self.comments_to_keep,
+            selectors_to_exclude: self.selectors_to_exclude,
+            selectors_to_remove: self.selectors_to_remove,
+            element_limits: self.element_limits,
+            finalizer: self.finalizer,
+            sanitize_style_contents: self.sanitize_style_contents,
+            sort_attrs: self.sort_attrs,
+            unwrap_anchors_with_href: self.unwrap_anchors_with_href,
+            attrs_to_promote: self.attrs_to_promote,
+            insert_space_on_unwrap: self.insert_space_on_unwrap,
+            elements_to_collapse: self.elements_to_collapse,
+            collect_removed_scripts: self.collect_removed_scripts,
+            attrs_to_default: self.attrs_to_default,
+            trust_marker: self.trust_marker,
+            drop_empty_attrs: self.drop_empty_attrs,
+            max_elements: self.max_elements,
+            strip_cdata: self.strip_cdata,
+            max_attrs_per_element: self.max_attrs_per_element,
+            max_total_text: self.max_total_text,
+            track_positions: self.track_positions,
+            allow_strip_structural: self.allow_strip_structural,
             _directive: std::marker::PhantomData,
         }
     }
 }
 
+impl<'a> PolicyBuilder<'a, Restrictive> {
+    /// Merges the rules of a [`Policy`] with any directive into the builder, consuming it,
+    /// e.g. to reuse a curated list of elements defined for another directive.
+    ///
+    /// The rules are imported as is, ignoring the source directive, so their meaning may flip:
+    /// elements and attributes excluded by a [`Permissive`] policy (removed there)
+    /// are kept when merged into a [`Restrictive`] builder, and vice versa.
+    /// Elements to remove and the other rules mean the same under both directives.
+    ///
+    /// The rules of [`PolicyBuilder::strip_all_attrs_from`] are dropped instead of being flipped,
+    /// so these elements keep no attributes rather than all of them.
+    pub fn merge_rules_from<U: SanitizeDirective>(self, other: Policy<'a, U>) -> Self {
+        self.merge(other.into_restrictive())
+    }
+}
+
+impl<'a> PolicyBuilder<'a, Permissive> {
+    /// Merges the rules of a [`Policy`] with any directive into the builder, consuming it,
+    /// e.g. to reuse a curated list of elements defined for another directive.
+    ///
+    /// The rules are imported as is, ignoring the source directive, so their meaning may flip:
+    /// elements and attributes excluded by a [`Restrictive`] policy (kept there)
+    /// are removed when merged into a [`Permissive`] builder.
+    pub fn merge_rules_from<U: SanitizeDirective>(self, other: Policy<'a, U>) -> Self {
+        self.merge(other.with_directive())
+    }
+
+    /// Removes every attribute of the specified elements, keeping the elements themselves,
+    /// e.g. `&["iframe", "object"]`, without enumerating their attributes.
+    ///
+    /// Attributes are removed before the other attribute rules (transforms, token caps, etc.) apply.
+    /// This method is only available under the [`Permissive`] directive: under [`Restrictive`],
+    /// elements keep no attributes unless they are excluded, which is already the default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::AllowAllPolicy;
+    ///
+    /// let policy = AllowAllPolicy::builder()
+    ///     .strip_all_attrs_from(&["iframe"])
+    ///     .build();
+    /// let html = policy.sanitize_html(r#"<iframe src="https://example.com" allow="camera"></iframe><p class="x"></p>"#);
+    /// assert!(html.contains(r#"<iframe></iframe><p class="x"></p>"#));
+    /// ```
+    pub fn strip_all_attrs_from(mut self, elements: &[&str]) -> Self {
+        self.attrs_to_exclude
+            .extend(intern_strings(elements).map(|element| AttributeRule {
+                element: Some(element),
+                attributes: &[],
+                attr_names: vec![],
+                all_attrs: true,
+                namespace: None,
+            }));
+        self
+    }
+}
+
 fn intern_strings<'a>(elements: &'a [&str]) -> impl Iterator<Item = LocalName> + 'a {
     elements.iter().map(|&name| LocalName::from(name))
 }
+
+/// Parses a whitespace-separated list of names, skipping `#` comments.
+fn parse_names(list: &str) -> impl Iterator<Item = LocalName> + '_ {
+    list.lines()
+        .map(|line| line.split_once('#').map_or(line, |(names, _)| names))
+        .flat_map(str::split_whitespace)
+        .map(LocalName::from)
+}
@@ -1,10 +1,21 @@
-use dom_query::NodeRef;
-use html5ever::LocalName;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+use dom_query::{Matcher, NodeRef};
+use html5ever::{local_name, ns, LocalName, Namespace, QualName};
+#[cfg(feature = "regex")]
+use regex::Regex;
 use tendril::StrTendril;
 
 use super::builder::PolicyBuilder;
+use crate::dom_helpers::is_preformatted;
 use crate::macros::sanitize_methods;
-use crate::traits::{SanitizeDirective, SanitizePolicy};
+use crate::traits::{SanitizeDirective, SanitizePolicy, Sanitizer};
+use crate::transform::{
+    apply_transforms, AttrTransform, BOOLEAN_ATTRS, WHITESPACE_SEPARATED_ATTRS,
+};
+use crate::url::is_small_data_image;
 use crate::{Permissive, Restrictive};
 
 fn is_node_name_in(names: &[LocalName], node: &NodeRef) -> bool {
@@ -12,6 +23,172 @@ fn is_node_name_in(names: &[LocalName], node: &NodeRef) -> bool {
         .is_some_and(|qual_name| names.contains(&qual_name.local))
 }
 
+fn is_node_matching_any(matchers: &[Matcher], node: &NodeRef) -> bool {
+    matchers.iter().any(|matcher| node.is_match(matcher))
+}
+
+fn starts_with_ignore_ascii_case(s: &str, prefix: &str) -> bool {
+    s.len() >= prefix.len() && s.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+/// Removes attributes whose local name is already used by a preceding attribute of the element,
+/// keeping the first one, like the HTML parser does for duplicate attributes.
+///
+/// The tokens of duplicate [`WHITESPACE_SEPARATED_ATTRS`] (e.g. `class` or `rel`) are merged
+/// into the first attribute instead of being dropped.
+fn dedupe_attrs(node: &NodeRef) {
+    node.update(|n| {
+        let Some(el) = n.as_element_mut() else {
+            return;
+        };
+        let mut i = 0;
+        while i < el.attrs.len() {
+            let Some(first) = el.attrs[..i]
+                .iter()
+                .position(|attr| attr.name.local == el.attrs[i].name.local)
+            else {
+                i += 1;
+                continue;
+            };
+            let duplicate = el.attrs.remove(i);
+            if !WHITESPACE_SEPARATED_ATTRS.contains(&duplicate.name.local.as_ref()) {
+                continue;
+            }
+            let mut value = String::from(&*el.attrs[first].value);
+            for token in duplicate.value.split_ascii_whitespace() {
+                if !value.split_ascii_whitespace().any(|t| t == token) {
+                    if !value.trim().is_empty() {
+                        value.push(' ');
+                    }
+                    value.push_str(token);
+                }
+            }
+            el.attrs[first].value = value.as_str().into();
+        }
+    });
+}
+
+/// A function run on the sanitized node, see [`PolicyBuilder::with_finalizer`].
+#[derive(Clone)]
+pub(crate) struct Finalizer<'a>(pub(crate) Arc<dyn Fn(&NodeRef) + Send + Sync + 'a>);
+
+impl fmt::Debug for Finalizer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Finalizer")
+    }
+}
+
+/// Appends the URL of an HTML `a` element with a non-empty `href` as text after its content.
+fn append_anchor_href(node: &NodeRef) {
+    let is_html_anchor = node
+        .qual_name_ref()
+        .is_some_and(|qual_name| qual_name.ns == ns!(html) && qual_name.local == local_name!("a"));
+    if !is_html_anchor {
+        return;
+    }
+    let Some(href) = node.attr("href").filter(|href| !href.trim().is_empty()) else {
+        return;
+    };
+    let text = node.tree.new_text(format!(" ({})", href.trim()));
+    node.append_child(&text);
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Returns the first (or last, if `last`) character of the text content of the node,
+/// walking only as far into the subtree as needed.
+fn edge_char(node: &NodeRef, last: bool) -> Option<char> {
+    if node.is_text() {
+        let text = node.text();
+        return if last {
+            text.chars().next_back()
+        } else {
+            text.chars().next()
+        };
+    }
+    node.children_it(last)
+        .find_map(|child| edge_char(&child, last))
+}
+
+/// Inserts a space at the start and end of the element's content if unwrapping the element
+/// would join two word characters there.
+fn insert_boundary_spaces(node: &NodeRef) {
+    let (Some(first), Some(last)) = (edge_char(node, false), edge_char(node, true)) else {
+        return;
+    };
+    let joins_prev = node
+        .prev_sibling()
+        .and_then(|prev| edge_char(&prev, true))
+        .is_some_and(|prev| is_word_char(prev) && is_word_char(first));
+    let joins_next = node
+        .next_sibling()
+        .and_then(|next| edge_char(&next, false))
+        .is_some_and(|next| is_word_char(last) && is_word_char(next));
+    if joins_prev {
+        node.prepend_child(&node.tree.new_text(" "));
+    }
+    if joins_next {
+        node.append_child(&node.tree.new_text(" "));
+    }
+}
+
+/// Removes the attributes of the element with an empty or whitespace-only value,
+/// except for `alt` and boolean attributes, for which an empty value is meaningful.
+fn drop_empty_attrs(node: &NodeRef) {
+    let node_attrs = node.attrs();
+    let empty_attrs: Vec<&str> = node_attrs
+        .iter()
+        .filter(|a| a.value.trim().is_empty())
+        .map(|a| a.name.local.as_ref())
+        .filter(|name| *name != "alt" && !BOOLEAN_ATTRS.contains(name))
+        .collect();
+    if !empty_attrs.is_empty() {
+        node.remove_attrs(&empty_attrs);
+    }
+}
+
+/// Removes the contents of the `script` and `style` elements in foreign (SVG or MathML) content,
+/// where the parser turns CDATA sections into plain text.
+fn strip_foreign_raw_text(node: &NodeRef) {
+    let elements: Vec<NodeRef> = node
+        .descendants_it()
+        .filter(|n| {
+            n.qual_name_ref().is_some_and(|qual_name| {
+                qual_name.ns != ns!(html)
+                    && matches!(
+                        qual_name.local,
+                        local_name!("script") | local_name!("style")
+                    )
+            })
+        })
+        .collect();
+    for element in elements {
+        element.set_text("");
+    }
+}
+
+/// Keeps only the first `max` attributes of the element, in document order.
+fn cap_attrs(node: &NodeRef, max: usize) {
+    node.update(|n| {
+        if let Some(el) = n.as_element_mut() {
+            el.attrs.truncate(max);
+        }
+    });
+}
+
+/// Sorts the attributes of the element by their local name, keeping the relative order
+/// of attributes with the same local name (e.g. `xlink:href` and `href`).
+fn sort_attrs(node: &NodeRef) {
+    node.update(|n| {
+        if let Some(el) = n.as_element_mut() {
+            el.attrs
+                .sort_by(|a, b| a.name.local.as_ref().cmp(b.name.local.as_ref()));
+        }
+    });
+}
+
 /// An **excluding** rule for sanitizing attributes of a specific element.
 #[derive(Debug, Clone, Default)]
 pub(crate) struct AttributeRule<'a> {
@@ -20,6 +197,23 @@ pub(crate) struct AttributeRule<'a> {
     pub(crate) element: Option<LocalName>,
     /// The list of attribute keys to be excluded.
     pub(crate) attributes: &'a [&'a str],
+    /// The list of owned attribute names to be excluded, in addition to `attributes`.
+    pub(crate) attr_names: Vec<LocalName>,
+    /// Whether the rule excludes all attributes of the element, regardless of `attributes`.
+    pub(crate) all_attrs: bool,
+    /// The namespace of the elements to which this rule applies.
+    /// If `None`, the rule applies to elements of all namespaces.
+    pub(crate) namespace: Option<Namespace>,
+}
+
+impl AttributeRule<'_> {
+    /// Returns all attribute names listed by the rule.
+    fn names(&self) -> impl Iterator<Item = &str> {
+        self.attributes
+            .iter()
+            .copied()
+            .chain(self.attr_names.iter().map(|name| name.as_ref()))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -31,9 +225,72 @@ pub struct Policy<'a, T: SanitizeDirective = Restrictive> {
     /// The list of element names excluded from the base [Policy].
     /// For [Permissive] directive: elements to remove (keeping their children)
     /// For [Restrictive] directive: elements to keep
-    pub(crate) elements_to_exclude: Vec<LocalName>,
+    pub(crate) elements_to_exclude: Cow<'a, [LocalName]>,
     /// Specifies the names of elements to remove from the DOM with their children during sanitization.
     pub(crate) elements_to_remove: Vec<LocalName>,
+    /// The list of attribute name prefixes to remove regardless of the directive.
+    pub(crate) attr_prefixes_to_strip: Vec<&'a str>,
+    /// The list of transforms applied to the values of surviving attributes, keyed by attribute name.
+    pub(crate) attr_transforms: Vec<(&'a str, &'a [AttrTransform])>,
+    /// The maximum number of whitespace-separated tokens kept in the value of an attribute, keyed by attribute name.
+    pub(crate) attr_token_caps: Vec<(&'a str, usize)>,
+    /// If set, `data:` URIs in `src` attributes are only kept for images not larger than this size in bytes.
+    pub(crate) data_images_max_bytes: Option<usize>,
+    /// Specifies the namespaces of elements to remove from the DOM with their children during sanitization.
+    pub(crate) namespaces_to_remove: Vec<Namespace>,
+    /// The list of patterns removed from the text content after sanitization.
+    #[cfg(feature = "regex")]
+    pub(crate) text_patterns_to_strip: Vec<Regex>,
+    /// Specifies the names of elements to replace with a text node containing their escaped markup.
+    pub(crate) elements_to_escape: Vec<LocalName>,
+    /// The list of HTML element names to rename, paired with their new names.
+    pub(crate) elements_to_rename: Vec<(LocalName, LocalName)>,
+    /// The list of element names to remove from the DOM if they are left empty after sanitization.
+    pub(crate) elements_to_prune: Vec<LocalName>,
+    /// Whether to remove attributes sharing the local name with a preceding attribute of the same element.
+    pub(crate) dedupe_attrs: bool,
+    /// If set, comments are removed unless their contents exactly match one of the listed strings.
+    pub(crate) comments_to_keep: Option<Vec<&'a str>>,
+    /// The list of compiled selectors of elements excluded from the base [Policy], like `elements_to_exclude`.
+    pub(crate) selectors_to_exclude: Vec<Matcher>,
+    /// The list of compiled selectors of elements to remove from the DOM with their children.
+    pub(crate) selectors_to_remove: Vec<Matcher>,
+    /// The maximum number of elements with a given name kept in the document, in document order.
+    pub(crate) element_limits: Vec<(LocalName, usize)>,
+    /// A function run on the sanitized node after all other rules, see [`PolicyBuilder::with_finalizer`].
+    pub(crate) finalizer: Option<Finalizer<'a>>,
+    /// Whether to filter the CSS in the `style` elements kept after sanitization.
+    pub(crate) sanitize_style_contents: bool,
+    /// Whether to sort the attributes of kept elements by their local name.
+    pub(crate) sort_attrs: bool,
+    /// Whether to append the URL of unwrapped `a` elements as text after their content.
+    pub(crate) unwrap_anchors_with_href: bool,
+    /// The attributes whose value is copied into another attribute before the attribute rules are applied, as `(element, from, to)`.
+    pub(crate) attrs_to_promote: Vec<(LocalName, LocalName, LocalName)>,
+    /// Whether to insert a space at the boundaries of unwrapped elements that would join two words.
+    pub(crate) insert_space_on_unwrap: bool,
+    /// The list of element names collapsed into their only child element if it has the same name.
+    pub(crate) elements_to_collapse: Vec<LocalName>,
+    /// Whether to collect the text of removed `script` and `style` elements in reports.
+    pub(crate) collect_removed_scripts: bool,
+    /// The attributes set on elements lacking them after sanitization, as `(element, name, value)`.
+    pub(crate) attrs_to_default: Vec<(LocalName, LocalName, String)>,
+    /// The attribute marking trusted elements, whose subtrees are not sanitized.
+    pub(crate) trust_marker: Option<LocalName>,
+    /// Whether to remove surviving attributes with an empty or whitespace-only value.
+    pub(crate) drop_empty_attrs: bool,
+    /// The maximum number of elements kept in document order, the rest of the content is removed.
+    pub(crate) max_elements: Option<usize>,
+    /// Whether to strip the text of `script` and `style` elements in foreign content.
+    pub(crate) strip_cdata: bool,
+    /// The maximum number of attributes kept on an element.
+    pub(crate) max_attrs_per_element: Option<usize>,
+    /// The maximum length of the text kept in the document, in bytes.
+    pub(crate) max_total_text: Option<usize>,
+    /// Whether to track source positions of removed elements in reports.
+    pub(crate) track_positions: bool,
+    /// Whether `html`, `head` and `body` may be removed by the [Restrictive] directive.
+    pub(crate) allow_strip_structural: bool,
     pub(crate) _directive: std::marker::PhantomData<T>,
 }
 
@@ -44,10 +301,14 @@ impl<T: SanitizeDirective> Policy<'_, T> {
 impl<T: SanitizeDirective> SanitizePolicy for Policy<'_, T> {
     fn should_exclude(&self, node: &NodeRef) -> bool {
         is_node_name_in(&self.elements_to_exclude, node)
+            || is_node_matching_any(&self.selectors_to_exclude, node)
     }
 
     fn should_remove(&self, node: &NodeRef) -> bool {
-        is_node_name_in(&self.elements_to_remove, node)
+        node.qual_name_ref().is_some_and(|qual_name| {
+            self.elements_to_remove.contains(&qual_name.local)
+                || self.namespaces_to_remove.contains(&qual_name.ns)
+        }) || is_node_matching_any(&self.selectors_to_remove, node)
     }
 
     fn has_attrs_to_exclude(&self) -> bool {
@@ -57,37 +318,555 @@ impl<T: SanitizeDirective> SanitizePolicy for Policy<'_, T> {
     fn is_empty(&self) -> bool {
         self.elements_to_exclude.is_empty()
             && self.elements_to_remove.is_empty()
+            && self.namespaces_to_remove.is_empty()
             && self.attrs_to_exclude.is_empty()
+            && self.elements_to_prune.is_empty()
+            && self.attr_prefixes_to_strip.is_empty()
+            && self.attr_transforms.is_empty()
+            && self.attr_token_caps.is_empty()
+            && self.data_images_max_bytes.is_none()
+            && self.elements_to_rename.is_empty()
+            && self.elements_to_escape.is_empty()
+            && !self.dedupe_attrs
+            && self.comments_to_keep.is_none()
+            && self.selectors_to_exclude.is_empty()
+            && self.selectors_to_remove.is_empty()
+            && self.element_limits.is_empty()
+            && !self.sanitize_style_contents
+            && !self.sort_attrs
+            && !self.unwrap_anchors_with_href
+            && self.attrs_to_promote.is_empty()
+            && self.elements_to_collapse.is_empty()
+            && self.attrs_to_default.is_empty()
+            && !self.drop_empty_attrs
+            && self.max_elements.is_none()
+            && !self.strip_cdata
+            && self.max_attrs_per_element.is_none()
+            && self.max_total_text.is_none()
+            && self.has_no_text_patterns()
+    }
+
+    #[cfg(feature = "regex")]
+    fn sanitize_text(&self, node: &NodeRef) {
+        if self.text_patterns_to_strip.is_empty() {
+            return;
+        }
+        let text_nodes: Vec<NodeRef> = node.descendants_it().filter(|n| n.is_text()).collect();
+        for text_node in text_nodes {
+            let text = text_node.text();
+            let mut stripped = std::borrow::Cow::Borrowed(text.as_ref());
+            for pattern in &self.text_patterns_to_strip {
+                if let std::borrow::Cow::Owned(s) = pattern.replace_all(&stripped, "") {
+                    stripped = std::borrow::Cow::Owned(s);
+                }
+            }
+            if let std::borrow::Cow::Owned(s) = stripped {
+                text_node.update(|n| {
+                    if let dom_query::NodeData::Text { ref mut contents } = n.data {
+                        *contents = s.into();
+                    }
+                });
+            }
+        }
+    }
+
+    fn sanitize_comments(&self, node: &NodeRef) {
+        let Some(ref comments_to_keep) = self.comments_to_keep else {
+            return;
+        };
+        let comments: Vec<NodeRef> = node
+            .descendants_it()
+            .filter(|n| {
+                n.query_or(false, |n| match n.data {
+                    dom_query::NodeData::Comment { ref contents } => {
+                        !comments_to_keep.contains(&contents.as_ref())
+                    }
+                    _ => false,
+                })
+            })
+            .collect();
+        for comment in comments {
+            comment.remove_from_parent();
+        }
+    }
+
+    fn prepare_attrs(&self, node: &NodeRef) {
+        if self.attrs_to_promote.is_empty() {
+            return;
+        }
+        let is_html = node
+            .qual_name_ref()
+            .is_some_and(|qual_name| qual_name.ns == ns!(html));
+        if !is_html {
+            return;
+        }
+        for (element, from, to) in &self.attrs_to_promote {
+            if !node.has_name(element) || node.has_attr(to) {
+                continue;
+            }
+            if let Some(value) = node.attr(from) {
+                node.set_attr(to, &value);
+            }
+        }
+    }
+
+    fn rewrite_attrs(&self, node: &NodeRef) {
+        if self.dedupe_attrs {
+            dedupe_attrs(node);
+        }
+        for (attr_name, transforms) in &self.attr_transforms {
+            let Some(value) = node.attr(attr_name) else {
+                continue;
+            };
+            let new_value = apply_transforms(transforms, &value);
+            if new_value != value.as_ref() {
+                node.set_attr(attr_name, &new_value);
+            }
+        }
+        for &(attr_name, max_tokens) in &self.attr_token_caps {
+            let Some(value) = node.attr(attr_name) else {
+                continue;
+            };
+            if value.split_ascii_whitespace().nth(max_tokens).is_some() {
+                let tokens: Vec<&str> = value.split_ascii_whitespace().take(max_tokens).collect();
+                node.set_attr(attr_name, &tokens.join(" "));
+            }
+        }
+        if let Some(max_bytes) = self.data_images_max_bytes {
+            if node.attr("src").is_some_and(|src| {
+                starts_with_ignore_ascii_case(src.trim_start(), "data:")
+                    && !is_small_data_image(&src, max_bytes)
+            }) {
+                node.remove_attr("src");
+            }
+        }
+        if self.drop_empty_attrs {
+            drop_empty_attrs(node);
+        }
+        if let Some(max) = self.max_attrs_per_element {
+            cap_attrs(node, max);
+        }
+        if self.sort_attrs {
+            sort_attrs(node);
+        }
+    }
+
+    fn before_unwrap(&self, node: &NodeRef) {
+        if self.unwrap_anchors_with_href {
+            append_anchor_href(node);
+        }
+        // inserted spaces would change the text of preformatted content
+        let in_preformatted = node.parent().is_some_and(|parent| is_preformatted(&parent));
+        if self.insert_space_on_unwrap && !in_preformatted {
+            insert_boundary_spaces(node);
+        }
+    }
+
+    fn sanitize_styles(&self, node: &NodeRef) {
+        if !self.sanitize_style_contents {
+            return;
+        }
+        let styles: Vec<NodeRef> = node
+            .descendants_it()
+            .filter(|n| {
+                n.qual_name_ref()
+                    .is_some_and(|qual_name| qual_name.local == local_name!("style"))
+            })
+            .collect();
+        for style in styles {
+            let css = style.text();
+            let sanitized = crate::css::sanitize_css(&css);
+            if sanitized != css.as_ref() {
+                style.set_text(sanitized);
+            }
+        }
+    }
+
+    fn finalize(&self, node: &NodeRef) {
+        if self.strip_cdata {
+            strip_foreign_raw_text(node);
+        }
+        if !self.attrs_to_default.is_empty() {
+            self.set_default_attrs(node);
+        }
+        if let Some(ref finalizer) = self.finalizer {
+            (finalizer.0)(node);
+        }
+        if self.dedupe_attrs {
+            // the finalizer may add attributes without going through the other rules
+            let elements: Vec<NodeRef> = node.descendants_it().filter(|n| n.is_element()).collect();
+            for element in elements {
+                dedupe_attrs(&element);
+            }
+        }
+    }
+
+    fn max_elements(&self) -> Option<usize> {
+        self.max_elements
+    }
+
+    fn max_total_text(&self) -> Option<usize> {
+        self.max_total_text
+    }
+
+    fn has_element_limits(&self) -> bool {
+        !self.element_limits.is_empty()
+    }
+
+    fn element_limit(&self, node: &NodeRef) -> Option<usize> {
+        let qual_name = node.qual_name_ref()?;
+        self.element_limits
+            .iter()
+            .filter(|(name, _)| name == &qual_name.local)
+            .map(|&(_, limit)| limit)
+            .min()
+    }
+
+    fn should_escape(&self, node: &NodeRef) -> bool {
+        is_node_name_in(&self.elements_to_escape, node)
+    }
+
+    fn allow_strip_structural(&self) -> bool {
+        self.allow_strip_structural
+    }
+
+    fn collect_removed_scripts(&self) -> bool {
+        self.collect_removed_scripts
+    }
+
+    fn has_trust_marker(&self) -> bool {
+        self.trust_marker.is_some()
+    }
+
+    fn is_trusted(&self, node: &NodeRef) -> bool {
+        self.trust_marker
+            .as_ref()
+            .is_some_and(|marker| node.has_attr(marker))
+    }
+
+    fn rename_element(&self, node: &NodeRef) {
+        if self.elements_to_rename.is_empty() {
+            return;
+        }
+        let new_name = node.qual_name_ref().and_then(|qual_name| {
+            if qual_name.ns != ns!(html) {
+                return None;
+            }
+            self.elements_to_rename
+                .iter()
+                .find(|(old_name, _)| old_name == &qual_name.local)
+                .map(|(_, new_name)| new_name.clone())
+        });
+        if let Some(new_name) = new_name {
+            // `NodeRef::rename` would drop the namespace, so the renamed element
+            // wouldn't be an HTML element anymore.
+            let new_name = QualName::new(None, ns!(html), new_name);
+            node.update(|n| {
+                if let Some(el) = n.as_element_mut() {
+                    el.name = new_name;
+                }
+            });
+        }
+    }
+
+    fn has_attrs_to_strip(&self) -> bool {
+        !self.attr_prefixes_to_strip.is_empty()
+    }
+
+    fn should_strip_attr(&self, _node: &NodeRef, attr_name: &str) -> bool {
+        self.attr_prefixes_to_strip
+            .iter()
+            .any(|prefix| starts_with_ignore_ascii_case(attr_name, prefix))
+    }
+
+    fn has_elements_to_collapse(&self) -> bool {
+        !self.elements_to_collapse.is_empty()
+    }
+
+    fn should_collapse(&self, node: &NodeRef) -> bool {
+        is_node_name_in(&self.elements_to_collapse, node)
+    }
+
+    fn has_elements_to_prune(&self) -> bool {
+        !self.elements_to_prune.is_empty()
+    }
+
+    fn should_prune(&self, node: &NodeRef) -> bool {
+        is_node_name_in(&self.elements_to_prune, node)
     }
 
     fn exclude_attrs<F>(&self, node: &NodeRef, exclude_fn: F)
     where
         F: FnOnce(&NodeRef, &[&str]),
     {
+        let node_attrs;
         let mut attrs: Vec<&str> = vec![];
+        let mut all_attrs = false;
         {
             if let Some(qual_name) = node.qual_name_ref() {
                 for rule in &self.attrs_to_exclude {
+                    if rule
+                        .namespace
+                        .as_ref()
+                        .is_some_and(|ns| ns != &qual_name.ns)
+                    {
+                        continue;
+                    }
                     let Some(element_name) = &rule.element else {
-                        attrs.extend(rule.attributes);
+                        attrs.extend(rule.names());
                         continue;
                     };
                     if &qual_name.local == element_name {
-                        attrs.extend(rule.attributes);
+                        attrs.extend(rule.names());
+                        all_attrs |= rule.all_attrs;
                     }
                 }
             }
         }
+        if all_attrs {
+            node_attrs = node.attrs();
+            attrs.extend(node_attrs.iter().map(|a| a.name.local.as_ref()));
+        }
 
         exclude_fn(node, &attrs);
     }
 }
 
+impl<T: SanitizeDirective> Policy<'_, T> {
+    #[cfg(feature = "regex")]
+    fn has_no_text_patterns(&self) -> bool {
+        self.text_patterns_to_strip.is_empty()
+    }
+
+    #[cfg(not(feature = "regex"))]
+    fn has_no_text_patterns(&self) -> bool {
+        true
+    }
+}
+
+impl<T: SanitizeDirective> Sanitizer for Policy<'_, T> {
+    fn sanitize_node(&self, node: &NodeRef) {
+        Policy::sanitize_node(self, node);
+    }
+}
+
 impl<'a, T: SanitizeDirective> Policy<'a, T> {
+    /// Sets the default attributes on the HTML elements under the node that lack them.
+    fn set_default_attrs(&self, node: &NodeRef) {
+        let elements: Vec<NodeRef> = node.descendants_it().filter(|n| n.is_element()).collect();
+        for element in elements {
+            let is_html = element
+                .qual_name_ref()
+                .is_some_and(|qual_name| qual_name.ns == ns!(html));
+            if !is_html {
+                continue;
+            }
+            for (name, attr_name, value) in &self.attrs_to_default {
+                if element.has_name(name) && !element.has_attr(attr_name) {
+                    element.set_attr(attr_name, value);
+                }
+            }
+        }
+    }
+
     /// Creates a new [`PolicyBuilder`] with default values.
     pub fn builder() -> PolicyBuilder<'a, T> {
         PolicyBuilder::new()
     }
+
+    /// Creates a [`Policy`] excluding the elements of a static slice, without any other rules.
+    ///
+    /// Unlike [`PolicyBuilder::exclude_elements`], the slice is borrowed, not copied into a `Vec`,
+    /// so constructing the policy doesn't allocate. This pays off when the same policy is built repeatedly,
+    /// e.g. per request in a high-throughput service. The element names must be known at compile time
+    /// to be declared as a constant, using the [`html5ever::local_name`] macro.
+    ///
+    /// The tradeoff is flexibility: there is no way to add attribute rules or other options, so with the
+    /// [`Restrictive`] directive all attributes are removed. If the policy needs more than that,
+    /// use the builder, or build the policy once and share it. Merging such a policy into a builder
+    /// copies the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dom_sanitizer::RestrictivePolicy;
+    /// use html5ever::{local_name, LocalName};
+    ///
+    /// const ALLOWED: &[LocalName] = &[local_name!("p"), local_name!("b"), local_name!("i")];
+    ///
+    /// let policy = RestrictivePolicy::from_static(ALLOWED);
+    /// let html = policy.sanitize_html(r#"<div><p class="x"><b>Bold</b> <u>text</u></p></div>"#);
+    /// assert!(html.contains("<body><p><b>Bold</b> text</p></body>"));
+    /// ```
+    pub fn from_static(elements_to_exclude: &'static [LocalName]) -> Self {
+        Policy {
+            elements_to_exclude: Cow::Borrowed(elements_to_exclude),
+            ..PolicyBuilder::new().build()
+        }
+    }
+}
+
+impl<'a, T: SanitizeDirective> Policy<'a, T> {
+    /// Moves the rules of the policy into a policy with the [`Restrictive`] directive,
+    /// dropping the rules of [`PolicyBuilder::strip_all_attrs_from`]: a [`Restrictive`] policy
+    /// would keep every attribute of these elements, while without the rules it keeps none of them.
+    pub(crate) fn into_restrictive(mut self) -> Policy<'a, Restrictive> {
+        self.attrs_to_exclude.retain(|rule| !rule.all_attrs);
+        self.with_directive()
+    }
+
+    /// Moves all rules of the policy into a policy with another directive.
+    pub(crate) fn with_directive<U: SanitizeDirective>(self) -> Policy<'a, U> {
+        Policy {
+            attrs_to_exclude: self.attrs_to_exclude,
+            elements_to_exclude: self.elements_to_exclude,
+            elements_to_remove: self.elements_to_remove,
+            attr_prefixes_to_strip: self.attr_prefixes_to_strip,
+            attr_transforms: self.attr_transforms,
+            attr_token_caps: self.attr_token_caps,
+            data_images_max_bytes: self.data_images_max_bytes,
+            namespaces_to_remove: self.namespaces_to_remove,
+            #[cfg(feature = "regex")]
+            text_patterns_to_strip: self.text_patterns_to_strip,
+            elements_to_escape: self.elements_to_escape,
+            elements_to_rename: self.elements_to_rename,
+            elements_to_prune: self.elements_to_prune,
+            dedupe_attrs: self.dedupe_attrs,
+            comments_to_keep: self.comments_to_keep,
+            selectors_to_exclude: self.selectors_to_exclude,
+            selectors_to_remove: self.selectors_to_remove,
+            element_limits: self.element_limits,
+            finalizer: self.finalizer,
+            sanitize_style_contents: self.sanitize_style_contents,
+            sort_attrs: self.sort_attrs,
+            unwrap_anchors_with_href: self.unwrap_anchors_with_href,
+            attrs_to_promote: self.attrs_to_promote,
+            insert_space_on_unwrap: self.insert_space_on_unwrap,
+            elements_to_collapse: self.elements_to_collapse,
+            collect_removed_scripts: self.collect_removed_scripts,
+            attrs_to_default: self.attrs_to_default,
+            trust_marker: self.trust_marker,
+            drop_empty_attrs: self.drop_empty_attrs,
+            max_elements: self.max_elements,
+            strip_cdata: self.strip_cdata,
+            max_attrs_per_element: self.max_attrs_per_element,
+            max_total_text: self.max_total_text,
+            track_positions: self.track_positions,
+            allow_strip_structural: self.allow_strip_structural,
+            _directive: std::marker::PhantomData,
+        }
+    }
+}
+
+impl Policy<'static, Restrictive> {
+    /// Infers a policy from a "known good" HTML sample: it allows exactly the elements found in the sample,
+    /// and for every element, the attributes it has anywhere in the sample.
+    ///
+    /// The result is a starting point for a policy, to be reviewed before use, not a security guarantee:
+    /// anything present in the sample is trusted, including event handlers or `javascript:` URLs.
+    /// Only local names are collected, namespaces are ignored. The `html`, `head` and `body` elements
+    /// the parser adds to the sample are kept by the [`Restrictive`] directive anyway.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use dom_sanitizer::DenyAllPolicy;
+    ///
+    /// let policy = DenyAllPolicy::learn_from(r#"<p class="lead">Text <a href="/">link</a></p>"#);
+    /// let html = policy.sanitize_html(r#"<div><p class="lead" id="x"><a href="/a" class="y">A</a></p></div>"#);
+    /// assert!(html.contains(r#"<p class="lead"><a href="/a">A</a></p>"#));
+    /// ```
+    pub fn learn_from<S: Into<StrTendril>>(html: S) -> Self {
+        let doc = dom_query::Document::from(html);
+        let mut elements: Vec<(LocalName, Vec<LocalName>)> = vec![];
+        for node in doc.root().descendants_it() {
+            let Some(name) = node
+                .qual_name_ref()
+                .map(|qual_name| qual_name.local.clone())
+            else {
+                continue;
+            };
+            let index = match elements.iter().position(|(element, _)| element == &name) {
+                Some(index) => index,
+                None => {
+                    elements.push((name, vec![]));
+                    elements.len() - 1
+                }
+            };
+            let attr_names = &mut elements[index].1;
+            for attr in node.attrs() {
+                if !attr_names.contains(&attr.name.local) {
+                    attr_names.push(attr.name.local);
+                }
+            }
+        }
+        let mut policy = Policy::builder().build();
+        for (element, attr_names) in elements {
+            if !attr_names.is_empty() {
+                policy.attrs_to_exclude.push(AttributeRule {
+                    element: Some(element.clone()),
+                    attr_names,
+                    ..AttributeRule::default()
+                });
+            }
+            policy.elements_to_exclude.to_mut().push(element);
+        }
+        policy
+    }
+}
+
+impl<'a> Policy<'a, Permissive> {
+    /// Converts the policy into its logical inverse, with the [`Restrictive`] directive.
+    ///
+    /// The rules are moved as is, only their meaning is flipped:
+    /// - excluded elements, which were unwrapped, become the only kept elements;
+    /// - excluded attributes, which were removed, become the only kept attributes;
+    /// - elements to remove are removed under both directives, so they carry over unchanged,
+    ///   as well as all other rules (attribute stripping, transforms, pruning, etc.).
+    ///
+    /// The elements of [`PolicyBuilder::strip_all_attrs_from`] are the exception: their rules are dropped,
+    /// so these elements keep no attributes, as they would otherwise keep all of them.
+    pub fn inverted(self) -> Policy<'a, Restrictive> {
+        self.into_restrictive()
+    }
+}
+
+impl<'a> Policy<'a, Restrictive> {
+    /// Converts the policy into its logical inverse, with the [`Permissive`] directive.
+    ///
+    /// The rules are moved as is, only their meaning is flipped:
+    /// - excluded elements, which were the only kept elements, become unwrapped;
+    /// - excluded attributes, which were the only kept attributes, become removed;
+    /// - elements to remove are removed under both directives, so they carry over unchanged,
+    ///   as well as all other rules (attribute stripping, transforms, pruning, etc.).
+    pub fn inverted(self) -> Policy<'a, Permissive> {
+        self.with_directive()
+    }
+
+    /// Returns the local names of the attributes the policy would strip from the element,
+    /// without modifying it, e.g. to find out why an `alt` or `title` disappeared.
+    ///
+    /// This is a diagnostic: it reports the attributes that are not kept by the attribute exclusion rules,
+    /// plus the kept ones removed regardless of the directive (e.g. by [`PolicyBuilder::strip_attr_prefixes`]).
+    /// Changes made by the value rules (e.g. [`PolicyBuilder::drop_empty_attrs`]) or by promoting
+    /// attributes are not reported, and neither is whether the element itself would be kept.
+    /// The names are returned in the order of the element's attributes.
+    pub fn stripped_attrs(&self, node: &NodeRef) -> Vec<String> {
+        let mut kept: Vec<String> = vec![];
+        if self.has_attrs_to_exclude() {
+            self.exclude_attrs(node, |_, attrs| {
+                kept = attrs.iter().map(|name| name.to_string()).collect();
+            });
+        }
+        node.attrs()
+            .iter()
+            .map(|a| a.name.local.as_ref())
+            .filter(|name| {
+                !kept.iter().any(|kept| kept == name) || self.should_strip_attr(node, name)
+            })
+            .map(String::from)
+            .collect()
+    }
 }
 
 /// Alias for [`Policy`] using the [`Permissive`] directive (default-allow behavior).
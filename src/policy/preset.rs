@@ -23,6 +23,15 @@
 /// - **`list_policy`**:
 ///   Excludes list-related elements such as `li`, `ul`, and `ol`.
 ///
+/// - **`embed_policy`**:
+///   Removes embedded content elements: `object`, `embed` and `applet`.
+///
+/// - **`strict_embed_policy`**:
+///   Same as `embed_policy`, but also removes `iframe`.
+///
+/// - **`iframe_attr_policy`**:
+///   Excludes the `src`, `width`, `height`, `allow`, `sandbox`, and `loading` attributes for `iframe`.
+///
 /// # Generics
 ///
 /// Each policy is generic over a type `T` that implements the `SanitizeDirective` trait.
@@ -51,6 +60,12 @@ use crate::traits::SanitizeDirective;
 
 /// Excludes all table-related elements, such as `table`, `caption`, `colgroup`, `col`, `th`,
 /// `tbody`, `tr`, `td`, and `tfoot`, from the base sanitization policy.
+///
+/// Malformed tables are fixed by the parser before sanitization: content misnested in a table
+/// (e.g. the text in `<table>foo<tr>`) is foster-parented, i.e. moved before the table,
+/// and missing `tbody` and `tr` elements are inserted, so `<table>foo<tr><td>a</table>`
+/// results in `foo<table><tbody><tr><td>a</td></tr></tbody></table>`.
+/// If only some table parts are excluded, those left outside of a table by unwrapping are unwrapped as well.
 pub fn table_policy<'a, T>() -> Policy<'a, T>
 where
     T: SanitizeDirective,
@@ -112,3 +127,46 @@ where
         .exclude_elements(&["li", "ul", "ol"])
         .build()
 }
+
+/// Removes embedded content elements, such as `object`, `embed`, and `applet`,
+/// from the DOM together with their children.
+///
+/// `iframe` is not listed here: under the [`crate::Restrictive`] directive it is removed
+/// unless explicitly allowed (e.g. with `exclude_elements(&["iframe"])` and [`iframe_attr_policy`]).
+/// Use [`strict_embed_policy`] to remove `iframe` regardless of the directive.
+pub fn embed_policy<'a, T>() -> Policy<'a, T>
+where
+    T: SanitizeDirective,
+{
+    Policy::builder()
+        .remove_elements(&["object", "embed", "applet"])
+        .build()
+}
+
+/// Removes embedded content elements, such as `object`, `embed`, `applet`, and `iframe`,
+/// from the DOM together with their children.
+pub fn strict_embed_policy<'a, T>() -> Policy<'a, T>
+where
+    T: SanitizeDirective,
+{
+    Policy::builder()
+        .remove_elements(&["object", "embed", "applet", "iframe"])
+        .build()
+}
+
+/// Excludes the `src`, `width`, `height`, `allow`, `sandbox`, and `loading` attributes
+/// for the `iframe` element from the base sanitization policy.
+///
+/// Under the [`crate::Restrictive`] directive only these attributes are kept on `iframe`.
+/// The `iframe` element itself still has to be allowed explicitly.
+pub fn iframe_attr_policy<'a, T>() -> Policy<'a, T>
+where
+    T: SanitizeDirective,
+{
+    Policy::builder()
+        .exclude_element_attrs(
+            "iframe",
+            &["src", "width", "height", "allow", "sandbox", "loading"],
+        )
+        .build()
+}
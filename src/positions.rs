@@ -0,0 +1,142 @@
+use std::borrow::Cow;
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::HashMap;
+
+use dom_query::{Document, NodeId};
+use html5ever::tendril::TendrilSink;
+use html5ever::tree_builder::{ElementFlags, NodeOrText, QuirksMode, TreeBuilderOpts, TreeSink};
+use html5ever::{parse_document, Attribute, ParseOpts, QualName};
+use tendril::StrTendril;
+
+/// Source lines of the element start tags, keyed by node id.
+pub(crate) type SourceLines = HashMap<NodeId, u64>;
+
+/// A [`TreeSink`] that delegates to [`Document`] and records the source line
+/// of every created element.
+struct PositionSink {
+    document: Document,
+    current_line: Cell<u64>,
+    lines: RefCell<SourceLines>,
+}
+
+/// Parses the HTML document the same way as `Document::from`, additionally tracking
+/// the source line of every element.
+pub(crate) fn parse_document_with_positions<S: Into<StrTendril>>(
+    html: S,
+) -> (Document, SourceLines) {
+    let sink = PositionSink {
+        document: Document::default(),
+        current_line: Cell::new(1),
+        lines: RefCell::new(HashMap::new()),
+    };
+    let opts = ParseOpts {
+        tokenizer: Default::default(),
+        tree_builder: TreeBuilderOpts {
+            scripting_enabled: false,
+            ..Default::default()
+        },
+    };
+    parse_document(sink, opts).one(html)
+}
+
+impl TreeSink for PositionSink {
+    type Handle = NodeId;
+    type Output = (Document, SourceLines);
+    type ElemName<'a> = Ref<'a, QualName>;
+
+    fn finish(self) -> Self::Output {
+        (self.document, self.lines.into_inner())
+    }
+
+    fn parse_error(&self, msg: Cow<'static, str>) {
+        self.document.parse_error(msg);
+    }
+
+    fn get_document(&self) -> Self::Handle {
+        self.document.get_document()
+    }
+
+    fn elem_name<'a>(&'a self, target: &'a Self::Handle) -> Self::ElemName<'a> {
+        self.document.elem_name(target)
+    }
+
+    fn create_element(
+        &self,
+        name: QualName,
+        attrs: Vec<Attribute>,
+        flags: ElementFlags,
+    ) -> Self::Handle {
+        let id = self.document.create_element(name, attrs, flags);
+        self.lines.borrow_mut().insert(id, self.current_line.get());
+        id
+    }
+
+    fn create_comment(&self, text: StrTendril) -> Self::Handle {
+        self.document.create_comment(text)
+    }
+
+    fn create_pi(&self, target: StrTendril, data: StrTendril) -> Self::Handle {
+        self.document.create_pi(target, data)
+    }
+
+    fn append(&self, parent: &Self::Handle, child: NodeOrText<Self::Handle>) {
+        self.document.append(parent, child);
+    }
+
+    fn append_based_on_parent_node(
+        &self,
+        element: &Self::Handle,
+        prev_element: &Self::Handle,
+        child: NodeOrText<Self::Handle>,
+    ) {
+        self.document
+            .append_based_on_parent_node(element, prev_element, child);
+    }
+
+    fn append_doctype_to_document(
+        &self,
+        name: StrTendril,
+        public_id: StrTendril,
+        system_id: StrTendril,
+    ) {
+        self.document
+            .append_doctype_to_document(name, public_id, system_id);
+    }
+
+    fn get_template_contents(&self, target: &Self::Handle) -> Self::Handle {
+        self.document.get_template_contents(target)
+    }
+
+    fn same_node(&self, x: &Self::Handle, y: &Self::Handle) -> bool {
+        self.document.same_node(x, y)
+    }
+
+    fn set_quirks_mode(&self, mode: QuirksMode) {
+        self.document.set_quirks_mode(mode);
+    }
+
+    fn append_before_sibling(&self, sibling: &Self::Handle, new_node: NodeOrText<Self::Handle>) {
+        self.document.append_before_sibling(sibling, new_node);
+    }
+
+    fn add_attrs_if_missing(&self, target: &Self::Handle, attrs: Vec<Attribute>) {
+        self.document.add_attrs_if_missing(target, attrs);
+    }
+
+    fn remove_from_parent(&self, target: &Self::Handle) {
+        self.document.remove_from_parent(target);
+    }
+
+    fn reparent_children(&self, node: &Self::Handle, new_parent: &Self::Handle) {
+        self.document.reparent_children(node, new_parent);
+    }
+
+    fn is_mathml_annotation_xml_integration_point(&self, handle: &Self::Handle) -> bool {
+        self.document
+            .is_mathml_annotation_xml_integration_point(handle)
+    }
+
+    fn set_current_line(&self, line_number: u64) {
+        self.current_line.set(line_number);
+    }
+}
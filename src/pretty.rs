@@ -0,0 +1,187 @@
+use dom_query::NodeRef;
+use html5ever::{local_name, LocalName};
+use tendril::StrTendril;
+
+const INDENT: &str = "  ";
+
+fn is_block(name: &LocalName) -> bool {
+    matches!(
+        *name,
+        local_name!("html")
+            | local_name!("head")
+            | local_name!("body")
+            | local_name!("title")
+            | local_name!("meta")
+            | local_name!("link")
+            | local_name!("base")
+            | local_name!("script")
+            | local_name!("style")
+            | local_name!("noscript")
+            | local_name!("address")
+            | local_name!("article")
+            | local_name!("aside")
+            | local_name!("blockquote")
+            | local_name!("details")
+            | local_name!("summary")
+            | local_name!("dialog")
+            | local_name!("div")
+            | local_name!("dl")
+            | local_name!("dt")
+            | local_name!("dd")
+            | local_name!("fieldset")
+            | local_name!("legend")
+            | local_name!("figure")
+            | local_name!("figcaption")
+            | local_name!("footer")
+            | local_name!("form")
+            | local_name!("h1")
+            | local_name!("h2")
+            | local_name!("h3")
+            | local_name!("h4")
+            | local_name!("h5")
+            | local_name!("h6")
+            | local_name!("header")
+            | local_name!("hgroup")
+            | local_name!("hr")
+            | local_name!("li")
+            | local_name!("main")
+            | local_name!("nav")
+            | local_name!("ol")
+            | local_name!("ul")
+            | local_name!("p")
+            | local_name!("section")
+            | local_name!("table")
+            | local_name!("caption")
+            | local_name!("colgroup")
+            | local_name!("thead")
+            | local_name!("tbody")
+            | local_name!("tfoot")
+            | local_name!("tr")
+            | local_name!("td")
+            | local_name!("th")
+    )
+}
+
+/// Elements whose contents are whitespace-sensitive and must be written verbatim.
+fn is_verbatim(name: &LocalName) -> bool {
+    matches!(
+        *name,
+        local_name!("pre") | local_name!("textarea") | local_name!("template")
+    )
+}
+
+fn is_block_node(node: &NodeRef) -> bool {
+    node.qual_name_ref()
+        .is_some_and(|qual_name| is_block(&qual_name.local) || is_verbatim(&qual_name.local))
+}
+
+fn escape(s: &str, attr_mode: bool, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '\u{00A0}' => out.push_str("&nbsp;"),
+            '"' if attr_mode => out.push_str("&quot;"),
+            '<' if !attr_mode => out.push_str("&lt;"),
+            '>' if !attr_mode => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn write_indent(depth: usize, out: &mut String) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    for _ in 0..depth {
+        out.push_str(INDENT);
+    }
+}
+
+/// Writes the start tag of the element node, with its attributes.
+pub(crate) fn write_start_tag(node: &NodeRef, out: &mut String) {
+    let Some(name) = node
+        .qual_name_ref()
+        .map(|qual_name| qual_name.local.clone())
+    else {
+        return;
+    };
+    out.push('<');
+    out.push_str(&name);
+    for attr in node.attrs() {
+        out.push(' ');
+        if let Some(ref prefix) = attr.name.prefix {
+            out.push_str(prefix);
+            out.push(':');
+        }
+        out.push_str(&attr.name.local);
+        out.push_str("=\"");
+        escape(&attr.value, true, out);
+        out.push('"');
+    }
+    out.push('>');
+}
+
+/// Writes a block-level node, which contains block-level children, one child per line.
+/// Blocks containing only inline content are written on a single line.
+fn write_block(node: &NodeRef, depth: usize, out: &mut String) {
+    let Some(name) = node
+        .qual_name_ref()
+        .map(|qual_name| qual_name.local.clone())
+    else {
+        return;
+    };
+    write_indent(depth, out);
+    let has_block_children = node.children_it(false).any(|child| is_block_node(&child));
+    if is_verbatim(&name) || !has_block_children {
+        out.push_str(&node.html());
+        return;
+    }
+    write_start_tag(node, out);
+    write_children(node, depth + 1, out);
+    write_indent(depth, out);
+    out.push_str("</");
+    out.push_str(&name);
+    out.push('>');
+}
+
+/// Writes the children of a node in block context: block-level children and runs of inline content
+/// are written on separate lines, whitespace-only text between them is dropped.
+fn write_children(node: &NodeRef, depth: usize, out: &mut String) {
+    let mut inline = String::new();
+    for child in node.children_it(false) {
+        if is_block_node(&child) {
+            flush_inline(&mut inline, depth, out);
+            write_block(&child, depth, out);
+        } else if child.is_text() {
+            escape(&child.text(), false, &mut inline);
+        } else if child.is_doctype() {
+            flush_inline(&mut inline, depth, out);
+            write_indent(depth, out);
+            out.push_str(&child.html());
+        } else {
+            inline.push_str(&child.html());
+        }
+    }
+    flush_inline(&mut inline, depth, out);
+}
+
+fn flush_inline(inline: &mut String, depth: usize, out: &mut String) {
+    let content = inline.trim();
+    if !content.is_empty() {
+        write_indent(depth, out);
+        out.push_str(content);
+    }
+    inline.clear();
+}
+
+/// Serializes the node's children as indented HTML: block-level elements are placed on their own lines
+/// and indented according to their depth.
+///
+/// The contents of `pre`, `textarea` and `template` elements, as well as blocks containing
+/// only inline content, are written as is.
+pub(crate) fn pretty_html(node: &NodeRef) -> StrTendril {
+    let mut out = String::new();
+    write_children(node, 0, &mut out);
+    out.push('\n');
+    StrTendril::from(out)
+}
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use dom_query::Document;
+use tendril::StrTendril;
+
+use crate::traits::Sanitizer;
+
+/// A shared, thread-safe policy, as stored in a [`PolicyRegistry`].
+pub type SharedPolicy<'a> = Arc<dyn Sanitizer + Send + Sync + 'a>;
+
+/// A collection of policies registered once and looked up by name.
+///
+/// Policies of different types and directives may be stored in the same registry,
+/// e.g. a strict [`crate::Policy`] for comments and a [`crate::plugin_policy::PluginPolicy`] for articles.
+///
+/// # Thread safety
+///
+/// Every registered policy must be `Send + Sync`. This holds for [`crate::Policy`], and for
+/// [`crate::plugin_policy::PluginPolicy`], whose checkers are `Send + Sync` and shared behind an `Arc`.
+/// Policies are never mutated during sanitization, so a registry, once populated, can be shared
+/// between threads (e.g. in a `static` `OnceLock` or behind an `Arc`) without locking.
+///
+/// # Examples
+///
+/// ```rust
+/// use dom_sanitizer::{AllowAllPolicy, DenyAllPolicy, PolicyRegistry};
+///
+/// let mut registry = PolicyRegistry::new();
+/// registry.register("comments", DenyAllPolicy::builder().exclude_elements(&["p"]).build());
+/// registry.register("articles", AllowAllPolicy::builder().remove_elements(&["script"]).build());
+///
+/// let html = registry.sanitize_html_with("comments", "<div><p>Hello</p></div>");
+/// assert!(html.unwrap().contains("<p>Hello</p>"));
+/// assert!(registry.sanitize_html_with("unknown", "<p></p>").is_none());
+/// ```
+#[derive(Default, Clone)]
+pub struct PolicyRegistry<'a> {
+    policies: HashMap<String, SharedPolicy<'a>>,
+}
+
+impl<'a> PolicyRegistry<'a> {
+    /// Creates a new empty [`PolicyRegistry`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a policy under the given name.
+    ///
+    /// Returns the policy previously registered under this name, if any.
+    pub fn register<S>(&mut self, name: &str, policy: S) -> Option<SharedPolicy<'a>>
+    where
+        S: Sanitizer + Send + Sync + 'a,
+    {
+        self.register_shared(name, Arc::new(policy))
+    }
+
+    /// Registers an already shared policy under the given name.
+    ///
+    /// Returns the policy previously registered under this name, if any.
+    pub fn register_shared(
+        &mut self,
+        name: &str,
+        policy: SharedPolicy<'a>,
+    ) -> Option<SharedPolicy<'a>> {
+        self.policies.insert(name.to_string(), policy)
+    }
+
+    /// Returns the policy registered under the given name.
+    pub fn get(&self, name: &str) -> Option<SharedPolicy<'a>> {
+        self.policies.get(name).cloned()
+    }
+
+    /// Returns `true` if a policy is registered under the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.policies.contains_key(name)
+    }
+
+    /// Returns the number of registered policies.
+    pub fn len(&self) -> usize {
+        self.policies.len()
+    }
+
+    /// Returns `true` if the registry contains no policies.
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Sanitizes the [`dom_query::Document`] with the policy registered under the given name.
+    ///
+    /// Returns `false` if no policy is registered under this name; the document is left untouched.
+    pub fn sanitize_with(&self, name: &str, document: &Document) -> bool {
+        let Some(policy) = self.policies.get(name) else {
+            return false;
+        };
+        policy.sanitize_document(document);
+        true
+    }
+
+    /// Sanitizes the HTML content with the policy registered under the given name.
+    ///
+    /// Returns `None` if no policy is registered under this name.
+    pub fn sanitize_html_with<S: Into<StrTendril>>(
+        &self,
+        name: &str,
+        html: S,
+    ) -> Option<StrTendril> {
+        let policy = self.policies.get(name)?;
+        let doc = Document::from(html);
+        policy.sanitize_document(&doc);
+        Some(doc.html())
+    }
+}
@@ -0,0 +1,162 @@
+use std::time::Instant;
+
+use dom_query::{NodeId, NodeRef};
+use html5ever::{local_name, ns, LocalName};
+use tendril::StrTendril;
+
+/// Describes how an element was removed from the DOM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalKind {
+    /// The element was removed together with its children.
+    Removed,
+    /// The element was removed, but its children were kept in its place.
+    Unwrapped,
+    /// The element was replaced with a text node containing its markup.
+    Escaped,
+}
+
+/// An element removed during sanitization.
+#[derive(Debug, Clone)]
+pub struct RemovedElement {
+    /// The local name of the removed element.
+    pub name: LocalName,
+    /// How the element was removed.
+    pub kind: RemovalKind,
+    /// The line of the element's start tag in the source HTML.
+    ///
+    /// Only available if the policy tracks positions and the HTML was parsed by the policy itself.
+    pub line: Option<u64>,
+    /// The markup of the removed element, captured before it was removed.
+    ///
+    /// Only available if markup capturing was enabled (see [`SanitizeReport::with_markup`]).
+    /// For [`RemovalKind::Unwrapped`] elements, it is only the start tag, as their children were kept.
+    pub markup: Option<StrTendril>,
+    /// The text of a removed `script` or `style` element.
+    ///
+    /// Only available if the policy collects removed scripts (see [`crate::PolicyBuilder::collect_removed_scripts`]).
+    pub raw_text: Option<StrTendril>,
+    pub(crate) id: NodeId,
+}
+
+/// A report about the changes made during sanitization.
+#[derive(Debug, Clone, Default)]
+pub struct SanitizeReport {
+    /// Elements removed from the DOM, in the order they were processed.
+    pub removed_elements: Vec<RemovedElement>,
+    /// The number of attributes removed from the kept elements.
+    pub removed_attrs: usize,
+    /// The number of attributes on the kept elements whose values were rewritten by the policy,
+    /// or that were added by it (e.g. by attribute promotion).
+    pub modified_attrs: usize,
+    /// The remaining number of bytes of markup to capture, if capturing is enabled.
+    markup_budget: Option<usize>,
+    /// The point in time after which sanitization is aborted, if set.
+    deadline: Option<Instant>,
+    /// The number of elements visited, used to check the deadline periodically.
+    visited: usize,
+    /// Whether sanitization was aborted because the deadline passed.
+    pub(crate) timed_out: bool,
+}
+
+/// The number of elements visited between two checks of the deadline.
+const DEADLINE_CHECK_INTERVAL: usize = 256;
+
+impl SanitizeReport {
+    /// Creates a new empty [`SanitizeReport`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new empty [`SanitizeReport`] that captures the markup of removed elements,
+    /// until their total size reaches `max_bytes`. Elements removed after the limit was reached
+    /// are still reported, but without markup.
+    pub fn with_markup(max_bytes: usize) -> Self {
+        Self {
+            markup_budget: Some(max_bytes),
+            ..Self::default()
+        }
+    }
+
+    /// Creates a new empty [`SanitizeReport`] that aborts sanitization once the `deadline` passed.
+    pub(crate) fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..Self::default()
+        }
+    }
+
+    /// Counts a visited element and returns `true` if sanitization must be aborted.
+    ///
+    /// The clock is only read every [`DEADLINE_CHECK_INTERVAL`] elements, starting with the first one.
+    pub(crate) fn is_past_deadline(&mut self) -> bool {
+        let Some(deadline) = self.deadline else {
+            return false;
+        };
+        if !self.timed_out && self.visited % DEADLINE_CHECK_INTERVAL == 0 {
+            self.timed_out = Instant::now() >= deadline;
+        }
+        self.visited += 1;
+        self.timed_out
+    }
+
+    /// Returns `true` if sanitization removed or altered anything.
+    pub fn is_changed(&self) -> bool {
+        !self.removed_elements.is_empty() || self.removed_attrs > 0 || self.modified_attrs > 0
+    }
+
+    pub(crate) fn record_element(&mut self, node: &NodeRef, kind: RemovalKind) {
+        let Some(name) = node
+            .qual_name_ref()
+            .map(|qual_name| qual_name.local.clone())
+        else {
+            return;
+        };
+        let markup = self.capture_markup(node, kind);
+        self.removed_elements.push(RemovedElement {
+            name,
+            kind,
+            line: None,
+            markup,
+            raw_text: None,
+            id: node.id,
+        });
+    }
+
+    /// Stores the text of the last recorded element, if it is a `script` or `style` element.
+    pub(crate) fn capture_raw_text(&mut self, node: &NodeRef) {
+        let Some(removed) = self.removed_elements.last_mut() else {
+            return;
+        };
+        if removed.id == node.id && is_raw_text_element(node) {
+            removed.raw_text = Some(node.text());
+        }
+    }
+
+    fn capture_markup(&mut self, node: &NodeRef, kind: RemovalKind) -> Option<StrTendril> {
+        let budget = self.markup_budget.as_mut()?;
+        let markup = match kind {
+            RemovalKind::Unwrapped => {
+                let mut start_tag = String::new();
+                crate::pretty::write_start_tag(node, &mut start_tag);
+                StrTendril::from(start_tag)
+            }
+            RemovalKind::Removed | RemovalKind::Escaped => node.html(),
+        };
+        if markup.len() > *budget {
+            *budget = 0;
+            return None;
+        }
+        *budget -= markup.len();
+        Some(markup)
+    }
+}
+
+fn is_raw_text_element(node: &NodeRef) -> bool {
+    node.qual_name_ref().is_some_and(|qual_name| {
+        qual_name.ns == ns!(html)
+            && matches!(
+                qual_name.local,
+                local_name!("script") | local_name!("style")
+            )
+    })
+}
@@ -0,0 +1,56 @@
+//! Options controlling how the sanitized HTML is serialized.
+
+use dom_query::Document;
+use tendril::StrTendril;
+
+/// Options for [`crate::Policy::sanitize_html_with`] and
+/// [`crate::plugin_policy::PluginPolicy::sanitize_html_with`].
+///
+/// The default options produce the same output as `sanitize_html`.
+/// Attribute quoting and the serialization of void elements are fixed by the HTML serializer
+/// (values are always double-quoted, void elements have no closing tag) and cannot be changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Whether to keep the doctype of the document in the output. Default: `true`.
+    pub include_doctype: bool,
+    /// Whether to serialize only the contents of `body`, dropping `html`, `head` and the doctype.
+    /// If the document has no `body`, the whole document is serialized. Default: `false`.
+    pub body_only: bool,
+    /// Whether to indent the output, like `sanitize_html_pretty`. Default: `false`.
+    pub pretty: bool,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self {
+            include_doctype: true,
+            body_only: false,
+            pretty: false,
+        }
+    }
+}
+
+impl SerializeOptions {
+    /// Serializes the document according to the options.
+    ///
+    /// Removing the doctype modifies the document.
+    pub(crate) fn serialize(&self, doc: &Document) -> StrTendril {
+        if !self.include_doctype {
+            let doctypes: Vec<_> = doc
+                .root()
+                .children_it(false)
+                .filter(|child| child.is_doctype())
+                .collect();
+            for doctype in doctypes {
+                doctype.remove_from_parent();
+            }
+        }
+        let body = if self.body_only { doc.body() } else { None };
+        match (body, self.pretty) {
+            (Some(body), true) => crate::pretty::pretty_html(&body),
+            (Some(body), false) => body.inner_html(),
+            (None, true) => crate::pretty::pretty_html(&doc.root()),
+            (None, false) => doc.html(),
+        }
+    }
+}
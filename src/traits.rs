@@ -1,4 +1,6 @@
-use dom_query::NodeRef;
+use dom_query::{Document, NodeRef};
+
+use crate::report::SanitizeReport;
 
 /// A trait for sanitization directives, defines methods for node and attribute sanitization.
 pub trait SanitizeDirective {
@@ -6,6 +8,18 @@ pub trait SanitizeDirective {
     fn sanitize_node(policy: &impl SanitizePolicy, node: &NodeRef)
     where
         Self: Sized;
+    /// Sanitizes a node like [`SanitizeDirective::sanitize_node`], recording the changes into the report.
+    ///
+    /// The default implementation doesn't record anything.
+    fn sanitize_node_report(
+        policy: &impl SanitizePolicy,
+        node: &NodeRef,
+        _report: &mut SanitizeReport,
+    ) where
+        Self: Sized,
+    {
+        Self::sanitize_node(policy, node);
+    }
     /// Sanitizes the attributes of a node by removing or retaining them based on the policy.
     fn sanitize_node_attrs(policy: &impl SanitizePolicy, node: &dom_query::NodeRef)
     where
@@ -26,4 +40,113 @@ pub trait SanitizePolicy {
         F: FnOnce(&NodeRef, &[&str]);
     /// A policy instance doesn't have any special exclusions.
     fn is_empty(&self) -> bool;
+    /// Whether the policy has rules that may remove, unwrap or change elements, rather than only their attributes.
+    ///
+    /// If it returns `false`, the [`crate::Permissive`] directive only walks the elements to sanitize
+    /// their attributes. The default implementation conservatively returns `true`.
+    fn has_element_rules(&self) -> bool {
+        true
+    }
+    /// Whether the structural elements (`html`, `head` and `body`) may be removed
+    /// like any other element. By default they are always kept.
+    fn allow_strip_structural(&self) -> bool {
+        false
+    }
+    /// Whether the policy marks some elements as trusted.
+    fn has_trust_marker(&self) -> bool {
+        false
+    }
+    /// Whether the node is trusted: it is kept with its whole subtree, without being sanitized.
+    fn is_trusted(&self, _node: &NodeRef) -> bool {
+        false
+    }
+    /// Whether the text of removed `script` and `style` elements is collected in reports.
+    fn collect_removed_scripts(&self) -> bool {
+        false
+    }
+    /// Whether the policy has attributes that must be removed regardless of the directive.
+    fn has_attrs_to_strip(&self) -> bool {
+        false
+    }
+    /// Whether the attribute must be removed from the node regardless of the directive.
+    fn should_strip_attr(&self, _node: &NodeRef, _attr_name: &str) -> bool {
+        false
+    }
+    /// Adjusts the attributes of a kept element before the attribute rules are applied.
+    fn prepare_attrs(&self, _node: &NodeRef) {}
+    /// Rewrites the values of the attributes that survived the exclusion.
+    fn rewrite_attrs(&self, _node: &NodeRef) {}
+    /// Whether the policy has elements that should be removed when they are left empty.
+    fn has_elements_to_prune(&self) -> bool {
+        false
+    }
+    /// Whether node should be removed from the DOM if it has no meaningful content after sanitization.
+    fn should_prune(&self, _node: &NodeRef) -> bool {
+        false
+    }
+    /// Whether the policy has wrapper elements that should be collapsed into a nested element with the same name.
+    fn has_elements_to_collapse(&self) -> bool {
+        false
+    }
+    /// Whether the node should be collapsed into its only child element if that has the same name.
+    fn should_collapse(&self, _node: &NodeRef) -> bool {
+        false
+    }
+    /// Whether the policy limits the number of kept elements.
+    fn has_element_limits(&self) -> bool {
+        false
+    }
+    /// The maximum number of elements with the same name as the node that may be kept.
+    fn element_limit(&self, _node: &NodeRef) -> Option<usize> {
+        None
+    }
+    /// The maximum number of elements kept in document order, the rest of the content is removed.
+    fn max_elements(&self) -> Option<usize> {
+        None
+    }
+    /// The maximum length of the text kept in document order, in bytes, the rest of the content is removed.
+    fn max_total_text(&self) -> Option<usize> {
+        None
+    }
+    /// Whether node should be unwrapped, i.e. replaced by its children, even if the directive would keep it.
+    fn should_unwrap(&self, _node: &NodeRef) -> bool {
+        false
+    }
+    /// Whether node should be replaced with a text node containing its escaped markup.
+    fn should_escape(&self, _node: &NodeRef) -> bool {
+        false
+    }
+    /// Called before the element node is unwrapped, i.e. removed while its children are kept.
+    fn before_unwrap(&self, _node: &NodeRef) {}
+    /// Renames the element node if the policy requires it. Called before any other rule is applied to the node.
+    fn rename_element(&self, _node: &NodeRef) {}
+    /// Post-processes the text nodes under the node, after its elements were sanitized and normalized.
+    fn sanitize_text(&self, _node: &NodeRef) {}
+    /// Removes the comments under the node that the policy doesn't keep. By default all comments are kept.
+    fn sanitize_comments(&self, _node: &NodeRef) {}
+    /// Filters the CSS source of the `style` elements under the node, if the policy requires it.
+    fn sanitize_styles(&self, _node: &NodeRef) {}
+    /// Runs the policy's custom finishing step on the node, after all other rules were applied.
+    fn finalize(&self, _node: &NodeRef) {}
+}
+
+/// An object-safe trait implemented by every policy type.
+///
+/// [`crate::Policy`] and [`crate::plugin_policy::PluginPolicy`] are different concrete types,
+/// parameterized by their directive. `Sanitizer` hides these differences, so policies
+/// can be used as `&dyn Sanitizer` (e.g. in a [`crate::PolicyChain`]).
+pub trait Sanitizer {
+    /// Sanitizes a node by applying the policy rules according to the directive type.
+    fn sanitize_node(&self, node: &NodeRef);
+
+    /// Sanitizes the [`dom_query::Document`].
+    fn sanitize_document(&self, document: &Document) {
+        self.sanitize_node(&document.root());
+    }
+}
+
+impl<S: Sanitizer + ?Sized> Sanitizer for &S {
+    fn sanitize_node(&self, node: &NodeRef) {
+        (**self).sanitize_node(node);
+    }
 }
@@ -0,0 +1,117 @@
+//! Built-in transforms for attribute values.
+
+/// A named transform applied to the value of a surviving attribute.
+///
+/// Transforms are applied in the order they are listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttrTransform {
+    /// Removes leading and trailing whitespace.
+    Trim,
+    /// Converts the value to lowercase.
+    Lowercase,
+    /// Removes ASCII control characters (`U+0000`–`U+001F` and `U+007F`), including tab,
+    /// line feed and carriage return.
+    ///
+    /// This is security-relevant: browsers ignore these characters in URLs, so they are used
+    /// to break up schemes like `java\nscript:` and bypass naive scheme checks.
+    StripControlChars,
+    /// Replaces every run of whitespace with a single space.
+    CollapseWhitespace,
+    /// Replaces the value with an empty string, e.g. to render boolean attributes as `disabled=""`.
+    Clear,
+}
+
+impl AttrTransform {
+    /// Applies the transform to the value.
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            AttrTransform::Trim => value.trim().to_string(),
+            AttrTransform::Lowercase => value.to_lowercase(),
+            AttrTransform::StripControlChars => {
+                value.chars().filter(|c| !c.is_ascii_control()).collect()
+            }
+            AttrTransform::CollapseWhitespace => collapse_whitespace(value),
+            AttrTransform::Clear => String::new(),
+        }
+    }
+}
+
+/// Transforms normalizing the whitespace of a value: runs of whitespace are collapsed
+/// to a single space and the value is trimmed.
+pub const NORMALIZE_WHITESPACE: &[AttrTransform] =
+    &[AttrTransform::CollapseWhitespace, AttrTransform::Trim];
+
+/// Attributes whose values are sets of whitespace-separated tokens,
+/// so normalizing their whitespace does not change their meaning.
+///
+/// `style` and URL attributes (including `srcset` and `ping`) are deliberately not listed.
+pub const WHITESPACE_SEPARATED_ATTRS: &[&str] = &[
+    "class",
+    "rel",
+    "rev",
+    "headers",
+    "accesskey",
+    "sandbox",
+    "itemprop",
+    "itemref",
+    "aria-controls",
+    "aria-describedby",
+    "aria-labelledby",
+    "aria-owns",
+];
+
+/// The transform canonicalizing the value of a boolean attribute to an empty string.
+pub const CLEAR_VALUE: &[AttrTransform] = &[AttrTransform::Clear];
+
+/// Boolean attributes of HTML elements: their presence means `true`, whatever their value.
+pub const BOOLEAN_ATTRS: &[&str] = &[
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+];
+
+/// Applies all transforms to the value in order.
+pub(crate) fn apply_transforms(transforms: &[AttrTransform], value: &str) -> String {
+    transforms
+        .iter()
+        .fold(value.to_string(), |acc, transform| transform.apply(&acc))
+}
+
+fn collapse_whitespace(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut prev_whitespace = false;
+    for c in value.chars() {
+        if c.is_whitespace() {
+            if !prev_whitespace {
+                result.push(' ');
+            }
+            prev_whitespace = true;
+        } else {
+            result.push(c);
+            prev_whitespace = false;
+        }
+    }
+    result
+}
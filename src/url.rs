@@ -0,0 +1,91 @@
+//! URL helpers shared by the attribute checkers.
+//!
+//! [`normalized_scheme`] is the primitive to build custom checkers on,
+//! instead of matching the raw attribute value with prefixes like `javascript:`.
+
+/// Returns `true` if the value is a `data:` URI of an image (except `image/svg+xml`)
+/// whose decoded size doesn't exceed `max_bytes`.
+///
+/// The size of base64 data is estimated from its length, without decoding.
+pub(crate) fn is_small_data_image(value: &str, max_bytes: usize) -> bool {
+    let value = value.trim_start();
+    let Some(rest) = strip_prefix_ignore_ascii_case(value, "data:") else {
+        return false;
+    };
+    let Some((media_type, data)) = rest.split_once(',') else {
+        return false;
+    };
+    let mut params = media_type.split(';');
+    let mime = params.next().unwrap_or_default().trim();
+    let Some(subtype) = strip_prefix_ignore_ascii_case(mime, "image/") else {
+        return false;
+    };
+    if subtype.is_empty() || subtype.eq_ignore_ascii_case("svg+xml") {
+        return false;
+    }
+    let is_base64 = params.any(|p| p.trim().eq_ignore_ascii_case("base64"));
+    let size = if is_base64 {
+        base64_decoded_len(data)
+    } else {
+        data.len()
+    };
+    size <= max_bytes
+}
+
+/// Estimates the decoded length of base64 data, ignoring whitespace and padding.
+fn base64_decoded_len(data: &str) -> usize {
+    let len = data
+        .bytes()
+        .filter(|b| *b != b'=' && !b.is_ascii_whitespace())
+        .count();
+    len * 3 / 4
+}
+
+fn strip_prefix_ignore_ascii_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    if s.len() >= prefix.len()
+        && s.is_char_boundary(prefix.len())
+        && s[..prefix.len()].eq_ignore_ascii_case(prefix)
+    {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Extracts the scheme of a URL, normalized the way browsers do before resolving it.
+///
+/// The attribute value is expected as the parser delivers it: character references
+/// such as `&#x6a;` are already decoded. The normalization is:
+/// - every ASCII whitespace character (tab, line feed, form feed, carriage return, space)
+///   and every ASCII control character (`U+0000`–`U+001F`, `U+007F`) is removed from the whole value,
+///   so `" java\tscript:"` yields `javascript`;
+/// - the scheme is lowercased (ASCII only).
+///
+/// Returns `None` if the value has no scheme, i.e. it is a relative URL, or if the part
+/// before the first `:` is not a valid scheme (it must start with an ASCII letter, followed by
+/// ASCII letters, digits, `+`, `-` or `.`). Non-ASCII characters are never stripped,
+/// so a scheme containing them is not valid.
+///
+/// # Examples
+///
+/// ```
+/// use dom_sanitizer::url::normalized_scheme;
+///
+/// assert_eq!(normalized_scheme(" JaVa\nScRiPt:alert(1)").as_deref(), Some("javascript"));
+/// assert_eq!(normalized_scheme("https://example.com").as_deref(), Some("https"));
+/// assert_eq!(normalized_scheme("/relative/path"), None);
+/// assert_eq!(normalized_scheme("images/a:b.png"), None);
+/// ```
+pub fn normalized_scheme(value: &str) -> Option<String> {
+    let mut scheme = String::new();
+    for c in value.chars() {
+        match c {
+            c if c.is_ascii_whitespace() || c.is_ascii_control() => continue,
+            ':' if !scheme.is_empty() => return Some(scheme),
+            c if c.is_ascii_alphabetic() => scheme.push(c.to_ascii_lowercase()),
+            '0'..='9' | '+' | '-' | '.' if !scheme.is_empty() => scheme.push(c),
+            _ => return None,
+        }
+    }
+    None
+}
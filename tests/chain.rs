@@ -0,0 +1,89 @@
+use dom_query::Document;
+use dom_sanitizer::plugin_policy::{preset, PluginPolicy};
+use dom_sanitizer::traits::Sanitizer;
+use dom_sanitizer::{AllowAllPolicy, DenyAllPolicy, Permissive, PolicyChain};
+
+mod data;
+use data::PARAGRAPH_CONTENTS;
+
+#[test]
+fn test_policy_chain() {
+    // structural cleanup: keep only `p`, `a` and `mark`, keep `href` and `role` attributes
+    let structure = DenyAllPolicy::builder()
+        .exclude_elements(&["p", "a", "mark"])
+        .exclude_attrs(&["href", "role"])
+        .build();
+    // attribute cleanup: drop `role` everywhere
+    let attrs = AllowAllPolicy::builder().exclude_attrs(&["role"]).build();
+    // custom logic: unwrap `mark` elements
+    let plugin: PluginPolicy<Permissive> = PluginPolicy::builder()
+        .exclude(preset::LocalNameMatcher::new("mark"))
+        .build();
+
+    let chain = PolicyChain::new()
+        .then(&structure)
+        .then(&attrs)
+        .then(&plugin);
+    assert_eq!(chain.len(), 3);
+
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    chain.sanitize_document(&doc);
+
+    assert!(!doc.select("div").exists());
+    assert!(!doc.select("[role]").exists());
+    assert!(!doc.select("mark").exists());
+    assert_eq!(doc.select("p > a[href]").length(), 3);
+    assert!(doc.html().contains("highlighted text"));
+}
+
+#[test]
+fn test_policy_chain_order() {
+    // The first policy removes `a` elements entirely,
+    // so the second one has nothing to unwrap.
+    let remove_links = AllowAllPolicy::builder().remove_elements(&["a"]).build();
+    let unwrap_links = AllowAllPolicy::builder().exclude_elements(&["a"]).build();
+
+    let chain = PolicyChain::new().then(&remove_links).then(&unwrap_links);
+    let html = chain.sanitize_html(PARAGRAPH_CONTENTS);
+    assert!(!html.contains("the first link"));
+
+    let chain = PolicyChain::new().then(&unwrap_links).then(&remove_links);
+    let html = chain.sanitize_html(PARAGRAPH_CONTENTS);
+    assert!(html.contains("the first link"));
+}
+
+#[test]
+fn test_sanitizer_trait_objects() {
+    let policy = DenyAllPolicy::builder().exclude_elements(&["p"]).build();
+    let plugin: PluginPolicy<Permissive> = PluginPolicy::builder()
+        .remove(preset::LocalNameMatcher::new("p"))
+        .build();
+    let policies: Vec<&dyn Sanitizer> = vec![&policy, &plugin];
+
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    for policy in policies {
+        policy.sanitize_document(&doc);
+    }
+    assert!(!doc.select("p").exists());
+    assert!(!doc.select("a").exists());
+}
+
+#[test]
+fn test_clean() {
+    let policy = DenyAllPolicy::builder().exclude_elements(&["p"]).build();
+    assert_eq!(
+        dom_sanitizer::clean(PARAGRAPH_CONTENTS, &policy),
+        policy.sanitize_html(PARAGRAPH_CONTENTS)
+    );
+
+    let plugin: PluginPolicy<Permissive> = PluginPolicy::builder()
+        .remove(preset::LocalNameMatcher::new("a"))
+        .build();
+    let chain = PolicyChain::new().then(&plugin).then(&policy);
+    let html = dom_sanitizer::clean(PARAGRAPH_CONTENTS, &chain);
+    assert!(html.contains("<p>The first paragraph contains .</p>"));
+
+    let sanitizer: &dyn Sanitizer = &plugin;
+    let html = dom_sanitizer::clean("<p>Text <a href=\"/\">link</a></p>", sanitizer);
+    assert!(html.contains("<p>Text </p>"));
+}
@@ -32,3 +32,33 @@ pub static SVG_CONTENTS: &str = r#"
         <div class="text">Some other text</div>
     </body>
 </html>"#;
+
+pub static FOREIGN_CONTENTS: &str = r#"
+<!DOCTYPE html>
+<html>
+    <head><title>Test</title></head>
+    <body>
+        <svg viewBox="0 0 100 100"><circle cx="50" cy="50" r="30" /><text>SVG text</text></svg>
+        <math><mi>x</mi><mo>=</mo><mn>2</mn></math>
+        <p>Some text</p>
+    </body>
+</html>"#;
+
+pub static MATHML_CONTENTS: &str = r#"
+<!DOCTYPE html>
+<html>
+    <head><title>Test</title></head>
+    <body>
+        <math display="block" onmouseover="alert(1)">
+            <maction actiontype="toggle" onclick="alert(1)">
+                <mrow href="javascript:alert(1)"><mi mathvariant="bold">x</mi><mo stretchy="false">=</mo><mn>2</mn></mrow>
+            </maction>
+            <semantics>
+                <mfrac><mn>1</mn><mn>2</mn></mfrac>
+                <annotation encoding="application/x-tex">\frac{1}{2}</annotation>
+                <annotation-xml encoding="text/html"><img src="x" onerror="alert(1)"></annotation-xml>
+            </semantics>
+        </math>
+        <p>Some text</p>
+    </body>
+</html>"#;
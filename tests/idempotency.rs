@@ -0,0 +1,94 @@
+//! Property test: sanitizing the output of a policy again must not change it.
+
+use dom_sanitizer::{AllowAllPolicy, DenyAllPolicy};
+
+// Elements whose unwrapping never produces markup the parser would restructure.
+// Tables, lists, headings, `pre`/`textarea` and foreign content are not covered by the guarantee.
+const ELEMENTS: &[&str] = &[
+    "div", "p", "span", "b", "i", "em", "a", "br", "img", "script", "style", "title", "noscript",
+];
+const ATTRS: &[&str] = &["id", "class", "href", "src", "style", "title", "onclick"];
+const TEXTS: &[&str] = &["foo", " ", "  bar  ", "\n", "a<b", "x & y", ""];
+
+/// A xorshift generator, so a failure is reproducible from its seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    fn subset<'a>(&mut self, items: &[&'a str]) -> Vec<&'a str> {
+        items
+            .iter()
+            .copied()
+            .filter(|_| self.below(3) == 0)
+            .collect()
+    }
+}
+
+fn gen_html(rng: &mut Rng, depth: usize, out: &mut String) {
+    for _ in 0..rng.below(4) + 1 {
+        if depth == 0 || rng.below(3) == 0 {
+            out.push_str(rng.pick(TEXTS));
+            if rng.below(8) == 0 {
+                out.push_str("<!-- c -->");
+            }
+            continue;
+        }
+        let name = rng.pick(ELEMENTS);
+        out.push('<');
+        out.push_str(name);
+        for _ in 0..rng.below(3) {
+            out.push_str(&format!(" {}=\"v{}\"", rng.pick(ATTRS), rng.below(3)));
+        }
+        out.push('>');
+        gen_html(rng, depth - 1, out);
+        // some elements are left unclosed to exercise the parser's error recovery
+        if rng.below(6) != 0 {
+            out.push_str(&format!("</{name}>"));
+        }
+    }
+}
+
+#[test]
+fn test_sanitize_html_is_idempotent() {
+    for seed in 1..=1000u64 {
+        let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let mut html = String::new();
+        gen_html(&mut rng, 4, &mut html);
+
+        let exclude_elements = rng.subset(ELEMENTS);
+        let remove_elements = rng.subset(ELEMENTS);
+        let exclude_attrs = rng.subset(ATTRS);
+
+        let permissive = AllowAllPolicy::builder()
+            .exclude_elements(&exclude_elements)
+            .remove_elements(&remove_elements)
+            .exclude_attrs(&exclude_attrs)
+            .build();
+        let once = permissive.sanitize_html(html.as_str());
+        let twice = permissive.sanitize_html(once.clone());
+        assert_eq!(once, twice, "permissive, seed {seed}, input: {html}");
+
+        let restrictive = DenyAllPolicy::builder()
+            .exclude_elements(&exclude_elements)
+            .remove_elements(&remove_elements)
+            .exclude_attrs(&exclude_attrs)
+            .build();
+        let once = restrictive.sanitize_html(html.as_str());
+        let twice = restrictive.sanitize_html(once.clone());
+        assert_eq!(once, twice, "restrictive, seed {seed}, input: {html}");
+    }
+}
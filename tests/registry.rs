@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::thread;
+
+use dom_query::Document;
+use dom_sanitizer::plugin_policy::{preset, PluginPolicy};
+use dom_sanitizer::{AllowAllPolicy, DenyAllPolicy, Permissive, PolicyRegistry};
+
+#[test]
+fn test_registry_sanitize_with() {
+    let mut registry = PolicyRegistry::new();
+    assert!(registry.is_empty());
+
+    registry.register(
+        "comments",
+        DenyAllPolicy::builder().exclude_elements(&["p"]).build(),
+    );
+    let plugin: PluginPolicy<Permissive> = PluginPolicy::builder()
+        .remove(preset::LocalNameMatcher::new("script"))
+        .build();
+    registry.register("articles", plugin);
+    assert_eq!(registry.len(), 2);
+    assert!(registry.contains("comments"));
+
+    let contents = r#"<div><p>Text</p><script>alert(1)</script></div>"#;
+
+    let doc = Document::from(contents);
+    assert!(registry.sanitize_with("comments", &doc));
+    assert!(!doc.select("div").exists());
+    assert!(doc.select("p").exists());
+
+    let doc = Document::from(contents);
+    assert!(registry.sanitize_with("articles", &doc));
+    assert!(doc.select("div > p").exists());
+    assert!(!doc.select("script").exists());
+
+    let doc = Document::from(contents);
+    assert!(!registry.sanitize_with("unknown", &doc));
+    assert!(doc.select("script").exists());
+}
+
+#[test]
+fn test_registry_replace() {
+    let mut registry = PolicyRegistry::new();
+    assert!(registry
+        .register("default", AllowAllPolicy::builder().build())
+        .is_none());
+    assert!(registry
+        .register("default", DenyAllPolicy::builder().build())
+        .is_some());
+    assert_eq!(registry.len(), 1);
+    let html = registry.sanitize_html_with("default", "<div><b>Text</b></div>");
+    assert!(!html.unwrap().contains("<b>"));
+}
+
+#[test]
+fn test_registry_shared_between_threads() {
+    let mut registry = PolicyRegistry::new();
+    registry.register(
+        "no_links",
+        AllowAllPolicy::builder().exclude_elements(&["a"]).build(),
+    );
+    let registry = Arc::new(registry);
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let registry = Arc::clone(&registry);
+            thread::spawn(move || {
+                let html = format!(r#"<p><a href="/{i}">Link {i}</a></p>"#);
+                registry
+                    .sanitize_html_with("no_links", html)
+                    .unwrap()
+                    .to_string()
+            })
+        })
+        .collect();
+    for (i, handle) in handles.into_iter().enumerate() {
+        let html = handle.join().unwrap();
+        assert!(html.contains(&format!("<p>Link {i}</p>")));
+    }
+}